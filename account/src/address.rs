@@ -0,0 +1,101 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::KeyError;
+
+/// Every address starts with this prefix
+pub const ADDRESS_PREFIX: &str = "etnl:";
+
+/// The 20 byte account address, derived from the sha256 hash of the compressed public key.
+///
+/// It is displayed as `etnl:` followed by 40 hex characters using mixed case as checksum:
+/// a letter is uppercase if the matching nibble of `sha256(lowercase hex)` is 8 or higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address([u8; 20]);
+
+impl Address {
+    /// Will derive the address of a compressed (33 bytes) public key
+    pub fn from_public_key_bytes(public_key: &[u8]) -> Self {
+        let hash = Sha256::digest(public_key);
+        let mut bytes = [0; 20];
+        bytes.copy_from_slice(&hash[hash.len() - 20..]);
+        Self(bytes)
+    }
+
+    /// Will wrap the raw address bytes
+    pub fn from_bytes(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    /// Will return the raw address bytes
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Will return the hex part of the address with checksum casing applied
+    fn checksummed_hex(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = Sha256::digest(lower.as_bytes());
+
+        lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", ADDRESS_PREFIX, self.checksummed_hex())
+    }
+}
+
+impl FromStr for Address {
+    type Err = KeyError;
+
+    /// Will parse an address, rejecting a wrong prefix, length, characters or checksum
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| KeyError::InvalidAddress(format!("`{}`: {}", s, reason));
+
+        let hex_part = s
+            .strip_prefix(ADDRESS_PREFIX)
+            .ok_or_else(|| invalid("missing `etnl:` prefix"))?;
+        if hex_part.len() != 40 || !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid("expected 40 hex characters"));
+        }
+
+        let mut bytes = [0; 20];
+        hex::decode_to_slice(hex_part.to_ascii_lowercase(), &mut bytes)
+            .map_err(|_| invalid("expected 40 hex characters"))?;
+
+        let address = Self(bytes);
+        if address.checksummed_hex() != hex_part {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        Ok(address)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,95 @@
+use thiserror::Error;
+
+use crate::StoreKey;
+
+/// Errors that can occur while handling keys
+#[derive(Debug, Error)]
+pub enum KeyError {
+    #[error("The private key is not a valid secp256k1 secret key")]
+    InvalidPrivateKey(#[source] secp256k1::Error),
+
+    #[error("The public key is not a valid secp256k1 public key")]
+    InvalidPublicKey(#[source] secp256k1::Error),
+
+    #[error("Invalid multisig account: {0}")]
+    InvalidMultisig(String),
+
+    #[error("Invalid hex encoding")]
+    InvalidHex(#[source] hex::FromHexError),
+
+    #[error("Invalid address {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid mnemonic")]
+    InvalidMnemonic(#[source] bip39::Error),
+
+    #[error("Invalid derivation path `{0}`")]
+    InvalidDerivationPath(String),
+
+    #[error("Child key {0} is invalid, use the next index")]
+    InvalidChildKey(u32),
+
+    #[error("Could not encrypt the keystore")]
+    Encryption,
+
+    #[error("Could not decrypt the keystore (wrong password or corrupted file)")]
+    Decryption,
+
+    #[error("Unsupported keystore: {0}")]
+    UnsupportedKeystore(String),
+
+    #[error("The keystore uses {rounds} kdf rounds, allowed are {min} to {max}")]
+    InvalidKdfRounds { rounds: u32, min: u32, max: u32 },
+
+    #[error("The keystore address {expected} does not belong to its key ({actual})")]
+    AddressMismatch { expected: String, actual: String },
+
+    #[error("Malformed keystore file")]
+    Format(#[source] serde_json::Error),
+
+    #[error("Could not access the keystore file")]
+    Io(#[from] std::io::Error),
+}
+
+impl KeyError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            KeyError::InvalidPrivateKey(_) => 7384920341,
+            KeyError::InvalidHex(_) => 7384920342,
+            KeyError::Encryption => 7384920343,
+            KeyError::Decryption => 7384920344,
+            KeyError::UnsupportedKeystore(_) => 7384920345,
+            KeyError::Format(_) => 7384920346,
+            KeyError::Io(_) => 7384920347,
+            KeyError::InvalidAddress(_) => 7384920348,
+            KeyError::InvalidMnemonic(_) => 7384920349,
+            KeyError::InvalidDerivationPath(_) => 7384920350,
+            KeyError::InvalidChildKey(_) => 7384920351,
+            KeyError::InvalidPublicKey(_) => 7384920352,
+            KeyError::InvalidMultisig(_) => 7384920353,
+            KeyError::InvalidKdfRounds { .. } => 7384920354,
+            KeyError::AddressMismatch { .. } => 7384920355,
+        }
+    }
+}
+
+/// Errors raised while accessing the storage of an account
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("`{caller}` can not write to the namespace of `{namespace}`")]
+    AccessDenied { caller: String, namespace: String },
+
+    #[error("The value of `{}` in `{}` has an unexpected type", hex::encode(&.0.key), .0.namespace)]
+    InvalidValue(StoreKey),
+}
+
+impl StoreError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            StoreError::AccessDenied { .. } => 7384920360,
+            StoreError::InvalidValue(_) => 7384920361,
+        }
+    }
+}
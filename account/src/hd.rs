@@ -0,0 +1,277 @@
+use std::{fmt, str::FromStr};
+
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{KeyError, Wallet};
+
+/// The coin type used in the derivation path of Eternal accounts (not registered in SLIP-44)
+pub const ETERNAL_COIN_TYPE: u32 = 8389;
+
+/// Child indices from this value on are hardened
+pub const HARDENED: u32 = 0x8000_0000;
+
+const MASTER_KEY_SALT: &[u8] = b"Bitcoin seed";
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// Will generate a new random english mnemonic with the given amount of words (12, 15, 18, 21 or 24)
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic, KeyError> {
+    Mnemonic::generate_in(Language::English, word_count).map_err(KeyError::InvalidMnemonic)
+}
+
+/// Will parse and validate (word list and checksum) an english mnemonic
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, KeyError> {
+    Mnemonic::parse_in(Language::English, phrase).map_err(KeyError::InvalidMnemonic)
+}
+
+/// A BIP32 derivation path like `m/44'/8389'/0'/0/0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Will return the path of the n-th Eternal account: `m/44'/8389'/account'/0/0`.
+    ///
+    /// The account is a hardened index, it has to be below [`HARDENED`].
+    pub fn eternal(account: u32) -> Result<Self, KeyError> {
+        if account >= HARDENED {
+            return Err(KeyError::InvalidDerivationPath(format!(
+                "m/44'/{}'/{}'/0/0",
+                ETERNAL_COIN_TYPE, account
+            )));
+        }
+
+        Ok(Self(vec![
+            44 | HARDENED,
+            ETERNAL_COIN_TYPE | HARDENED,
+            account | HARDENED,
+            0,
+            0,
+        ]))
+    }
+
+    /// Will return the child indices of the path
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || KeyError::InvalidDerivationPath(s.to_string());
+
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+
+        parts
+            .map(|part| {
+                let (index, hardened) = match part.strip_suffix(['\'', 'h', 'H']) {
+                    Some(index) => (index, true),
+                    None => (part, false),
+                };
+                let index: u32 = index.parse().map_err(|_| invalid())?;
+                if index >= HARDENED {
+                    return Err(invalid());
+                }
+
+                Ok(if hardened { index | HARDENED } else { index })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            if index & HARDENED != 0 {
+                write!(f, "/{}'", index & !HARDENED)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A BIP32 extended private key on secp256k1. The key material is wiped on drop.
+#[derive(Clone)]
+pub struct ExtendedPrivateKey {
+    secret: Zeroizing<[u8; 32]>,
+    chain_code: Zeroizing<[u8; 32]>,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+}
+
+impl ExtendedPrivateKey {
+    /// Will derive the master key from a BIP39 seed
+    pub fn from_seed(seed: &[u8]) -> Result<Self, KeyError> {
+        let (secret, chain_code) = hmac_sha512(MASTER_KEY_SALT, &[seed]);
+        // Make sure the left half is a valid secret key
+        Self::secret_key(&secret)?;
+
+        Ok(Self {
+            secret,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+        })
+    }
+
+    /// Will derive the master key from a mnemonic and an optional passphrase
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self, KeyError> {
+        let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+        Self::from_seed(seed.as_ref())
+    }
+
+    /// Will derive the child key with the given index, indices from [`HARDENED`] on are hardened
+    pub fn derive_child(&self, index: u32) -> Result<Self, KeyError> {
+        let secret_key = Self::secret_key(&self.secret)?;
+        let index_bytes = index.to_be_bytes();
+
+        let (tweak, chain_code) = if index & HARDENED != 0 {
+            hmac_sha512(
+                self.chain_code.as_ref(),
+                &[&[0], self.secret.as_ref(), &index_bytes],
+            )
+        } else {
+            hmac_sha512(
+                self.chain_code.as_ref(),
+                &[&self.public_key().serialize(), &index_bytes],
+            )
+        };
+
+        let tweak = Scalar::from_be_bytes(*tweak).map_err(|_| KeyError::InvalidChildKey(index))?;
+        let child = secret_key
+            .add_tweak(&tweak)
+            .map_err(|_| KeyError::InvalidChildKey(index))?;
+
+        Ok(Self {
+            secret: Zeroizing::new(child.secret_bytes()),
+            chain_code,
+            depth: self.depth.saturating_add(1),
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+        })
+    }
+
+    /// Will derive the key at the given path, relative to this key
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, KeyError> {
+        path.indices()
+            .iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    /// Will return the public key of this extended key
+    pub fn public_key(&self) -> PublicKey {
+        // The secret is validated whenever an extended key is created
+        let secret_key = Self::secret_key(&self.secret).expect("valid extended secret key");
+        PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key)
+    }
+
+    /// Will return the first four bytes of `ripemd160(sha256(public key))`
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let hash = Ripemd160::digest(Sha256::digest(self.public_key().serialize()));
+        let mut fingerprint = [0; 4];
+        fingerprint.copy_from_slice(&hash[..4]);
+        fingerprint
+    }
+
+    /// Will turn the derived key into a wallet
+    pub fn wallet(&self) -> Result<Wallet, KeyError> {
+        Wallet::from_secret_bytes(self.secret.as_ref())
+    }
+
+    /// Will return the base58check `xprv` serialization
+    pub fn to_xprv(&self) -> Zeroizing<String> {
+        let mut key = Zeroizing::new([0; 33]);
+        key[1..].copy_from_slice(self.secret.as_ref());
+        Zeroizing::new(self.serialize(XPRV_VERSION, key.as_ref()))
+    }
+
+    /// Will return the base58check `xpub` serialization
+    pub fn to_xpub(&self) -> String {
+        self.serialize(XPUB_VERSION, &self.public_key().serialize())
+    }
+
+    fn serialize(&self, version: [u8; 4], key: &[u8]) -> String {
+        let mut data = Zeroizing::new(Vec::with_capacity(78));
+        data.extend_from_slice(&version);
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_number.to_be_bytes());
+        data.extend_from_slice(self.chain_code.as_ref());
+        data.extend_from_slice(key);
+
+        bs58::encode(data.as_slice()).with_check().into_string()
+    }
+
+    fn secret_key(secret: &[u8; 32]) -> Result<SecretKey, KeyError> {
+        SecretKey::from_slice(secret).map_err(KeyError::InvalidPrivateKey)
+    }
+}
+
+impl fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret key or chain code
+        f.debug_struct("ExtendedPrivateKey")
+            .field("depth", &self.depth)
+            .field("child_number", &self.child_number)
+            .field("public_key", &self.public_key().to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A recoverable wallet, every account is derived from the mnemonic
+pub struct HdWallet {
+    master: ExtendedPrivateKey,
+}
+
+impl HdWallet {
+    /// Will create an HD wallet from a mnemonic and an optional passphrase
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            master: ExtendedPrivateKey::from_mnemonic(mnemonic, passphrase)?,
+        })
+    }
+
+    /// Will return the master extended key
+    pub fn master(&self) -> &ExtendedPrivateKey {
+        &self.master
+    }
+
+    /// Will return the wallet of the n-th account on the Eternal derivation path
+    pub fn account(&self, index: u32) -> Result<Wallet, KeyError> {
+        self.master
+            .derive_path(&DerivationPath::eternal(index)?)?
+            .wallet()
+    }
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("hmac accepts any key length");
+    for part in data {
+        mac.update(part);
+    }
+    let mut result = mac.finalize().into_bytes();
+
+    let mut left = Zeroizing::new([0; 32]);
+    let mut right = Zeroizing::new([0; 32]);
+    left.copy_from_slice(&result[..32]);
+    right.copy_from_slice(&result[32..]);
+    result.as_mut_slice().zeroize();
+
+    (left, right)
+}
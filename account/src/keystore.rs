@@ -0,0 +1,197 @@
+use std::{fs, path::Path};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{KeyError, Wallet};
+
+/// The keystore file format version written by this crate
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Default amount of PBKDF2 rounds used when encrypting a keystore
+pub const DEFAULT_KDF_ROUNDS: u32 = 262_144;
+
+/// Fewer rounds make the password too cheap to brute force
+pub const MIN_KDF_ROUNDS: u32 = 1_000;
+
+/// More rounds would let a crafted keystore stall the decryption
+pub const MAX_KDF_ROUNDS: u32 = 10_000_000;
+
+const CIPHER: &str = "aes-256-gcm";
+const KDF: &str = "pbkdf2-hmac-sha256";
+
+/// An encrypted wallet, as stored inside a keystore file.
+///
+/// The key is derived from the password with PBKDF2-HMAC-SHA256 and the secret
+/// is sealed with AES-256-GCM. The public key is authenticated as associated data,
+/// so a keystore can not be tampered with to point at another account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: String,
+    pub public_key: String,
+    pub crypto: KeystoreCrypto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub nonce: String,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub rounds: u32,
+    pub salt: String,
+}
+
+impl Keystore {
+    /// Will encrypt the wallet with the given password
+    pub fn encrypt(wallet: &Wallet, password: &str) -> Result<Self, KeyError> {
+        Self::encrypt_with_rounds(wallet, password, DEFAULT_KDF_ROUNDS)
+    }
+
+    /// Will encrypt the wallet using a custom amount of kdf rounds
+    pub fn encrypt_with_rounds(
+        wallet: &Wallet,
+        password: &str,
+        rounds: u32,
+    ) -> Result<Self, KeyError> {
+        check_rounds(rounds)?;
+
+        let mut salt = [0; 32];
+        let mut nonce = [0; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let public_key = wallet.public_key().to_string();
+        let key = derive_key(password, &salt, rounds);
+        let cipher = Aes256Gcm::new_from_slice(key.as_ref()).map_err(|_| KeyError::Encryption)?;
+
+        let secret = wallet.export_secret_bytes();
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret.as_ref(),
+                    aad: public_key.as_bytes(),
+                },
+            )
+            .map_err(|_| KeyError::Encryption)?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address: wallet.address().to_string(),
+            public_key,
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                ciphertext: hex::encode(ciphertext),
+                nonce: hex::encode(nonce),
+                kdf: KDF.to_string(),
+                kdf_params: KdfParams {
+                    rounds,
+                    salt: hex::encode(salt),
+                },
+            },
+        })
+    }
+
+    /// Will decrypt the keystore, failing if the password is wrong or the file was altered
+    pub fn decrypt(&self, password: &str) -> Result<Wallet, KeyError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeyError::UnsupportedKeystore(format!(
+                "version {}",
+                self.version
+            )));
+        }
+        if self.crypto.cipher != CIPHER {
+            return Err(KeyError::UnsupportedKeystore(self.crypto.cipher.clone()));
+        }
+        if self.crypto.kdf != KDF {
+            return Err(KeyError::UnsupportedKeystore(self.crypto.kdf.clone()));
+        }
+        check_rounds(self.crypto.kdf_params.rounds)?;
+
+        let salt = hex::decode(&self.crypto.kdf_params.salt).map_err(KeyError::InvalidHex)?;
+        let nonce = hex::decode(&self.crypto.nonce).map_err(KeyError::InvalidHex)?;
+        let ciphertext = hex::decode(&self.crypto.ciphertext).map_err(KeyError::InvalidHex)?;
+        if nonce.len() != 12 {
+            return Err(KeyError::Decryption);
+        }
+
+        let key = derive_key(password, &salt, self.crypto.kdf_params.rounds);
+        let cipher = Aes256Gcm::new_from_slice(key.as_ref()).map_err(|_| KeyError::Decryption)?;
+        let secret = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.public_key.as_bytes(),
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| KeyError::Decryption)?;
+
+        let wallet = Wallet::from_secret_bytes(&secret)?;
+        if wallet.public_key().to_string() != self.public_key {
+            return Err(KeyError::Decryption);
+        }
+        // The address is not authenticated, it has to match the decrypted key
+        let address = wallet.address().to_string();
+        if address != self.address {
+            return Err(KeyError::AddressMismatch {
+                expected: self.address.clone(),
+                actual: address,
+            });
+        }
+
+        Ok(wallet)
+    }
+
+    /// Will parse a keystore from its json representation
+    pub fn from_json(json: &str) -> Result<Self, KeyError> {
+        serde_json::from_str(json).map_err(KeyError::Format)
+    }
+
+    /// Will return the json representation of the keystore
+    pub fn to_json(&self) -> Result<String, KeyError> {
+        serde_json::to_string_pretty(self).map_err(KeyError::Format)
+    }
+
+    /// Will read a keystore file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, KeyError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Will write the keystore into a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeyError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+fn check_rounds(rounds: u32) -> Result<(), KeyError> {
+    if !(MIN_KDF_ROUNDS..=MAX_KDF_ROUNDS).contains(&rounds) {
+        return Err(KeyError::InvalidKdfRounds {
+            rounds,
+            min: MIN_KDF_ROUNDS,
+            max: MAX_KDF_ROUNDS,
+        });
+    }
+    Ok(())
+}
+
+fn derive_key(password: &str, salt: &[u8], rounds: u32) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, key.as_mut());
+    key
+}
@@ -1,18 +1,33 @@
+pub mod address;
+mod error;
+pub mod hd;
+pub mod keystore;
+pub mod store;
+mod wallet;
+
 use rand::RngCore;
 use secp256k1::{PublicKey, Secp256k1, SecretKey};
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
 
+pub use address::Address;
+pub use secp256k1;
+pub use error::{KeyError, StoreError};
+pub use hd::{DerivationPath, ExtendedPrivateKey, HdWallet};
+pub use keystore::Keystore;
+pub use store::{Store, StoreKey};
+pub use wallet::{verify_signature, Wallet};
+
+/// The public, on-chain part of an account. Private keys live in a [`Wallet`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
-    pub private_key: String,
     pub public_key: String,
-    // pub secret_key: String,
     pub public_key_bytes: Vec<u8>,
-    pub store: HashMap<String, String>,
+    pub store: Store,
     pub acc_type: AccountType,
     pub tokens: u128,
+    /// The amount of transactions sent, the next one has to carry `nonce + 1`
+    #[serde(default)]
+    pub nonce: u128,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,51 +40,110 @@ pub enum AccountType {
         burn: bool,
     },
     SmartContract,
+    /// Controlled by `public_keys`, at least `threshold` of them have to sign
+    Multisig {
+        public_keys: Vec<String>,
+        threshold: u8,
+    },
     Validator {
+        stake: u128,
         correctly_validated_blocks: u128,
         incorrectly_validated_blocks: u128,
-        you_get_the_idea: bool,
+        /// Set once the validator got slashed, jailed validators can not propose blocks
+        jailed: bool,
+        /// Unstaked tokens waiting to be released, oldest first
+        #[serde(default)]
+        unbonding: Vec<Unbonding>,
     },
 }
 
+/// Unstaked tokens on their way back into the balance, still slashable until released
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unbonding {
+    pub amount: u128,
+    /// Height of the first block in which the tokens are spendable again
+    pub release_height: u64,
+}
+
 impl Account {
+    /// Will create an account for a random key, the private key is discarded
     pub fn new(account_type: AccountType) -> Self {
-        let (priv_key, _, pub_key, pk_bytes) = Self::generate_keypair();
+        Wallet::generate().account(account_type)
+    }
 
+    /// Will create an account owned by the given public key
+    pub fn from_public_key(public_key: &PublicKey, account_type: AccountType) -> Self {
         Self {
-            private_key: priv_key,
-            public_key: pub_key,
-            public_key_bytes: pk_bytes.to_vec(),
-            store: HashMap::new(),
+            public_key: public_key.to_string(),
+            public_key_bytes: public_key.serialize().to_vec(),
+            store: Store::default(),
             acc_type: account_type,
             tokens: 0,
+            nonce: 0,
         }
     }
 
-    pub fn from(priv_key: String) -> Self {
-        let context = Secp256k1::default();
+    /// Will create an m-of-n multisig account. The keys are sorted, so the address
+    /// does not depend on the order they are given in
+    pub fn multisig(public_keys: Vec<String>, threshold: u8) -> Result<Self, KeyError> {
+        let invalid = |reason: &str| KeyError::InvalidMultisig(reason.to_string());
 
-        let secret_key = SecretKey::from_slice(&priv_key.as_bytes()).unwrap();
+        let mut keys = public_keys
+            .iter()
+            .map(|key| key.parse::<PublicKey>().map_err(KeyError::InvalidPublicKey))
+            .collect::<Result<Vec<_>, _>>()?;
+        keys.sort();
+        keys.dedup();
 
-        let pub_key = PublicKey::from_secret_key(&context, &secret_key);
+        if keys.len() != public_keys.len() {
+            return Err(invalid("duplicate public keys"));
+        }
+        if threshold == 0 || threshold as usize > keys.len() {
+            return Err(invalid("threshold has to be between 1 and the amount of keys"));
+        }
 
-        Self {
-            private_key: priv_key,
-            public_key: pub_key.clone().to_string(),
-            public_key_bytes: pub_key.clone().serialize().to_vec(),
-            store: HashMap::new(),
-            acc_type: AccountType::User,
-            tokens: 0,
+        // The owner "key" is the threshold followed by all compressed keys
+        let mut bytes = vec![threshold];
+        for key in &keys {
+            bytes.extend_from_slice(&key.serialize());
         }
+
+        Ok(Self {
+            public_key: hex::encode(&bytes),
+            public_key_bytes: bytes,
+            store: Store::default(),
+            acc_type: AccountType::Multisig {
+                public_keys: keys.iter().map(|key| key.to_string()).collect(),
+                threshold,
+            },
+            tokens: 0,
+            nonce: 0,
+        })
     }
 
-    pub fn generate_adress(&self) -> String {
-        format!("etnl:{}", &self.public_key[33..66])
+    /// Will create the user account for a hex encoded private key (as returned by
+    /// [`Account::generate_keypair`] or [`Wallet::export_private_key`])
+    pub fn from(priv_key: String) -> Result<Self, KeyError> {
+        Ok(Wallet::from_private_key(&priv_key)?.account(AccountType::User))
     }
 
-    pub fn sign(&self) {}
+    /// Will return the address derived from the public key
+    pub fn address(&self) -> Address {
+        Address::from_public_key_bytes(&self.public_key_bytes)
+    }
 
-    pub fn verify(&self) {}
+    /// Will return the checksummed `etnl:` address as string
+    pub fn generate_adress(&self) -> String {
+        self.address().to_string()
+    }
+
+    /// Will check that the signature over the message was created by this account's key
+    pub fn verify(&self, message: &[u8], signature: &str) -> bool {
+        match PublicKey::from_slice(&self.public_key_bytes) {
+            Ok(public_key) => verify_signature(&public_key, message, signature),
+            Err(_) => false,
+        }
+    }
 
     pub fn generate_keypair() -> (String, String, String, [u8; 33]) {
         let mut priv_key = [0; 32];
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, StoreError};
+
+/// The key/value storage of an account. The entries are split into namespaces named after
+/// the address owning them: the account itself or a contract keeping data about the
+/// account. Every namespace can be read, but only its owner may write to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Store {
+    /// Empty namespaces are not kept
+    namespaces: BTreeMap<String, Namespace>,
+}
+
+/// The entries of a namespace, serialized as hex keys and values
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    into = "BTreeMap<String, String>",
+    try_from = "BTreeMap<String, String>"
+)]
+struct Namespace(BTreeMap<Vec<u8>, Vec<u8>>);
+
+/// Points to an entry of a namespace
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StoreKey {
+    pub namespace: String,
+    #[serde(with = "hex::serde")]
+    pub key: Vec<u8>,
+}
+
+impl StoreKey {
+    /// Will point to the key inside the namespace
+    pub fn new(namespace: &str, key: &[u8]) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            key: key.to_vec(),
+        }
+    }
+}
+
+impl Store {
+    /// Will return the value stored under the key, none if there is no such entry
+    pub fn get_bytes(&self, namespace: &str, key: &[u8]) -> Option<&[u8]> {
+        let entries = &self.namespaces.get(namespace)?.0;
+        entries.get(key).map(Vec::as_slice)
+    }
+
+    /// Will return the u128 stored under the key, fails if the value is no u128
+    pub fn get_u128(&self, namespace: &str, key: &[u8]) -> Result<Option<u128>, StoreError> {
+        let Some(value) = self.get_bytes(namespace, key) else {
+            return Ok(None);
+        };
+        let bytes = value
+            .try_into()
+            .map_err(|_| StoreError::InvalidValue(StoreKey::new(namespace, key)))?;
+
+        Ok(Some(u128::from_be_bytes(bytes)))
+    }
+
+    /// Will return the address stored under the key, fails if the value is no address
+    pub fn get_address(&self, namespace: &str, key: &[u8]) -> Result<Option<Address>, StoreError> {
+        let Some(value) = self.get_bytes(namespace, key) else {
+            return Ok(None);
+        };
+        let bytes = value
+            .try_into()
+            .map_err(|_| StoreError::InvalidValue(StoreKey::new(namespace, key)))?;
+
+        Ok(Some(Address::from_bytes(bytes)))
+    }
+
+    /// Will store the value under the key if the caller owns the namespace
+    pub fn set_bytes(
+        &mut self,
+        caller: &str,
+        namespace: &str,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<(), StoreError> {
+        check_access(caller, namespace)?;
+
+        let entries = &mut self.namespaces.entry(namespace.to_string()).or_default().0;
+        entries.insert(key.to_vec(), value);
+        Ok(())
+    }
+
+    /// Will store the u128 as 16 big endian bytes
+    pub fn set_u128(
+        &mut self,
+        caller: &str,
+        namespace: &str,
+        key: &[u8],
+        value: u128,
+    ) -> Result<(), StoreError> {
+        self.set_bytes(caller, namespace, key, value.to_be_bytes().to_vec())
+    }
+
+    /// Will store the 20 address bytes
+    pub fn set_address(
+        &mut self,
+        caller: &str,
+        namespace: &str,
+        key: &[u8],
+        value: &Address,
+    ) -> Result<(), StoreError> {
+        self.set_bytes(caller, namespace, key, value.as_bytes().to_vec())
+    }
+
+    /// Will remove the entry if the caller owns the namespace, returning the old value
+    pub fn remove(
+        &mut self,
+        caller: &str,
+        namespace: &str,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        check_access(caller, namespace)?;
+
+        let Some(entries) = self.namespaces.get_mut(namespace) else {
+            return Ok(None);
+        };
+        let value = entries.0.remove(key);
+        if entries.0.is_empty() {
+            self.namespaces.remove(namespace);
+        }
+        Ok(value)
+    }
+
+    /// Will return all entries as (namespace, key, value), sorted by namespace and key
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[u8], &[u8])> {
+        self.namespaces.iter().flat_map(|(namespace, entries)| {
+            entries
+                .0
+                .iter()
+                .map(move |(key, value)| (namespace.as_str(), key.as_slice(), value.as_slice()))
+        })
+    }
+
+    /// Will return the amount of entries over all namespaces
+    pub fn len(&self) -> usize {
+        self.namespaces
+            .values()
+            .map(|entries| entries.0.len())
+            .sum()
+    }
+
+    /// True if no namespace has any entries
+    pub fn is_empty(&self) -> bool {
+        self.namespaces.is_empty()
+    }
+
+    /// Will remove all entries, regardless of who owns them
+    pub fn clear(&mut self) {
+        self.namespaces.clear();
+    }
+}
+
+/// Only the owner of a namespace may write to it
+fn check_access(caller: &str, namespace: &str) -> Result<(), StoreError> {
+    if caller != namespace {
+        return Err(StoreError::AccessDenied {
+            caller: caller.to_string(),
+            namespace: namespace.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+impl From<Namespace> for BTreeMap<String, String> {
+    fn from(namespace: Namespace) -> Self {
+        namespace
+            .0
+            .into_iter()
+            .map(|(key, value)| (hex::encode(key), hex::encode(value)))
+            .collect()
+    }
+}
+
+impl TryFrom<BTreeMap<String, String>> for Namespace {
+    type Error = hex::FromHexError;
+
+    fn try_from(entries: BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| Ok((hex::decode(key)?, hex::decode(value)?)))
+            .collect::<Result<_, Self::Error>>()?;
+        Ok(Self(entries))
+    }
+}
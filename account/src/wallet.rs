@@ -0,0 +1,130 @@
+use std::fmt;
+
+use rand::RngCore;
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::{Account, AccountType, Address, KeyError};
+
+/// Holds the private key of an account.
+///
+/// The chain state only ever stores the public [`Account`], the secret never leaves
+/// the wallet unless it is exported explicitly. The key material is wiped on drop.
+pub struct Wallet {
+    secret: Zeroizing<[u8; 32]>,
+    public_key: PublicKey,
+}
+
+impl Wallet {
+    /// Will create a wallet with a freshly generated random key
+    pub fn generate() -> Self {
+        let mut secret = Zeroizing::new([0; 32]);
+        loop {
+            rand::thread_rng().fill_bytes(secret.as_mut());
+            if let Ok(wallet) = Self::from_secret_bytes(secret.as_ref()) {
+                return wallet;
+            }
+        }
+    }
+
+    /// Will create a wallet from the raw 32 bytes of a secret key
+    pub fn from_secret_bytes(bytes: &[u8]) -> Result<Self, KeyError> {
+        let mut secret_key = SecretKey::from_slice(bytes).map_err(KeyError::InvalidPrivateKey)?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key);
+
+        let wallet = Self {
+            secret: Zeroizing::new(secret_key.secret_bytes()),
+            public_key,
+        };
+        secret_key.non_secure_erase();
+
+        Ok(wallet)
+    }
+
+    /// Will create a wallet from a hex encoded secret key, an optional `0x` prefix is allowed
+    pub fn from_private_key(hex_key: &str) -> Result<Self, KeyError> {
+        let hex_key = hex_key.trim();
+        let hex_key = hex_key.strip_prefix("0x").unwrap_or(hex_key);
+
+        let mut bytes = Zeroizing::new([0; 32]);
+        hex::decode_to_slice(hex_key, bytes.as_mut()).map_err(KeyError::InvalidHex)?;
+
+        Self::from_secret_bytes(bytes.as_ref())
+    }
+
+    /// Will return the public key belonging to the wallet
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Will return the address of the wallet
+    pub fn address(&self) -> Address {
+        Address::from_public_key_bytes(&self.public_key.serialize())
+    }
+
+    /// Will return the public on-chain representation of the wallet
+    pub fn account(&self, account_type: AccountType) -> Account {
+        Account::from_public_key(&self.public_key, account_type)
+    }
+
+    /// Will return the raw secret key bytes, wiped once the returned value is dropped
+    pub fn export_secret_bytes(&self) -> Zeroizing<[u8; 32]> {
+        self.secret.clone()
+    }
+
+    /// Will return the secret key hex encoded, wiped once the returned value is dropped
+    pub fn export_private_key(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(self.secret.as_ref()))
+    }
+
+    /// Will sign the sha256 hash of the message and return the compact signature as hex
+    pub fn sign(&self, message: &[u8]) -> String {
+        let digest = Sha256::digest(message);
+        // A sha256 digest always has the 32 bytes a message requires
+        let message = Message::from_slice(&digest).expect("sha256 digest is 32 bytes");
+
+        let mut secret_key =
+            SecretKey::from_slice(self.secret.as_ref()).expect("wallet holds a valid secret key");
+        let signature = Secp256k1::signing_only().sign_ecdsa(&message, &secret_key);
+        secret_key.non_secure_erase();
+
+        hex::encode(signature.serialize_compact())
+    }
+}
+
+impl Clone for Wallet {
+    fn clone(&self) -> Self {
+        Self {
+            secret: self.secret.clone(),
+            public_key: self.public_key,
+        }
+    }
+}
+
+impl fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret key
+        f.debug_struct("Wallet")
+            .field("public_key", &self.public_key.to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Will check a compact hex signature created by [`Wallet::sign`]
+pub fn verify_signature(public_key: &PublicKey, message: &[u8], signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_compact(&signature) else {
+        return false;
+    };
+    let digest = Sha256::digest(message);
+    let Ok(message) = Message::from_slice(&digest) else {
+        return false;
+    };
+
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, public_key)
+        .is_ok()
+}
@@ -0,0 +1,100 @@
+use eternal_account::{address::ADDRESS_PREFIX, Account, AccountType, Address, KeyError, Wallet};
+
+#[test]
+fn addresses_round_trip_with_checksum() {
+    let wallet = Wallet::generate();
+    let address = wallet.address();
+    assert_eq!(
+        address,
+        Address::from_public_key_bytes(&wallet.public_key().serialize())
+    );
+
+    let text = address.to_string();
+    assert!(text.starts_with(ADDRESS_PREFIX));
+    assert_eq!(text.len(), ADDRESS_PREFIX.len() + 40);
+    assert_eq!(text.parse::<Address>().unwrap(), address);
+
+    let json = serde_json::to_string(&address).unwrap();
+    assert_eq!(json, format!("\"{}\"", text));
+    assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), address);
+}
+
+#[test]
+fn malformed_addresses_are_rejected() {
+    // Enough letters that flipping the case of one breaks the checksum
+    let address = (0..)
+        .map(|_| Wallet::generate().address().to_string())
+        .find(|text| {
+            text[ADDRESS_PREFIX.len()..]
+                .chars()
+                .any(|c| c.is_ascii_alphabetic())
+        })
+        .unwrap();
+    let hex_part = &address[ADDRESS_PREFIX.len()..];
+
+    let position = hex_part.find(|c: char| c.is_ascii_alphabetic()).unwrap();
+    let mut flipped: Vec<char> = hex_part.chars().collect();
+    flipped[position] = if flipped[position].is_ascii_uppercase() {
+        flipped[position].to_ascii_lowercase()
+    } else {
+        flipped[position].to_ascii_uppercase()
+    };
+    let flipped: String = flipped.into_iter().collect();
+
+    for invalid in [
+        hex_part.to_string(),
+        format!("eth:{}", hex_part),
+        format!("{}{}", ADDRESS_PREFIX, &hex_part[2..]),
+        format!("{}{}zz", ADDRESS_PREFIX, &hex_part[2..]),
+        format!("{}{}", ADDRESS_PREFIX, flipped),
+    ] {
+        assert!(
+            matches!(invalid.parse::<Address>(), Err(KeyError::InvalidAddress(_))),
+            "{} was accepted",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn private_keys_round_trip() {
+    let wallet = Wallet::generate();
+    let private_key = wallet.export_private_key();
+    assert_eq!(private_key.len(), 64);
+
+    let imported = Wallet::from_private_key(&private_key).unwrap();
+    assert_eq!(imported.address(), wallet.address());
+    let prefixed = Wallet::from_private_key(&format!("0x{}", private_key.as_str())).unwrap();
+    assert_eq!(prefixed.address(), wallet.address());
+    let bytes = Wallet::from_secret_bytes(wallet.export_secret_bytes().as_ref()).unwrap();
+    assert_eq!(bytes.address(), wallet.address());
+
+    assert!(matches!(
+        Wallet::from_private_key("not hex"),
+        Err(KeyError::InvalidHex(_))
+    ));
+    assert!(matches!(
+        Wallet::from_secret_bytes(&[0; 32]),
+        Err(KeyError::InvalidPrivateKey(_))
+    ));
+}
+
+#[test]
+fn generated_keypairs_import_as_accounts() {
+    let (private_key, _, public_key, public_key_bytes) = Account::generate_keypair();
+
+    let account = Account::from(private_key.clone()).unwrap();
+    assert!(matches!(account.acc_type, AccountType::User));
+    assert_eq!(account.public_key, public_key);
+    assert_eq!(account.public_key_bytes, public_key_bytes);
+    assert_eq!(
+        account.address(),
+        Wallet::from_private_key(&private_key).unwrap().address()
+    );
+    assert_eq!(account.generate_adress(), account.address().to_string());
+
+    assert!(matches!(
+        Account::from("not hex".to_string()),
+        Err(KeyError::InvalidHex(_))
+    ));
+}
@@ -0,0 +1,142 @@
+use bip39::Mnemonic;
+use eternal_account::{hd, DerivationPath, ExtendedPrivateKey, HdWallet, KeyError};
+
+// https://github.com/trezor/python-mnemonic/blob/master/vectors.json (passphrase "TREZOR")
+const BIP39_VECTORS: &[(&str, &str, &str, &str)] = &[
+    (
+        "00000000000000000000000000000000",
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF",
+    ),
+    (
+        "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+        "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        "xprv9s21ZrQH143K2gA81bYFHqU68xz1cX2APaSq5tt6MFSLeXnCKV1RVUJt9FWNTbrrryem4ZckN8k4Ls1H6nwdvDTvnV7zEXs2HgPezuVccsq",
+    ),
+    (
+        "ffffffffffffffffffffffffffffffff",
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+        "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+        "xprv9s21ZrQH143K2V4oox4M8Zmhi2Fjx5XK4Lf7GKRvPSgydU3mjZuKGCTg7UPiBUD7ydVPvSLtg9hjp7MQTYsW67rZHAXeccqYqrsx8LcXnyd",
+    ),
+];
+
+// https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+const BIP32_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const BIP32_VECTORS: &[(&str, &str, &str)] = &[
+    (
+        "m",
+        "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+        "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+    ),
+    (
+        "m/0'",
+        "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+        "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+    ),
+    (
+        "m/0'/1",
+        "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+        "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+    ),
+    (
+        "m/0'/1/2'",
+        "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+        "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+    ),
+    (
+        "m/0'/1/2'/2",
+        "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+        "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+    ),
+    (
+        "m/0'/1/2'/2/1000000000",
+        "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+        "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+    ),
+];
+
+#[test]
+fn bip39_vectors() {
+    for (entropy, phrase, seed, xprv) in BIP39_VECTORS {
+        let mnemonic = Mnemonic::from_entropy(&hex::decode(entropy).unwrap()).unwrap();
+        assert_eq!(mnemonic.to_string(), *phrase);
+
+        let parsed = hd::parse_mnemonic(phrase).unwrap();
+        assert_eq!(hex::encode(parsed.to_seed("TREZOR")), *seed);
+
+        let master = ExtendedPrivateKey::from_mnemonic(&parsed, "TREZOR").unwrap();
+        assert_eq!(master.to_xprv().as_str(), *xprv);
+    }
+}
+
+#[test]
+fn bip39_rejects_invalid_mnemonics() {
+    // Bad checksum
+    assert!(hd::parse_mnemonic(
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+    )
+    .is_err());
+    // Unknown word
+    assert!(hd::parse_mnemonic(
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon eternal"
+    )
+    .is_err());
+
+    let generated = hd::generate_mnemonic(24).unwrap();
+    assert_eq!(
+        hd::parse_mnemonic(&generated.to_string()).unwrap(),
+        generated
+    );
+}
+
+#[test]
+fn bip32_vectors() {
+    let master = ExtendedPrivateKey::from_seed(&hex::decode(BIP32_SEED).unwrap()).unwrap();
+
+    for (path, xpub, xprv) in BIP32_VECTORS {
+        let path: DerivationPath = path.parse().unwrap();
+        let key = master.derive_path(&path).unwrap();
+
+        assert_eq!(key.to_xpub(), *xpub, "{}", path);
+        assert_eq!(key.to_xprv().as_str(), *xprv, "{}", path);
+    }
+}
+
+#[test]
+fn eternal_accounts_are_recoverable() {
+    let mnemonic = hd::parse_mnemonic(BIP39_VECTORS[0].1).unwrap();
+
+    let first = HdWallet::from_mnemonic(&mnemonic, "").unwrap();
+    let second = HdWallet::from_mnemonic(&mnemonic, "").unwrap();
+
+    assert_eq!(
+        first.account(0).unwrap().address(),
+        second.account(0).unwrap().address()
+    );
+    assert_ne!(
+        first.account(0).unwrap().address(),
+        first.account(1).unwrap().address()
+    );
+    assert_eq!(
+        DerivationPath::eternal(0).unwrap().to_string(),
+        format!("m/44'/{}'/0'/0/0", hd::ETERNAL_COIN_TYPE)
+    );
+
+    // The account index is hardened, it can not wrap around into another account
+    let last = hd::HARDENED - 1;
+    assert_eq!(
+        DerivationPath::eternal(last).unwrap().indices()[2],
+        u32::MAX
+    );
+    assert!(matches!(
+        DerivationPath::eternal(hd::HARDENED),
+        Err(KeyError::InvalidDerivationPath(_))
+    ));
+    assert!(matches!(
+        first.account(hd::HARDENED),
+        Err(KeyError::InvalidDerivationPath(_))
+    ));
+}
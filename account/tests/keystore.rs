@@ -0,0 +1,143 @@
+use std::{env, fs};
+
+use eternal_account::{
+    keystore::{MAX_KDF_ROUNDS, MIN_KDF_ROUNDS},
+    AccountType, KeyError, Keystore, Wallet,
+};
+
+/// Few rounds keep the tests fast, the format is the same
+const ROUNDS: u32 = 1_000;
+
+#[test]
+fn keystore_round_trip() {
+    let wallet = Wallet::generate();
+    let keystore = Keystore::encrypt_with_rounds(&wallet, "correct horse", ROUNDS).unwrap();
+    assert_eq!(keystore.address, wallet.address().to_string());
+    assert_eq!(keystore.public_key, wallet.public_key().to_string());
+
+    let json = keystore.to_json().unwrap();
+    assert!(!json.contains(&hex::encode(*wallet.export_secret_bytes())));
+    let decrypted = Keystore::from_json(&json)
+        .unwrap()
+        .decrypt("correct horse")
+        .unwrap();
+    assert_eq!(decrypted.address(), wallet.address());
+    assert_eq!(
+        *decrypted.export_secret_bytes(),
+        *wallet.export_secret_bytes()
+    );
+
+    let path = env::temp_dir().join(format!("eternal-keystore-{}.json", std::process::id()));
+    keystore.save(&path).unwrap();
+    let loaded = Keystore::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        loaded.decrypt("correct horse").unwrap().address(),
+        wallet.address()
+    );
+}
+
+#[test]
+fn wrong_password_or_tampering_fails() {
+    let wallet = Wallet::generate();
+    let keystore = Keystore::encrypt_with_rounds(&wallet, "correct horse", ROUNDS).unwrap();
+
+    assert!(matches!(
+        keystore.decrypt("battery staple"),
+        Err(KeyError::Decryption)
+    ));
+
+    let mut tampered = keystore.clone();
+    let mut ciphertext = hex::decode(&tampered.crypto.ciphertext).unwrap();
+    ciphertext[0] ^= 1;
+    tampered.crypto.ciphertext = hex::encode(ciphertext);
+    assert!(matches!(
+        tampered.decrypt("correct horse"),
+        Err(KeyError::Decryption)
+    ));
+
+    // The public key is authenticated, pointing the keystore at another account fails
+    let mut moved = keystore.clone();
+    moved.public_key = Wallet::generate().public_key().to_string();
+    assert!(matches!(
+        moved.decrypt("correct horse"),
+        Err(KeyError::Decryption)
+    ));
+
+    let mut newer = keystore;
+    newer.version += 1;
+    assert!(matches!(
+        newer.decrypt("correct horse"),
+        Err(KeyError::UnsupportedKeystore(_))
+    ));
+}
+
+#[test]
+fn unsupported_ciphers_and_kdfs_are_rejected() {
+    let wallet = Wallet::generate();
+    let keystore = Keystore::encrypt_with_rounds(&wallet, "correct horse", ROUNDS).unwrap();
+
+    let mut cipher = keystore.clone();
+    cipher.crypto.cipher = "aes-128-ctr".to_string();
+    assert!(matches!(
+        cipher.decrypt("correct horse"),
+        Err(KeyError::UnsupportedKeystore(name)) if name == "aes-128-ctr"
+    ));
+
+    let mut kdf = keystore;
+    kdf.crypto.kdf = "scrypt".to_string();
+    assert!(matches!(
+        kdf.decrypt("correct horse"),
+        Err(KeyError::UnsupportedKeystore(name)) if name == "scrypt"
+    ));
+}
+
+#[test]
+fn kdf_rounds_are_bounded() {
+    let wallet = Wallet::generate();
+    assert!(matches!(
+        Keystore::encrypt_with_rounds(&wallet, "correct horse", MIN_KDF_ROUNDS - 1),
+        Err(KeyError::InvalidKdfRounds { rounds, .. }) if rounds == MIN_KDF_ROUNDS - 1
+    ));
+
+    // Checked before deriving the key, a crafted file can not stall the decryption
+    let mut keystore = Keystore::encrypt_with_rounds(&wallet, "correct horse", ROUNDS).unwrap();
+    keystore.crypto.kdf_params.rounds = MAX_KDF_ROUNDS + 1;
+    assert!(matches!(
+        keystore.decrypt("correct horse"),
+        Err(KeyError::InvalidKdfRounds {
+            min: MIN_KDF_ROUNDS,
+            max: MAX_KDF_ROUNDS,
+            ..
+        })
+    ));
+    keystore.crypto.kdf_params.rounds = 0;
+    assert!(matches!(
+        keystore.decrypt("correct horse"),
+        Err(KeyError::InvalidKdfRounds { rounds: 0, .. })
+    ));
+}
+
+#[test]
+fn the_address_has_to_match_the_key() {
+    let wallet = Wallet::generate();
+    let mut keystore = Keystore::encrypt_with_rounds(&wallet, "correct horse", ROUNDS).unwrap();
+    let other = Wallet::generate().address().to_string();
+    keystore.address = other.clone();
+
+    assert!(matches!(
+        keystore.decrypt("correct horse"),
+        Err(KeyError::AddressMismatch { expected, actual })
+            if expected == other && actual == wallet.address().to_string()
+    ));
+}
+
+#[test]
+fn accounts_hold_no_secrets() {
+    let wallet = Wallet::generate();
+    let account = wallet.account(AccountType::User);
+
+    let json = serde_json::to_string(&account).unwrap();
+    assert!(!json.contains(&hex::encode(*wallet.export_secret_bytes())));
+    assert!(!json.contains("private"));
+}
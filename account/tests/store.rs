@@ -0,0 +1,85 @@
+use eternal_account::{Address, Store, StoreError};
+
+const OWNER: &str = "etnl:00000000000000000000000000000000000000aa";
+const CONTRACT: &str = "etnl:0000000000000000000000000000000000000001";
+
+#[test]
+fn typed_values() {
+    let mut storage = Store::default();
+    let address = Address::from_bytes([7; 20]);
+
+    storage.set_u128(OWNER, OWNER, b"amount", 1_000).unwrap();
+    storage
+        .set_address(OWNER, OWNER, b"friend", &address)
+        .unwrap();
+    storage
+        .set_bytes(OWNER, OWNER, b"color", b"red".to_vec())
+        .unwrap();
+
+    assert_eq!(storage.get_u128(OWNER, b"amount").unwrap(), Some(1_000));
+    assert_eq!(
+        storage.get_address(OWNER, b"friend").unwrap(),
+        Some(address)
+    );
+    assert_eq!(storage.get_bytes(OWNER, b"color"), Some(&b"red"[..]));
+    assert_eq!(storage.get_u128(OWNER, b"missing").unwrap(), None);
+    assert!(matches!(
+        storage.get_u128(OWNER, b"color"),
+        Err(StoreError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        storage.get_address(OWNER, b"amount"),
+        Err(StoreError::InvalidValue(_))
+    ));
+    assert_eq!(storage.len(), 3);
+}
+
+#[test]
+fn only_the_owner_writes_a_namespace() {
+    let mut storage = Store::default();
+    storage
+        .set_u128(CONTRACT, CONTRACT, b"balance", 50)
+        .unwrap();
+
+    // The same key in another namespace is another entry
+    storage.set_u128(OWNER, OWNER, b"balance", 1).unwrap();
+    assert_eq!(storage.get_u128(CONTRACT, b"balance").unwrap(), Some(50));
+
+    assert!(matches!(
+        storage.set_u128(OWNER, CONTRACT, b"balance", 5_000),
+        Err(StoreError::AccessDenied { .. })
+    ));
+    assert!(matches!(
+        storage.remove(OWNER, CONTRACT, b"balance"),
+        Err(StoreError::AccessDenied { .. })
+    ));
+    assert_eq!(storage.get_u128(CONTRACT, b"balance").unwrap(), Some(50));
+
+    // Emptied namespaces are dropped
+    storage.remove(OWNER, OWNER, b"balance").unwrap();
+    storage.remove(CONTRACT, CONTRACT, b"balance").unwrap();
+    assert!(storage.is_empty());
+}
+
+#[test]
+fn entries_are_sorted_and_serialized_as_hex() {
+    let mut storage = Store::default();
+    storage.set_bytes(OWNER, OWNER, b"b", vec![2]).unwrap();
+    storage.set_bytes(OWNER, OWNER, b"a", vec![1]).unwrap();
+    storage.set_bytes(CONTRACT, CONTRACT, b"z", vec![]).unwrap();
+
+    let entries: Vec<_> = storage.entries().collect();
+    assert_eq!(
+        entries,
+        [
+            (CONTRACT, &b"z"[..], &[][..]),
+            (OWNER, &b"a"[..], &[1][..]),
+            (OWNER, &b"b"[..], &[2][..]),
+        ]
+    );
+
+    let json = serde_json::to_value(&storage).unwrap();
+    assert_eq!(json[OWNER]["61"], "01");
+    let restored: Store = serde_json::from_value(json).unwrap();
+    assert_eq!(restored, storage);
+}
@@ -1,13 +1,64 @@
-use crate::transaction::Transaction;
+use crate::{
+    domain::{tagged_hasher, update_field, update_optional, BLOCK_TAG, TRANSACTION_LEAF_TAG},
+    merkle::{self, Hash, MerkleProof},
+    time::now_millis,
+    transaction::Transaction,
+};
+use eternal_account::Wallet;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use sha2::Digest;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Block {
     pub transactions: Vec<Transaction>,
     pub prev: Option<String>,
     pub hash: Option<String>,
     pub nonce: u128,
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    /// The validator or authority sealing the block (not used by proof of work)
+    pub proposer: Option<String>,
+    /// The proposer's signature over the block hash
+    pub signature: Option<String>,
+    /// The state root after executing the transactions, see [`crate::state`]
+    #[serde(default)]
+    pub state_root: Option<String>,
+}
+
+/// The part of a block which is hashed and signed. The transactions are only committed
+/// to by their Merkle root, so the header chain can be followed without the transactions.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BlockHeader {
+    pub prev: Option<String>,
+    pub hash: Option<String>,
+    pub nonce: u128,
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub proposer: Option<String>,
+    pub signature: Option<String>,
+    pub state_root: Option<String>,
+    /// The Merkle root over the transaction hashes, see [`Block::transactions_root`]
+    pub transactions_root: String,
+}
+
+impl BlockHeader {
+    /// Will hash every field but the hash and the signature, fixed size integers big endian
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = tagged_hasher(BLOCK_TAG);
+        update_field(&mut hasher, self.transactions_root.as_bytes());
+        update_optional(&mut hasher, self.prev.as_deref().map(str::as_bytes));
+        hasher.update(self.nonce.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        update_optional(&mut hasher, self.proposer.as_deref().map(str::as_bytes));
+        update_optional(&mut hasher, self.state_root.as_deref().map(str::as_bytes));
+
+        format!("{:X}", hasher.finalize())
+    }
+
+    /// Checks if the hash is set and matches the header
+    pub fn verify_own_hash(&self) -> bool {
+        self.hash.as_ref() == Some(&self.calculate_hash())
+    }
 }
 
 impl Block {
@@ -16,26 +67,71 @@ impl Block {
             nonce: 0,
             hash: None,
             prev: prev_hash,
+            timestamp: now_millis(),
             transactions: Vec::new(),
+            proposer: None,
+            signature: None,
+            state_root: None,
         }
     }
 
+    /// Will set the wallet as proposer and sign the resulting block hash
+    pub fn sign(&mut self, wallet: &Wallet) {
+        self.proposer = Some(wallet.address().to_string());
+        self.update_hash();
+
+        let hash = self.calculate_hash();
+        self.signature = Some(wallet.sign(hash.as_bytes()));
+    }
+
     pub fn set_nonce(&mut self, nonce: u128) {
         self.nonce = nonce;
         self.update_hash();
     }
 
+    /// Will commit to the state after executing the transactions
+    pub fn set_state_root(&mut self, state_root: String) {
+        self.state_root = Some(state_root);
+        self.update_hash();
+    }
+
+    /// Will set the Unix timestamp in milliseconds
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+        self.update_hash();
+    }
+
     pub fn calculate_hash(&self) -> String {
-        let mut hasher = Sha256::new();
+        self.header().calculate_hash()
+    }
 
-        for transaction in self.transactions.iter() {
-            hasher.update(transaction.calculate_hash())
+    /// Will return the header of the block
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            prev: self.prev.clone(),
+            hash: self.hash.clone(),
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            proposer: self.proposer.clone(),
+            signature: self.signature.clone(),
+            state_root: self.state_root.clone(),
+            transactions_root: merkle::to_hex(&self.transactions_root()),
         }
+    }
+
+    /// Will return the Merkle root over the hashes of the transactions, in block order
+    pub fn transactions_root(&self) -> Hash {
+        merkle::root(&self.transaction_leaves())
+    }
 
-        let block_as_string = format!("{:?}", (&self.prev, &self.nonce));
-        hasher.update(&block_as_string);
+    /// Will return the proof that the transaction at the given position is committed to
+    /// by the transactions root
+    pub fn transaction_proof(&self, index: usize) -> Option<MerkleProof> {
+        merkle::proof(&self.transaction_leaves(), index)
+    }
 
-        return format!("{:X}", hasher.finalize());
+    fn transaction_leaves(&self) -> Vec<Hash> {
+        self.transactions.iter().map(transaction_leaf).collect()
     }
 
     /// Appends a transaction to the queue
@@ -67,3 +163,16 @@ impl Block {
         false
     }
 }
+
+/// Will return the leaf of a transaction in the tree of [`Block::transactions_root`].
+/// It covers the signatures, so the block hash commits to who signed its transactions.
+pub fn transaction_leaf(transaction: &Transaction) -> Hash {
+    let mut hasher = tagged_hasher(TRANSACTION_LEAF_TAG);
+    update_field(&mut hasher, &transaction.calculate_hash());
+    hasher.update((transaction.signatures.len() as u64).to_be_bytes());
+    for signature in &transaction.signatures {
+        update_field(&mut hasher, signature.public_key.as_bytes());
+        update_field(&mut hasher, signature.signature.as_bytes());
+    }
+    hasher.finalize().into()
+}
@@ -1,167 +1,1021 @@
-use eternal_account::{Account, AccountType};
+use eternal_account::{Account, AccountType, Store, StoreKey, Wallet};
 use eternal_vm::smart_contract::SmartContract;
 use serde::{Deserialize, Serialize};
 
-use crate::{block::Block, transaction::Transaction};
-use eternal_vm::WorldState;
+pub use crate::consensus::Consensus;
+use crate::{
+    block::{Block, BlockHeader},
+    consensus::{AuthoritySet, ConsensusEngine, ProofOfStake},
+    domain::{tagged_hasher, update_optional, CONTRACT_ADDRESS_TAG},
+    error::{BlockError, LookupError, SnapshotError, SpecError, TxError, VoteError},
+    finality::{is_supermajority, Checkpoint, FinalityGadget, Vote, VoteKind},
+    index::{ChainIndex, Receipt, TxLocation},
+    limits::{serialized_size, Limits, BLOCK_HEADER_RESERVE},
+    merkle,
+    pos::{release_unbonded, ValidatorSet},
+    proof::{AccountProof, AuthorityProof, BalanceProof, StorageProof, TransactionProof},
+    pruning::Pruning,
+    snapshot::{
+        check_contracts, entry, link_headers, verify_headers, Snapshot, SnapshotState,
+        CHUNK_ACCOUNTS,
+    },
+    spec::{ChainSpec, GenesisState},
+    state::{
+        account_proof, authority_proof, state_root, store_proof, store_root, token_proof,
+        token_root,
+    },
+    time::{median_time_past, now_millis, MAX_FUTURE_DRIFT_MS},
+    transaction::Transaction,
+};
+use eternal_vm::{ledger::TokenLedger, VmError, WorldState};
+use sha2::Digest;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Blockchain {
+    /// The headers of every block, the genesis block has height 0
+    #[serde(default)]
+    pub headers: Vec<BlockHeader>,
+    /// The blocks from [`Blockchain::first_block_height`] on, older blocks are only
+    /// known by their header
     pub blocks: Vec<Block>,
+    /// Height of the first block in `blocks`
+    #[serde(default)]
+    base: usize,
     #[serde(skip_serializing, skip_deserializing)]
     pub smart_contracts: HashMap<String, SmartContract>,
     pub accounts: HashMap<String, Account>,
+    /// The token balances of every contract
+    #[serde(skip_serializing, skip_deserializing)]
+    pub ledger: TokenLedger,
     pub temp: Vec<String>,
     pub pending_transactions: Vec<Transaction>,
+    pub chain_id: u64,
+    pub consensus: Consensus,
+    pub limits: Limits,
+    /// The current authorities (proof of authority only)
+    pub authorities: AuthoritySet,
+    /// The latest block committed by more than 2/3 of the stake
+    pub finalized: Option<Checkpoint>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub finality: FinalityGadget,
+    /// Lookup tables over the blocks, see [`Blockchain::rebuild_index`]
+    #[serde(skip_serializing, skip_deserializing)]
+    index: ChainIndex,
+    /// The state after the block at `state_base`, used to re-derive the state on a reorg
+    /// and for [`Blockchain::state_at`]
+    #[serde(skip_serializing, skip_deserializing)]
+    base_state: Option<(GenesisState, AuthoritySet)>,
+    /// Height of the block `base_state` belongs to, never below `base`
+    #[serde(default)]
+    state_base: usize,
+    /// A snapshot of the state is taken at every multiple of this height, never if 0
+    #[serde(default)]
+    pub snapshot_interval: usize,
+    /// The snapshots taken since the last [`Blockchain::take_snapshots`]
+    #[serde(skip_serializing, skip_deserializing)]
+    snapshots: Vec<Snapshot>,
+    /// What is kept of the history, see [`Pruning`]
+    #[serde(default)]
+    pub pruning: Pruning,
+    /// The states after the recent snapshot heights, oldest first. The base state moves
+    /// on to them as the chain is pruned.
+    #[serde(skip_serializing, skip_deserializing)]
+    checkpoints: Vec<(usize, (GenesisState, AuthoritySet))>,
 }
 
 impl Blockchain {
-    pub fn new() -> Self {
+    /// Will create the chain with the genesis block and state of the spec.
+    /// Fails if the spec expects a different genesis hash.
+    pub fn from_spec(spec: &ChainSpec) -> Result<Self, SpecError> {
+        let (accounts, smart_contracts, ledger) = spec.genesis_state()?;
+
+        let mut chain = Self::with_consensus(spec.chain_id, spec.consensus.clone());
+        chain.limits = spec.limits.clone();
+        chain.accounts = accounts.clone();
+        chain.smart_contracts = smart_contracts.clone();
+        chain.ledger = ledger.clone();
+        chain.base_state = Some((
+            (accounts, smart_contracts, ledger),
+            chain.authorities.clone(),
+        ));
+        chain.push_block(spec.genesis_block()?);
+
+        chain.check_genesis(spec)?;
+        Ok(chain)
+    }
+
+    /// Will create the chain from a snapshot of the state after the given block. The
+    /// headers of the blocks before it have to lead from the genesis block of the spec to
+    /// the block, see [`verify_headers`], which has to commit to the snapshot's state.
+    /// Only the blocks from there on are stored.
+    pub fn from_snapshot(
+        spec: &ChainSpec,
+        headers: &[BlockHeader],
+        block: Block,
+        snapshot: &Snapshot,
+    ) -> Result<Self, SnapshotError> {
+        // The sealers of proof of authority and proof of stake chains follow from the state
+        let header = block.header();
+        let state = snapshot.restore(spec.chain_id, headers.len(), &header)?;
+        verify_headers(spec, headers.iter().chain([&header]), &state)?;
+        Self::restore(spec, headers, block, state)
+    }
+
+    /// Will create the chain from a snapshot the node took itself, e.g. to prune the
+    /// blocks before it. The seals of the headers are not checked again.
+    pub fn from_own_snapshot(
+        spec: &ChainSpec,
+        headers: &[BlockHeader],
+        block: Block,
+        snapshot: &Snapshot,
+    ) -> Result<Self, SnapshotError> {
+        let header = block.header();
+        link_headers(spec, headers.iter().chain([&header]))?;
+        let state = snapshot.restore(spec.chain_id, headers.len(), &header)?;
+        Self::restore(spec, headers, block, state)
+    }
+
+    fn restore(
+        spec: &ChainSpec,
+        headers: &[BlockHeader],
+        block: Block,
+        state: SnapshotState,
+    ) -> Result<Self, SnapshotError> {
+        check_contracts(spec, &state)?;
+
+        let mut chain = Self::with_consensus(spec.chain_id, spec.consensus.clone());
+        chain.limits = spec.limits.clone();
+        chain.accounts = state.accounts;
+        chain.smart_contracts = state.smart_contracts;
+        chain.ledger = state.ledger;
+        chain.authorities = state.authorities;
+        chain.headers = headers.to_vec();
+        chain.base = headers.len();
+        chain.state_base = headers.len();
+        chain.rebuild_index();
+        chain.push_block(block.clone());
+
+        // The snapshot holds the state the block commits to, before the engine's bookkeeping
+        chain.engine().finalize(&mut chain, &block);
+        chain.base_state = Some((chain.current_state(), chain.authorities.clone()));
+
+        Ok(chain)
+    }
+
+    /// Checks that the genesis block of the chain matches the spec
+    pub fn check_genesis(&self, spec: &ChainSpec) -> Result<(), SpecError> {
+        let actual = self
+            .headers
+            .first()
+            .and_then(|h| h.hash.clone())
+            .unwrap_or_default();
+        let expected = match &spec.genesis_hash {
+            Some(hash) => hash.clone(),
+            None => spec.genesis_block()?.hash.unwrap_or_default(),
+        };
+
+        if actual != expected {
+            return Err(SpecError::GenesisHashMismatch { expected, actual });
+        }
+
+        Ok(())
+    }
+
+    fn with_consensus(chain_id: u64, consensus: Consensus) -> Self {
         let accounts = HashMap::new();
         let scs = HashMap::new();
+        let authorities = consensus.authority_set();
         Self {
+            headers: Vec::new(),
             blocks: Vec::new(),
+            base: 0,
             accounts,
             temp: vec![],
             smart_contracts: scs,
+            ledger: TokenLedger::default(),
             pending_transactions: Vec::new(),
+            chain_id,
+            consensus,
+            limits: Limits::default(),
+            authorities,
+            finalized: None,
+            finality: FinalityGadget::new(),
+            index: ChainIndex::default(),
+            base_state: None,
+            state_base: 0,
+            snapshot_interval: 0,
+            snapshots: Vec::new(),
+            pruning: Pruning::default(),
+            checkpoints: Vec::new(),
         }
     }
 
-    pub fn get_bob_alice(&mut self) -> (String, String) {
-        let mut alice = Account::new(AccountType::User);
-        let mut bob = Account::new(AccountType::User);
-        bob.tokens = 100_000;
-        alice.tokens = 100_00;
-        println!("\n{}\n{}", bob.generate_adress(), alice.generate_adress());
+    /// Will return the engine enforcing the chain's consensus rules
+    pub fn engine(&self) -> Box<dyn ConsensusEngine> {
+        self.consensus.engine()
+    }
 
-        let alice_addr = alice.generate_adress().clone();
-        let bob_arr = bob.generate_adress().clone();
+    /// Will return the validators of the current state (empty if not running proof of stake)
+    pub fn validator_set(&self) -> ValidatorSet {
+        match self.consensus {
+            Consensus::ProofOfStake { min_stake } => {
+                ProofOfStake::new(min_stake).validator_set(self)
+            }
+            _ => ValidatorSet::default(),
+        }
+    }
 
-        self.accounts.insert(alice.generate_adress(), alice);
-        self.accounts.insert(bob.generate_adress(), bob);
+    /// Will return the validator expected to propose the next block
+    pub fn next_proposer(&self) -> Option<String> {
+        match self.consensus {
+            Consensus::ProofOfStake { min_stake } => {
+                ProofOfStake::new(min_stake).next_proposer(self)
+            }
+            _ => None,
+        }
+    }
 
-        (bob_arr, alice_addr)
+    /// Will return the nonce the next transaction of the address has to carry, after the
+    /// pending transactions of the address
+    pub fn next_nonce(&self, address: &str) -> u128 {
+        let nonce = self
+            .accounts
+            .get(address)
+            .map_or(0, |account| account.nonce);
+        nonce + self.pending_from(address) + 1
     }
 
-    pub fn get_block(&self, hash: String) -> Block {
-        let binding = self.blocks.clone().to_owned();
-        let block: &Block = binding
+    fn pending_from(&self, address: &str) -> u128 {
+        self.pending_transactions
             .iter()
-            .find(|b| b.hash == Some(hash.clone()))
-            .unwrap();
+            .filter(|transaction| transaction.from == address)
+            .count() as u128
+    }
+
+    /// Checks that the transaction belongs to this chain, is not expired at the given time
+    /// and carries the sender's next nonce, after `queued` other transactions of the sender
+    fn check_transaction_context(
+        &self,
+        transaction: &Transaction,
+        timestamp: u64,
+        queued: u128,
+    ) -> Result<(), TxError> {
+        if transaction.chain_id != self.chain_id {
+            return Err(TxError::WrongChain {
+                expected: self.chain_id,
+                actual: transaction.chain_id,
+            });
+        }
+
+        if let Some(valid_until) = transaction.valid_until {
+            if transaction.is_expired(timestamp) {
+                return Err(TxError::Expired {
+                    valid_until,
+                    timestamp,
+                });
+            }
+        }
+
+        // Every nonce is used once, so a signed transaction can not be replayed
+        let account = self
+            .accounts
+            .get(&transaction.from)
+            .ok_or_else(|| TxError::SenderNotFound(transaction.from.clone()))?;
+        let expected = account.nonce + queued + 1;
+        if transaction.nonce != expected {
+            return Err(TxError::InvalidNonce {
+                expected,
+                actual: transaction.nonce,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Will queue a transaction for the next blocks. Transactions of other chains,
+    /// breaking the limits, expired ones, duplicates and ones not carrying the nonce after
+    /// the pending transactions of the sender are rejected.
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), TxError> {
+        self.limits.check_transaction(&transaction)?;
+        if self.pending_transactions.contains(&transaction) {
+            return Err(TxError::AlreadyPending);
+        }
+
+        let queued = self.pending_from(&transaction.from);
+        self.check_transaction_context(&transaction, now_millis(), queued)?;
+        self.pending_transactions.push(transaction);
+
+        Ok(())
+    }
+
+    /// Will take as many pending transactions as fit into the next block,
+    /// expired transactions are dropped
+    pub fn take_pending_transactions(&mut self) -> Vec<Transaction> {
+        let now = now_millis();
+        self.pending_transactions.retain(|t| !t.is_expired(now));
+
+        let mut size = BLOCK_HEADER_RESERVE;
+        let mut count = 0;
+        for transaction in &self.pending_transactions {
+            // One more byte for the separating comma
+            let transaction_size = serialized_size(transaction) + 1;
+            if count == self.limits.max_block_transactions
+                || size + transaction_size > self.limits.max_block_size
+            {
+                break;
+            }
+
+            size += transaction_size;
+            count += 1;
+        }
+
+        self.pending_transactions.drain(..count).collect()
+    }
 
-        let block = block.clone();
+    /// Will build a block of the given transactions on top of the head, seal it with
+    /// the signer's key and append it
+    pub fn produce_block(
+        &mut self,
+        transactions: Vec<Transaction>,
+        signer: &Wallet,
+    ) -> Result<Block, BlockError> {
+        let engine = self.engine();
+
+        let mut block = engine.prepare_block(self);
+        for transaction in transactions {
+            block.add_transaction(transaction);
+        }
+        self.commit_state(&mut block)?;
+        engine.seal(self, &mut block, signer)?;
+
+        self.append_block(block.clone())?;
+        Ok(block)
+    }
 
-        block
+    /// Will return the block with the given hash. Fails if the block is known but not
+    /// stored anymore.
+    pub fn get_block(&self, hash: &str) -> Result<Option<&Block>, LookupError> {
+        match self.index.block_height(hash) {
+            Some(height) => self.get_block_by_height(height),
+            None => Ok(None),
+        }
     }
 
-    pub fn append_block(&mut self, block: Block) -> Result<(), String> {
-        // The genesis block may create user out of nowhere,
-        // and also may do some other things
-        let is_genesis = self.len() == 0;
+    /// Will return the block at the given height, the genesis block has height 0.
+    /// Fails for the blocks before [`Blockchain::first_block_height`].
+    pub fn get_block_by_height(&self, height: usize) -> Result<Option<&Block>, LookupError> {
+        match height.checked_sub(self.base) {
+            Some(offset) => Ok(self.blocks.get(offset)),
+            None => Err(LookupError::BlockPruned {
+                height,
+                first: self.base,
+            }),
+        }
+    }
+
+    /// True if the block with the given hash is part of the chain, stored or not
+    pub fn contains_block(&self, hash: &str) -> bool {
+        self.index.block_height(hash).is_some()
+    }
+
+    /// Will return the height of the block with the given hash, stored or not
+    pub fn get_block_height(&self, hash: &str) -> Option<usize> {
+        self.index.block_height(hash)
+    }
+
+    /// Will return the header of the block at the given height, also for blocks which are
+    /// not stored
+    pub fn get_header(&self, height: usize) -> Option<&BlockHeader> {
+        self.headers.get(height)
+    }
+
+    /// Will return the height of the first stored block, 0 unless the chain was started
+    /// from a snapshot or prunes its blocks
+    pub fn first_block_height(&self) -> usize {
+        self.base
+    }
+
+    /// Will return the height of the first block whose state can still be derived, see
+    /// [`Blockchain::state_at`]
+    pub fn first_state_height(&self) -> usize {
+        // Only the genesis state is known from before its block's bookkeeping
+        match self.state_base {
+            0 => 0,
+            height => height + 1,
+        }
+    }
+
+    /// Will return the transaction with the given hash and where it is stored. Unknown
+    /// transactions which are not pending may be in blocks which are not stored, that is
+    /// an error then.
+    pub fn get_transaction(
+        &self,
+        hash: &str,
+    ) -> Result<Option<(&Transaction, TxLocation)>, LookupError> {
+        let Some(location) = self.locate_transaction(hash)? else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .transaction_at(location)
+            .map(|transaction| (transaction, location)))
+    }
+
+    /// Will return the receipt of an included transaction
+    pub fn get_receipt(&self, hash: &str) -> Result<Option<Receipt>, LookupError> {
+        let Some(location) = self.locate_transaction(hash)? else {
+            return Ok(None);
+        };
+        let Some(block) = self.get_block_by_height(location.height)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Receipt {
+            transaction_hash: hash.to_string(),
+            block_hash: block.hash.clone().unwrap_or_default(),
+            location,
+            timestamp: block.timestamp,
+        }))
+    }
+
+    fn locate_transaction(&self, hash: &str) -> Result<Option<TxLocation>, LookupError> {
+        if let Some(location) = self.index.transaction_location(hash) {
+            return Ok(Some(location));
+        }
+
+        // Pending transactions are simply not included yet
+        let pending = self.pending_transactions.iter().any(|t| t.hash() == hash);
+        if self.base > 0 && !pending {
+            return Err(LookupError::TransactionPruned { first: self.base });
+        }
+        Ok(None)
+    }
+
+    /// Will return the state the block at the given height commits to by its state root.
+    /// It is re-derived by executing the stored blocks since the oldest kept state, which
+    /// fails for heights before [`Blockchain::first_state_height`].
+    pub fn state_at(&self, height: usize) -> Result<Option<SnapshotState>, LookupError> {
+        let Some(header) = self.headers.get(height) else {
+            return Ok(None);
+        };
+        let first = self.first_state_height();
+        if height < first {
+            return Err(LookupError::StatePruned { height, first });
+        }
+
+        // The bookkeeping of the head's engine usually leaves the state as it is
+        if height + 1 == self.len() && header.state_root.as_ref() == Some(&self.state_root()) {
+            return Ok(Some(SnapshotState {
+                accounts: self.accounts.clone(),
+                smart_contracts: self.smart_contracts.clone(),
+                ledger: self.ledger.clone(),
+                authorities: self.authorities.clone(),
+            }));
+        }
+
+        let ((accounts, smart_contracts, ledger), authorities) =
+            self.base_state.clone().unwrap_or_default();
+        let mut scratch = Self::with_consensus(self.chain_id, self.consensus.clone());
+        scratch.accounts = accounts;
+        scratch.smart_contracts = smart_contracts;
+        scratch.ledger = ledger;
+        scratch.authorities = authorities;
+        scratch.headers = vec![self.headers[self.state_base].clone()];
+
+        let engine = self.engine();
+        for current in self.state_base + 1..=height {
+            let Some(block) = self.get_block_by_height(current)? else {
+                return Ok(None);
+            };
+            scratch.execute_transactions(block)?;
+            if current < height {
+                engine.finalize(&mut scratch, block);
+                scratch.headers = vec![block.header()];
+            }
+        }
+
+        let actual = scratch.state_root();
+        if header.state_root.as_ref() != Some(&actual) {
+            return Err(BlockError::StateRootMismatch {
+                expected: header.state_root.clone().unwrap_or_default(),
+                actual,
+            }
+            .into());
+        }
+
+        Ok(Some(SnapshotState {
+            accounts: scratch.accounts,
+            smart_contracts: scratch.smart_contracts,
+            ledger: scratch.ledger,
+            authorities: scratch.authorities,
+        }))
+    }
+
+    /// Will return the proof that the transaction is included in its block
+    pub fn prove_transaction(&self, hash: &str) -> Result<Option<TransactionProof>, LookupError> {
+        let Some((transaction, location)) = self.get_transaction(hash)? else {
+            return Ok(None);
+        };
+        let Some(block) = self.get_block_by_height(location.height)? else {
+            return Ok(None);
+        };
+
+        Ok(block
+            .transaction_proof(location.index)
+            .map(|proof| TransactionProof {
+                transaction: transaction.clone(),
+                height: location.height,
+                block_hash: block.hash.clone().unwrap_or_default(),
+                proof,
+            }))
+    }
+
+    /// Will return the proof of the account, the store entries of the given keys and the
+    /// balances of the given token contracts as of the state the block at the given
+    /// height commits to, none if there is no such account
+    pub fn prove_account(
+        &self,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+        height: usize,
+    ) -> Result<Option<AccountProof>, LookupError> {
+        let Some(state) = self.state_at(height)? else {
+            return Ok(None);
+        };
+        let (accounts, smart_contracts, ledger) =
+            (&state.accounts, &state.smart_contracts, &state.ledger);
+        let Some(proof) = account_proof(
+            accounts,
+            smart_contracts,
+            ledger,
+            &state.authorities,
+            address,
+        ) else {
+            return Ok(None);
+        };
+
+        let store = &accounts[address].store;
+        let storage = keys
+            .iter()
+            .filter_map(|key| {
+                let value = store.get_bytes(&key.namespace, &key.key)?;
+                Some(StorageProof {
+                    key: key.clone(),
+                    value: value.to_vec(),
+                    proof: store_proof(store, key)?,
+                })
+            })
+            .collect();
+        let balances = contracts
+            .iter()
+            .filter_map(|contract| {
+                let proof = token_proof(ledger, address, contract)?;
+                Some(BalanceProof {
+                    contract: contract.clone(),
+                    balance: ledger.balance(contract, address),
+                    proof,
+                })
+            })
+            .collect();
+
+        let contract = smart_contracts.get(address);
+        let mut account = entry(address, &accounts[address], contract, ledger);
+        account.store.clear();
+        account.balances.clear();
+        Ok(Some(AccountProof {
+            height,
+            block_hash: self.headers[height].hash.clone().unwrap_or_default(),
+            account,
+            store_root: merkle::to_hex(&store_root(store)),
+            token_root: merkle::to_hex(&token_root(ledger, address)),
+            proof,
+            storage,
+            balances,
+        }))
+    }
+
+    /// Will return the proof of the authorities which sealed the block at the given
+    /// height, none if the chain has no authorities
+    pub fn prove_authorities(&self, height: usize) -> Result<Option<AuthorityProof>, LookupError> {
+        let Some(state) = self.state_at(height)? else {
+            return Ok(None);
+        };
+        let Some(proof) = authority_proof(
+            &state.accounts,
+            &state.smart_contracts,
+            &state.ledger,
+            &state.authorities,
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(AuthorityProof {
+            height,
+            block_hash: self.headers[height].hash.clone().unwrap_or_default(),
+            authorities: state.authorities,
+            proof,
+        }))
+    }
+
+    /// Will return every transaction sent or received by the address, oldest first.
+    /// Only the stored blocks are searched.
+    pub fn get_account_history(&self, address: &str) -> Vec<(&Transaction, TxLocation)> {
+        self.index
+            .account_history(address)
+            .iter()
+            .filter_map(|location| {
+                self.transaction_at(*location)
+                    .map(|transaction| (transaction, *location))
+            })
+            .collect()
+    }
+
+    fn transaction_at(&self, location: TxLocation) -> Option<&Transaction> {
+        self.blocks
+            .get(location.height.checked_sub(self.base)?)?
+            .transactions
+            .get(location.index)
+    }
+
+    /// Will rebuild the lookup tables, needed after the blocks were loaded or replaced directly
+    pub fn rebuild_index(&mut self) {
+        self.index = ChainIndex::from_blocks(self.base, &self.blocks);
+        for (height, header) in self.headers.iter().enumerate().take(self.base) {
+            self.index.insert_header(height, header);
+        }
+    }
+
+    pub(crate) fn push_block(&mut self, block: Block) {
+        self.index.insert_block(self.len(), &block);
+        self.headers.push(block.header());
+        self.blocks.push(block);
+    }
+
+    /// Will hand out the snapshots taken while appending blocks, see
+    /// [`Blockchain::snapshot_interval`]
+    pub fn take_snapshots(&mut self) -> Vec<Snapshot> {
+        std::mem::take(&mut self.snapshots)
+    }
+
+    pub fn append_block(&mut self, block: Block) -> Result<(), BlockError> {
+        // The genesis block is created from the chain spec, never appended
+        if self.len() == 0 {
+            return Err(BlockError::MissingGenesis);
+        }
 
         // Check if the hash matches the transactions
         if !block.verify_own_hash() {
-            return Err("The block hash is mismatching! (Code: 93820394)".into());
+            return Err(BlockError::HashMismatch);
         }
 
         // Check if the newly added block is meant to be appended onto the last block
         if !(block.prev == self.get_last_block_hash()) {
-            return Err("The new block has to point to the previous block (Code: 3948230)".into());
+            return Err(BlockError::InvalidPrev);
         }
 
         // There has to be at least one transaction inside the queue
         if block.get_transaction_count() == 0 {
-            return Err("There has to be at least one transaction \
-            inside the block! (Code: 9482930)"
-                .into());
+            return Err(BlockError::Empty);
         }
 
-        // Reject block having nonces that are already used (Prevent reply attacks etc.)
-        // @Todo (Will skip that for simplicity)
+        // Every node has to be able to handle the block
+        self.limits.check_block(&block)?;
+
+        // The timestamp may neither go back behind the recent blocks nor run ahead of the clock
+        if let Some(median) = median_time_past(&self.headers) {
+            if block.timestamp < median {
+                return Err(BlockError::TimestampTooOld {
+                    timestamp: block.timestamp,
+                    median,
+                });
+            }
+        }
+        let now = now_millis();
+        if block.timestamp > now.saturating_add(MAX_FUTURE_DRIFT_MS) {
+            return Err(BlockError::TimestampInFuture {
+                timestamp: block.timestamp,
+                now,
+                max_drift: MAX_FUTURE_DRIFT_MS,
+            });
+        }
+
+        // The consensus rules decide who may append a block
+        let engine = self.engine();
+        engine.verify_header(self, &block)?;
 
         // This is expensive and just used for rollback if some transactions succeed whilst
         // others don't (prevent inconsistent states)
         // Arguably, that could be implemented more resource-aware
-        let old_state = self.accounts.clone();
+        let old_state = self.current_state();
+
+        let result = self.execute_transactions(&block).and_then(|outputs| {
+            // Every node has to end up with the state the proposer committed to
+            let state_root = self.state_root();
+            if block.state_root.as_ref() != Some(&state_root) {
+                return Err(BlockError::StateRootMismatch {
+                    expected: block.state_root.clone().unwrap_or_default(),
+                    actual: state_root,
+                });
+            }
+            Ok(outputs)
+        });
+        match result {
+            Ok(outputs) => self.temp.extend(outputs),
+            Err(err) => {
+                (self.accounts, self.smart_contracts, self.ledger) = old_state;
+                return Err(err);
+            }
+        }
+
+        // Taken before the engine's bookkeeping, so the snapshot matches the block's state root
+        let height = self.len();
+        let snapshot_height = height.checked_rem(self.snapshot_interval) == Some(0);
+        if snapshot_height {
+            let snapshot = Snapshot::new(self, height, &block.header(), CHUNK_ACCOUNTS);
+            self.snapshots.push(snapshot);
+        }
+
+        engine.finalize(self, &block);
+        self.push_block(block);
+
+        if snapshot_height && self.pruning != Pruning::Archive {
+            let state = (self.current_state(), self.authorities.clone());
+            self.checkpoints.push((height, state));
+        }
+        self.prune();
+
+        Ok(())
+    }
+
+    /// Will move the base state on to the newest checkpoint which still leaves enough
+    /// recent blocks and, if blocks are pruned as well, drop the blocks before it
+    fn prune(&mut self) {
+        let Some(recent) = self.pruning.recent_state() else {
+            return;
+        };
+        let Some(limit) = self.len().checked_sub(recent + 1) else {
+            return;
+        };
+        let Some(position) = self.checkpoints.iter().rposition(|(h, _)| *h <= limit) else {
+            return;
+        };
+
+        let mut checkpoints = self.checkpoints.split_off(position);
+        let (height, state) = checkpoints.remove(0);
+        self.checkpoints = checkpoints;
+        self.state_base = height;
+        self.base_state = Some(state);
+
+        if self.pruning.prunes_blocks() && height > self.base {
+            let pruned: Vec<Block> = self.blocks.drain(..height - self.base).collect();
+            for (height, block) in (self.base..).zip(&pruned) {
+                self.index.remove_transactions(height, block);
+            }
+            self.base = height;
+        }
+    }
+
+    /// Will release the unbonded stake and execute each transaction of the block on the
+    /// current state and return their outputs. The state is left half-way changed if a
+    /// transaction fails.
+    fn execute_transactions(&mut self, block: &Block) -> Result<Vec<String>, BlockError> {
+        self.begin_block();
 
-        // Execute each transaction
+        let mut outputs = Vec::new();
         for (i, transaction) in block.transactions.iter().enumerate() {
-            // Execute the transaction
-
-            match transaction.execute(self, &is_genesis) {
-                Err(err) => {
-                    self.accounts = old_state;
-
-                    return Err(format!(
-                        "Could not execute transaction {} due to `{}`. Rolling back \
-                    (Code: 38203984)",
-                        i + 1,
-                        err
-                    ));
-                }
-                Ok(out) => self.temp.push(out),
+            let output = self
+                .execute_transaction(transaction, block.timestamp)
+                .map_err(|source| BlockError::Transaction {
+                    index: i + 1,
+                    source,
+                })?;
+            outputs.push(output);
+        }
+
+        Ok(outputs)
+    }
+
+    /// Will release the unbonding stake due at the height of the next block, before any
+    /// of its transactions is executed
+    pub(crate) fn begin_block(&mut self) {
+        let height = self.block_height();
+        release_unbonded(&mut self.accounts, height);
+    }
+
+    /// Transactions of other chains, expired ones and ones not carrying the sender's next
+    /// nonce can not be executed. Executing a transaction uses up its nonce.
+    pub(crate) fn execute_transaction(
+        &mut self,
+        transaction: &Transaction,
+        timestamp: u64,
+    ) -> Result<String, TxError> {
+        self.check_transaction_context(transaction, timestamp, 0)?;
+        let output = transaction.execute(self)?;
+
+        // The context check made sure the sender exists
+        if let Some(account) = self.accounts.get_mut(&transaction.from) {
+            account.nonce = transaction.nonce;
+        }
+        Ok(output)
+    }
+
+    /// Will return the root of the current state, see [`crate::state`]
+    pub fn state_root(&self) -> String {
+        state_root(
+            &self.accounts,
+            &self.smart_contracts,
+            &self.ledger,
+            &self.authorities,
+        )
+    }
+
+    /// Will return a copy of the accounts, contracts and token balances
+    pub(crate) fn current_state(&self) -> GenesisState {
+        (
+            self.accounts.clone(),
+            self.smart_contracts.clone(),
+            self.ledger.clone(),
+        )
+    }
+
+    /// Will execute the transactions of a produced block on a copy of the state and commit
+    /// the block to the resulting state root. The block still has to be sealed and appended.
+    pub fn commit_state(&self, block: &mut Block) -> Result<(), BlockError> {
+        // Transactions only see the accounts, the contracts, the token balances, the
+        // headers (for the height) and the head
+        let mut scratch = Self::with_consensus(self.chain_id, self.consensus.clone());
+        (scratch.accounts, scratch.smart_contracts, scratch.ledger) = self.current_state();
+        scratch.authorities = self.authorities.clone();
+        scratch.headers = self.headers.clone();
+        scratch.blocks = self.blocks.last().cloned().into_iter().collect();
+
+        scratch.execute_transactions(block)?;
+        block.set_state_root(scratch.state_root());
+
+        Ok(())
+    }
+
+    /// Will replace every block from `fork_height` on with the given blocks. The state is
+    /// re-derived by executing the chain again, nothing changes if any block is invalid.
+    /// Finalized blocks can never be replaced.
+    pub fn reorg(&mut self, fork_height: usize, blocks: Vec<Block>) -> Result<(), BlockError> {
+        if let Some(finalized) = &self.finalized {
+            if fork_height <= finalized.height {
+                return Err(BlockError::ReorgBelowFinalized {
+                    fork_height,
+                    finalized: finalized.height,
+                });
             }
         }
+        // Neither the genesis block nor the blocks up to the oldest kept state can be replaced
+        if fork_height <= self.state_base || fork_height > self.len() {
+            return Err(BlockError::UnknownForkPoint(fork_height));
+        }
 
-        self.blocks.push(block);
+        let ((accounts, smart_contracts, ledger), authorities) =
+            self.base_state.clone().unwrap_or_default();
+        let mut rebuilt = Self::with_consensus(self.chain_id, self.consensus.clone());
+        rebuilt.limits = self.limits.clone();
+        rebuilt.accounts = accounts;
+        rebuilt.smart_contracts = smart_contracts;
+        rebuilt.ledger = ledger;
+        rebuilt.authorities = authorities;
+        rebuilt.base_state = self.base_state.clone();
+        rebuilt.state_base = self.state_base;
+        rebuilt.snapshot_interval = self.snapshot_interval;
+        rebuilt.pruning = self.pruning;
+        rebuilt.headers = self.headers[..=self.state_base].to_vec();
+        rebuilt.blocks = self.blocks[..=self.state_base - self.base].to_vec();
+        rebuilt.base = self.base;
+        rebuilt.rebuild_index();
+
+        let kept = self.blocks[self.state_base - self.base + 1..fork_height - self.base]
+            .iter()
+            .cloned();
+        for block in kept.chain(blocks) {
+            rebuilt.append_block(block)?;
+        }
+
+        rebuilt.pending_transactions = std::mem::take(&mut self.pending_transactions);
+        rebuilt.finalized = self.finalized.take();
+        rebuilt.finality = std::mem::take(&mut self.finality);
+        *self = rebuilt;
 
         Ok(())
     }
 
-    /// Will return the amount of blocks currently stored
+    /// Will switch to the fork if the consensus engine prefers it over the current chain.
+    /// Returns whether the fork was adopted.
+    pub fn import_fork(
+        &mut self,
+        fork_height: usize,
+        blocks: Vec<Block>,
+    ) -> Result<bool, BlockError> {
+        if !self.engine().fork_choice(self, fork_height, &blocks) {
+            return Ok(false);
+        }
+
+        self.reorg(fork_height, blocks)?;
+        Ok(true)
+    }
+
+    /// Will record a validator's vote. Returns the new checkpoint once more than 2/3
+    /// of the stake precommitted to the same block. Precommits are refused until more
+    /// than 2/3 of the stake prevoted for the block in the same round.
+    pub fn add_vote(&mut self, vote: Vote) -> Result<Option<Checkpoint>, VoteError> {
+        let block_hash = self.headers.get(vote.height).and_then(|h| h.hash.as_ref());
+        if block_hash != Some(&vote.block_hash) {
+            return Err(VoteError::UnknownBlock(vote.block_hash));
+        }
+
+        // Votes for blocks that are already final do not matter anymore
+        if let Some(finalized) = &self.finalized {
+            if vote.height <= finalized.height {
+                return Ok(None);
+            }
+        }
+
+        let account = self
+            .accounts
+            .get(&vote.validator)
+            .ok_or_else(|| VoteError::NotAValidator(vote.validator.clone()))?;
+        if !vote.verify(account) {
+            return Err(VoteError::InvalidSignature);
+        }
+
+        let validators = self.validator_set();
+        let (kind, height, block_hash) = (vote.kind, vote.height, vote.block_hash.clone());
+        // Validators may only precommit once more than 2/3 of the stake prevoted for the block
+        if kind == VoteKind::Precommit
+            && !self
+                .finality
+                .has_polka(&validators, height, vote.round, &block_hash)
+        {
+            return Err(VoteError::NoPolka {
+                height,
+                round: vote.round,
+            });
+        }
+        let stake = self.finality.add_vote(vote, &validators)?;
+
+        if kind == VoteKind::Precommit && is_supermajority(stake, validators.total_stake()) {
+            let checkpoint = Checkpoint {
+                height,
+                hash: block_hash,
+            };
+            self.finalized = Some(checkpoint.clone());
+            self.finality.prune(height);
+
+            return Ok(Some(checkpoint));
+        }
+
+        Ok(None)
+    }
+
+    /// Will return the amount of blocks of the chain, including those only known by their header
     pub fn len(&self) -> usize {
-        self.blocks.len()
+        self.headers.len()
     }
 
     /// Will return the hash of the last block
     pub fn get_last_block_hash(&self) -> Option<String> {
-        if self.len() == 0 {
-            return None;
-        }
-
-        self.blocks[self.len() - 1].hash.clone()
+        self.headers.last().and_then(|h| h.hash.clone())
     }
 
-    pub fn check_validity(&self) -> Result<(), String> {
-        for (block_num, block) in self.blocks.iter().enumerate() {
+    pub fn check_validity(&self) -> Result<(), BlockError> {
+        for (block_num, block) in (self.base..).zip(&self.blocks) {
             // Check if block saved hash matches to calculated hash
             if !block.verify_own_hash() {
-                return Err(format!(
-                    "Stored hash for Block #{} \
-                    does not match calculated hash (Code: 665234234)",
-                    block_num + 1
-                )
-                .into());
+                return Err(BlockError::StoredHashMismatch {
+                    block: block_num + 1,
+                });
             }
 
             // Check previous black hash points to actual previous block
             if block_num == 0 {
                 // Genesis block should point to nowhere
                 if block.prev.is_some() {
-                    return Err("The genesis block has a previous hash set which \
-                     it shouldn't Code :394823098"
-                        .into());
+                    return Err(BlockError::GenesisHasPrev);
                 }
             } else {
                 // Non genesis blocks should point to previous blocks hash (which is validated before)
-                if block.prev.is_none() {
-                    return Err(format!("Block #{} has no previous hash set", block_num + 1).into());
-                }
+                let Some(prev_hash_proposed) = block.prev.as_ref() else {
+                    return Err(BlockError::MissingPrev {
+                        block: block_num + 1,
+                    });
+                };
+
+                if block.prev != self.headers[block_num - 1].hash {
+                    // Store the values locally to use them within the error message on failure
+                    let prev_hash_actual = self.headers[block_num - 1].hash.clone();
 
-                // Store the values locally to use them within the error message on failure
-                let prev_hash_proposed = block.prev.as_ref().unwrap();
-                let prev_hash_actual = self.blocks[block_num - 1].hash.as_ref().unwrap();
-
-                if !(&block.prev == &self.blocks[block_num - 1].hash) {
-                    return Err(format!(
-                        "Block #{} is not connected to previous block (Hashes do \
-                    not match. Should be `{}` but is `{}`)",
-                        block_num, prev_hash_proposed, prev_hash_actual
-                    )
-                    .into());
+                    return Err(BlockError::NotConnected {
+                        block: block_num,
+                        expected: prev_hash_proposed.clone(),
+                        actual: prev_hash_actual.unwrap_or_default(),
+                    });
                 }
             }
 
@@ -170,12 +1024,10 @@ impl Blockchain {
                 // Careful! With that implementation an unsigned message will always
                 // be valid! You may remove the first check to only accept signed transactions
                 if transaction.is_signed() && !transaction.check_signature() {
-                    return Err(format!(
-                        "Transaction #{} for Block #{} has an invalid signature \
-                    (Code: 4398239048)",
-                        transaction_num + 1,
-                        block_num + 1
-                    ));
+                    return Err(BlockError::InvalidSignature {
+                        block: block_num + 1,
+                        transaction: transaction_num + 1,
+                    });
                 }
             }
         }
@@ -196,15 +1048,14 @@ impl WorldState for Blockchain {
         self.accounts.get(id)
     }
 
-    fn create_account(&mut self, account_type: AccountType) -> Result<String, &'static str> {
-        let acc = Account::new(account_type);
-        let address = acc.generate_adress();
-        return if !self.get_user_ids().contains(&address) {
-            self.accounts.insert(address.clone(), acc);
-            Ok(address.clone())
-        } else {
-            Err("User already exists! (Code: 934823094)")
-        };
+    fn create_account(&mut self, account: Account) -> Result<String, VmError> {
+        let address = account.generate_adress();
+        if self.accounts.contains_key(&address) {
+            return Err(VmError::AccountExists(address));
+        }
+        self.accounts.insert(address.clone(), account);
+
+        Ok(address)
     }
 
     fn get_smart_contact_ids(&self) -> Vec<String> {
@@ -219,16 +1070,27 @@ impl WorldState for Blockchain {
         self.smart_contracts.get(id)
     }
 
-    fn create_smart_contact(
-        &mut self,
-        smart_contract: SmartContract,
-    ) -> Result<String, &'static str> {
-        let address = self.create_account(AccountType::SmartContract).unwrap();
+    /// Contracts have no key. Their address is derived from the head and the amount of
+    /// accounts, so every node executing the block derives the same.
+    fn create_smart_contact(&mut self, smart_contract: SmartContract) -> Result<String, VmError> {
+        let mut hasher = tagged_hasher(CONTRACT_ADDRESS_TAG);
+        let head = self.get_last_block_hash();
+        update_optional(&mut hasher, head.as_deref().map(str::as_bytes));
+        hasher.update((self.accounts.len() as u64).to_be_bytes());
+
+        let address = self.create_account(Account {
+            public_key: String::new(),
+            public_key_bytes: hasher.finalize().to_vec(),
+            store: Store::default(),
+            acc_type: AccountType::SmartContract,
+            tokens: 0,
+            nonce: 0,
+        })?;
         return if !self.get_smart_contact_ids().contains(&address) {
             self.smart_contracts.insert(address.clone(), smart_contract);
             Ok(address)
         } else {
-            Err("User Contract exists! (Code: 934823094)")
+            Err(VmError::ContractExists(address))
         };
     }
 
@@ -239,4 +1101,12 @@ impl WorldState for Blockchain {
     fn get_smart_contacts(&mut self) -> &mut HashMap<String, SmartContract> {
         &mut self.smart_contracts
     }
+
+    fn get_token_ledger(&mut self) -> &mut TokenLedger {
+        &mut self.ledger
+    }
+
+    fn block_height(&self) -> u64 {
+        self.len() as u64
+    }
 }
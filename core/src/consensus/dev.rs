@@ -0,0 +1,38 @@
+use eternal_account::Wallet;
+
+use super::{check_signed_by, sign_as, ConsensusEngine};
+use crate::{block::Block, blockchain::Blockchain, error::BlockError};
+
+/// A single authority signs every block as soon as it is produced.
+/// Only meant for local devnets and tests, there is nothing to agree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevAuthority {
+    /// Address of the only account allowed to seal blocks
+    pub authority: String,
+}
+
+impl DevAuthority {
+    pub fn new(authority: String) -> Self {
+        Self { authority }
+    }
+}
+
+impl ConsensusEngine for DevAuthority {
+    fn verify_header(&self, chain: &Blockchain, block: &Block) -> Result<(), BlockError> {
+        check_signed_by(chain, block, &self.authority)
+    }
+
+    fn seal(
+        &self,
+        _chain: &Blockchain,
+        block: &mut Block,
+        signer: &Wallet,
+    ) -> Result<(), BlockError> {
+        sign_as(block, signer, &self.authority)
+    }
+
+    /// The authority never forks its own chain
+    fn fork_choice(&self, _chain: &Blockchain, _fork_height: usize, _fork: &[Block]) -> bool {
+        false
+    }
+}
@@ -0,0 +1,131 @@
+use eternal_account::Wallet;
+use serde::{Deserialize, Serialize};
+
+use crate::{block::Block, blockchain::Blockchain, error::BlockError};
+
+mod dev;
+mod proof_of_authority;
+mod proof_of_stake;
+mod proof_of_work;
+
+pub use dev::DevAuthority;
+pub use proof_of_authority::{AuthoritySet, ProofOfAuthority, Proposal};
+pub use proof_of_stake::ProofOfStake;
+pub use proof_of_work::ProofOfWork;
+
+/// The rules deciding who may append a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Consensus {
+    /// The block hash has to start with `difficulty` zeros
+    ProofOfWork { difficulty: u32 },
+    /// Validators with at least `min_stake` take turns proposing blocks, weighted by stake
+    ProofOfStake { min_stake: u128 },
+    /// The authorities (public keys) take turns signing a block every `period_ms`,
+    /// further authorities are added or removed by their votes
+    ProofOfAuthority {
+        authorities: Vec<String>,
+        period_ms: u64,
+    },
+    /// A single authority signs every block, meant for local devnets
+    Dev { authority: String },
+}
+
+impl Consensus {
+    /// Will return the engine implementing the rules
+    pub fn engine(&self) -> Box<dyn ConsensusEngine> {
+        match self {
+            Consensus::ProofOfWork { difficulty } => Box::new(ProofOfWork::new(*difficulty)),
+            Consensus::ProofOfStake { min_stake } => Box::new(ProofOfStake::new(*min_stake)),
+            Consensus::ProofOfAuthority { period_ms, .. } => {
+                Box::new(ProofOfAuthority::new(*period_ms))
+            }
+            Consensus::Dev { authority } => Box::new(DevAuthority::new(authority.clone())),
+        }
+    }
+
+    /// Will return the authorities the chain starts with (empty if not running proof of authority)
+    pub fn authority_set(&self) -> AuthoritySet {
+        match self {
+            Consensus::ProofOfAuthority { authorities, .. } => {
+                AuthoritySet::new(authorities.clone())
+            }
+            _ => AuthoritySet::default(),
+        }
+    }
+}
+
+/// The consensus specific part of producing and importing blocks.
+///
+/// The blockchain takes care of the rules every chain shares (hashes, links and
+/// transactions), an engine only decides who may seal a block and which fork wins.
+pub trait ConsensusEngine {
+    /// Checks the consensus rules of a block before its transactions are executed
+    fn verify_header(&self, chain: &Blockchain, block: &Block) -> Result<(), BlockError>;
+
+    /// Will return an empty block on top of the current head
+    fn prepare_block(&self, chain: &Blockchain) -> Block {
+        Block::new(chain.get_last_block_hash())
+    }
+
+    /// Will seal a block once all transactions are added, so it passes `verify_header`
+    fn seal(
+        &self,
+        chain: &Blockchain,
+        block: &mut Block,
+        signer: &Wallet,
+    ) -> Result<(), BlockError>;
+
+    /// Applies the consensus state changes after the transactions of a block were executed
+    fn finalize(&self, _chain: &mut Blockchain, _block: &Block) {}
+
+    /// True if the fork replacing every block from `fork_height` on should become canonical
+    fn fork_choice(&self, chain: &Blockchain, fork_height: usize, fork: &[Block]) -> bool;
+}
+
+/// Checks that the block was proposed and signed by the expected account
+pub(crate) fn check_signed_by(
+    chain: &Blockchain,
+    block: &Block,
+    expected: &str,
+) -> Result<(), BlockError> {
+    let proposer = block.proposer.as_ref().ok_or(BlockError::MissingProposer)?;
+    if proposer != expected {
+        return Err(BlockError::UnexpectedProposer {
+            expected: expected.to_string(),
+            actual: proposer.clone(),
+        });
+    }
+
+    let valid_signature = match (chain.accounts.get(proposer), &block.signature, &block.hash) {
+        (Some(account), Some(signature), Some(hash)) => account.verify(hash.as_bytes(), signature),
+        _ => false,
+    };
+    if !valid_signature {
+        return Err(BlockError::InvalidProposerSignature);
+    }
+
+    Ok(())
+}
+
+/// Will sign the block if the signer is the expected proposer
+pub(crate) fn sign_as(
+    block: &mut Block,
+    signer: &Wallet,
+    expected: &str,
+) -> Result<(), BlockError> {
+    let address = signer.address().to_string();
+    if address != expected {
+        return Err(BlockError::UnexpectedProposer {
+            expected: expected.to_string(),
+            actual: address,
+        });
+    }
+
+    block.sign(signer);
+    Ok(())
+}
+
+/// The longest chain wins, the current chain is kept on a tie
+pub(crate) fn longest_chain(chain: &Blockchain, fork_height: usize, fork: &[Block]) -> bool {
+    fork_height + fork.len() > chain.len()
+}
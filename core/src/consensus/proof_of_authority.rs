@@ -0,0 +1,247 @@
+use eternal_account::{secp256k1::PublicKey, verify_signature, Address, Wallet};
+use serde::{Deserialize, Serialize};
+
+use super::{longest_chain, sign_as, ConsensusEngine};
+use crate::{
+    block::Block, blockchain::Blockchain, error::BlockError, transaction::TransactionData,
+};
+
+/// A pending proposal to add or remove an authority
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proposal {
+    /// Public key of the authority to add or remove
+    pub public_key: String,
+    pub add: bool,
+    /// Addresses of the authorities voting for the proposal
+    pub voters: Vec<String>,
+}
+
+/// The authorities allowed to seal blocks, changed by the votes of the authorities themselves
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuthoritySet {
+    /// Public keys of the authorities, sorted
+    authorities: Vec<String>,
+    proposals: Vec<Proposal>,
+}
+
+impl AuthoritySet {
+    /// Will create the set from the configured public keys
+    pub fn new(public_keys: Vec<String>) -> Self {
+        let mut public_keys: Vec<String> = public_keys.iter().map(|k| normalize(k)).collect();
+        public_keys.sort();
+        public_keys.dedup();
+
+        Self {
+            authorities: public_keys,
+            proposals: Vec::new(),
+        }
+    }
+
+    /// Will return the public keys of the authorities
+    pub fn authorities(&self) -> &[String] {
+        &self.authorities
+    }
+
+    /// Will return the open proposals
+    pub fn proposals(&self) -> &[Proposal] {
+        &self.proposals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.authorities.is_empty()
+    }
+
+    /// True if the address belongs to one of the authorities
+    pub fn is_authority(&self, address: &str) -> bool {
+        self.authorities
+            .iter()
+            .any(|key| address_of(key).as_deref() == Some(address))
+    }
+
+    /// Will return the public key of the authority whose turn it is to seal the block at `height`
+    pub fn in_turn(&self, height: usize) -> Option<&String> {
+        if self.authorities.is_empty() {
+            return None;
+        }
+
+        self.authorities.get(height % self.authorities.len())
+    }
+
+    /// Will count an authority's vote. The proposal passes once more than half of the
+    /// authorities voted for it. Votes of non-authorities and votes that would not
+    /// change anything are ignored, the last authority can not be removed.
+    ///
+    /// Returns true if the set changed.
+    pub fn vote(&mut self, voter: &str, public_key: &str, add: bool) -> bool {
+        let public_key = normalize(public_key);
+        let public_key = public_key.as_str();
+        let already_authority = self.authorities.iter().any(|k| k == public_key);
+        if !self.is_authority(voter) || already_authority == add {
+            return false;
+        }
+
+        // A voter may only support one side of a proposal
+        for proposal in self.proposals.iter_mut() {
+            if proposal.public_key == public_key && proposal.add != add {
+                proposal.voters.retain(|v| v != voter);
+            }
+        }
+        self.proposals.retain(|p| !p.voters.is_empty());
+
+        let index = match self
+            .proposals
+            .iter()
+            .position(|p| p.public_key == public_key && p.add == add)
+        {
+            Some(index) => index,
+            None => {
+                self.proposals.push(Proposal {
+                    public_key: public_key.to_string(),
+                    add,
+                    voters: Vec::new(),
+                });
+                self.proposals.len() - 1
+            }
+        };
+
+        let proposal = &mut self.proposals[index];
+        if !proposal.voters.iter().any(|v| v == voter) {
+            proposal.voters.push(voter.to_string());
+        }
+        if proposal.voters.len() <= self.authorities.len() / 2 {
+            return false;
+        }
+        if !add && self.authorities.len() == 1 {
+            return false;
+        }
+
+        self.proposals.remove(index);
+        if add {
+            self.authorities.push(public_key.to_string());
+            self.authorities.sort();
+        } else {
+            self.authorities.retain(|k| k != public_key);
+
+            // Votes of the removed authority do not count anymore
+            if let Some(removed) = address_of(public_key) {
+                for proposal in self.proposals.iter_mut() {
+                    proposal.voters.retain(|v| v != &removed);
+                }
+                self.proposals.retain(|p| !p.voters.is_empty());
+            }
+        }
+        // Open proposals about the same key are settled
+        self.proposals.retain(|p| p.public_key != public_key);
+
+        true
+    }
+
+    /// Will count the signed votes of the block's transactions, in block order
+    pub fn apply_votes(&mut self, block: &Block) {
+        for transaction in block.transactions.iter().filter(|t| t.is_signed()) {
+            if let TransactionData::VoteAuthority { public_key, add } = &transaction.data {
+                self.vote(&transaction.from, public_key, *add);
+            }
+        }
+    }
+}
+
+/// Clique style proof of authority: the authorities take turns sealing a block
+/// every `period_ms` by signing its header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOfAuthority {
+    /// The time between two blocks in milliseconds
+    pub period_ms: u64,
+}
+
+impl ProofOfAuthority {
+    pub fn new(period_ms: u64) -> Self {
+        Self { period_ms }
+    }
+
+    /// Will return the address and public key of the authority sealing the next block
+    fn in_turn(&self, chain: &Blockchain) -> Result<(String, PublicKey), BlockError> {
+        let public_key = chain
+            .authorities
+            .in_turn(chain.len())
+            .ok_or(BlockError::NoValidators)?;
+        let key = public_key
+            .parse::<PublicKey>()
+            .map_err(|_| BlockError::InvalidAuthority(public_key.clone()))?;
+
+        Ok((
+            Address::from_public_key_bytes(&key.serialize()).to_string(),
+            key,
+        ))
+    }
+}
+
+impl ConsensusEngine for ProofOfAuthority {
+    fn verify_header(&self, chain: &Blockchain, block: &Block) -> Result<(), BlockError> {
+        // Blocks are sealed once per period
+        if let Some(parent) = chain.headers.last() {
+            let earliest = parent.timestamp.saturating_add(self.period_ms);
+            if block.timestamp < earliest {
+                return Err(BlockError::PeriodNotElapsed {
+                    timestamp: block.timestamp,
+                    earliest,
+                });
+            }
+        }
+
+        let (expected, public_key) = self.in_turn(chain)?;
+
+        let proposer = block.proposer.as_ref().ok_or(BlockError::MissingProposer)?;
+        if proposer != &expected {
+            return Err(BlockError::UnexpectedProposer {
+                expected,
+                actual: proposer.clone(),
+            });
+        }
+
+        let valid_signature = match (&block.signature, &block.hash) {
+            (Some(signature), Some(hash)) => {
+                verify_signature(&public_key, hash.as_bytes(), signature)
+            }
+            _ => false,
+        };
+        if !valid_signature {
+            return Err(BlockError::InvalidProposerSignature);
+        }
+
+        Ok(())
+    }
+
+    fn seal(
+        &self,
+        chain: &Blockchain,
+        block: &mut Block,
+        signer: &Wallet,
+    ) -> Result<(), BlockError> {
+        let (expected, _) = self.in_turn(chain)?;
+        sign_as(block, signer, &expected)
+    }
+
+    /// Counts the signed authority votes of the block
+    fn finalize(&self, chain: &mut Blockchain, block: &Block) {
+        chain.authorities.apply_votes(block);
+    }
+
+    fn fork_choice(&self, chain: &Blockchain, fork_height: usize, fork: &[Block]) -> bool {
+        longest_chain(chain, fork_height, fork)
+    }
+}
+
+/// Will derive the address of a hex encoded public key
+fn address_of(public_key: &str) -> Option<String> {
+    let key = public_key.parse::<PublicKey>().ok()?;
+    Some(Address::from_public_key_bytes(&key.serialize()).to_string())
+}
+
+/// Will bring a hex encoded public key into its compressed lowercase form, so keys can be compared
+fn normalize(public_key: &str) -> String {
+    public_key
+        .parse::<PublicKey>()
+        .map(|key| key.to_string())
+        .unwrap_or_else(|_| public_key.to_string())
+}
@@ -0,0 +1,107 @@
+use eternal_account::{AccountType, Wallet};
+
+use super::{check_signed_by, longest_chain, sign_as, ConsensusEngine};
+use crate::{
+    block::Block,
+    blockchain::Blockchain,
+    error::BlockError,
+    pos::{ValidatorSet, PROPOSER_TIMEOUT_MS},
+};
+
+/// Validators take turns proposing blocks, the proposer of each slot is picked by stake.
+/// If it misses its slot, fallback proposers (picked the same way) take over one by one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOfStake {
+    /// The stake needed to become part of the validator set
+    pub min_stake: u128,
+}
+
+impl ProofOfStake {
+    pub fn new(min_stake: u128) -> Self {
+        Self { min_stake }
+    }
+
+    /// Will return the validators of the chain's current state
+    pub fn validator_set(&self, chain: &Blockchain) -> ValidatorSet {
+        ValidatorSet::from_accounts(&chain.accounts, self.min_stake)
+    }
+
+    /// Will return the validator selected to propose the next block
+    pub fn next_proposer(&self, chain: &Blockchain) -> Option<String> {
+        let seed = chain.get_last_block_hash().unwrap_or_default();
+        self.validator_set(chain)
+            .proposer(&seed, chain.len() as u64)
+            .cloned()
+    }
+
+    /// Will return the validators allowed to propose the next block at the timestamp: the
+    /// selected proposer, and one fallback for every [`PROPOSER_TIMEOUT_MS`] passed since
+    /// the parent block
+    pub fn proposers_at(&self, chain: &Blockchain, timestamp: u64) -> Vec<String> {
+        let seed = chain.get_last_block_hash().unwrap_or_default();
+        let parent = chain.headers.last().map_or(0, |header| header.timestamp);
+        let fallbacks = timestamp.saturating_sub(parent) / PROPOSER_TIMEOUT_MS;
+        let count = usize::try_from(fallbacks)
+            .unwrap_or(usize::MAX)
+            .saturating_add(1);
+
+        self.validator_set(chain)
+            .proposers(&seed, chain.len() as u64, count)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Will return the allowed proposer the block is meant to come from
+    fn expected(
+        &self,
+        chain: &Blockchain,
+        block: &Block,
+        proposer: &str,
+    ) -> Result<String, BlockError> {
+        let allowed = self.proposers_at(chain, block.timestamp);
+        if allowed.iter().any(|address| address == proposer) {
+            return Ok(proposer.to_string());
+        }
+
+        allowed.into_iter().next().ok_or(BlockError::NoValidators)
+    }
+}
+
+impl ConsensusEngine for ProofOfStake {
+    fn verify_header(&self, chain: &Blockchain, block: &Block) -> Result<(), BlockError> {
+        let proposer = block.proposer.as_deref().unwrap_or_default();
+        let expected = self.expected(chain, block, proposer)?;
+        check_signed_by(chain, block, &expected)
+    }
+
+    fn seal(
+        &self,
+        chain: &Blockchain,
+        block: &mut Block,
+        signer: &Wallet,
+    ) -> Result<(), BlockError> {
+        let expected = self.expected(chain, block, &signer.address().to_string())?;
+        sign_as(block, signer, &expected)
+    }
+
+    /// Counts the block for its proposer
+    fn finalize(&self, chain: &mut Blockchain, block: &Block) {
+        let Some(proposer) = block.proposer.as_ref() else {
+            return;
+        };
+
+        if let Some(AccountType::Validator {
+            correctly_validated_blocks,
+            ..
+        }) = chain.accounts.get_mut(proposer).map(|a| &mut a.acc_type)
+        {
+            *correctly_validated_blocks += 1;
+        }
+    }
+
+    /// Finalized blocks are protected by the chain itself, above them the longest chain wins
+    fn fork_choice(&self, chain: &Blockchain, fork_height: usize, fork: &[Block]) -> bool {
+        longest_chain(chain, fork_height, fork)
+    }
+}
@@ -0,0 +1,58 @@
+use eternal_account::Wallet;
+
+use super::{longest_chain, ConsensusEngine};
+use crate::{block::Block, blockchain::Blockchain, error::BlockError};
+
+/// Anyone may append a block whose hash starts with enough zeros
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOfWork {
+    /// The amount of leading zeros (hex digits) of a valid block hash
+    pub difficulty: u32,
+}
+
+impl ProofOfWork {
+    pub fn new(difficulty: u32) -> Self {
+        Self { difficulty }
+    }
+
+    /// True if the hash starts with enough zeros
+    pub fn meets_difficulty(&self, hash: &str) -> bool {
+        hash.chars().take_while(|c| *c == '0').count() >= self.difficulty as usize
+    }
+
+    /// Will increase the nonce until the hash of the block is good enough, the chain is
+    /// not needed for that
+    pub fn mine(&self, block: &mut Block) {
+        block.update_hash();
+        while !self.meets_difficulty(block.hash.as_deref().unwrap_or_default()) {
+            block.set_nonce(block.nonce.wrapping_add(1));
+        }
+    }
+}
+
+impl ConsensusEngine for ProofOfWork {
+    fn verify_header(&self, _chain: &Blockchain, block: &Block) -> Result<(), BlockError> {
+        match &block.hash {
+            Some(hash) if self.meets_difficulty(hash) => Ok(()),
+            _ => Err(BlockError::InsufficientWork {
+                difficulty: self.difficulty,
+            }),
+        }
+    }
+
+    /// Mining needs no key, the nonce is increased until the hash is good enough
+    fn seal(
+        &self,
+        _chain: &Blockchain,
+        block: &mut Block,
+        _signer: &Wallet,
+    ) -> Result<(), BlockError> {
+        self.mine(block);
+        Ok(())
+    }
+
+    /// Every block needs the same work, so the longest chain has the most work
+    fn fork_choice(&self, chain: &Blockchain, fork_height: usize, fork: &[Block]) -> bool {
+        longest_chain(chain, fork_height, fork)
+    }
+}
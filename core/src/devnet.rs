@@ -0,0 +1,98 @@
+use std::{thread, time::Duration};
+
+use eternal_account::Wallet;
+
+use crate::{
+    block::Block,
+    blockchain::Blockchain,
+    consensus::Consensus,
+    error::{BlockError, TxError},
+    transaction::{Transaction, TransactionData},
+};
+
+/// A single node chain without any networking, sealing the pending transactions on a
+/// fixed interval with the locally known keys. Meant for local integration testing.
+#[derive(Debug)]
+pub struct Devnet {
+    pub chain: Blockchain,
+    /// The keys used for sealing, whichever may seal the next block is used
+    signers: Vec<Wallet>,
+    /// The time between two blocks
+    pub period: Duration,
+}
+
+impl Devnet {
+    /// Will create a devnet on top of the chain, blocks are produced as fast as the
+    /// consensus allows (every `period_ms` for proof of authority, instantly otherwise)
+    pub fn new(chain: Blockchain, signers: Vec<Wallet>) -> Self {
+        let period = match &chain.consensus {
+            Consensus::ProofOfAuthority { period_ms, .. } => Duration::from_millis(*period_ms),
+            _ => Duration::ZERO,
+        };
+
+        Self {
+            chain,
+            signers,
+            period,
+        }
+    }
+
+    /// Queues a transaction for the next block
+    pub fn submit(&mut self, transaction: Transaction) -> Result<(), TxError> {
+        self.chain.submit_transaction(transaction)
+    }
+
+    /// Will sign a transaction of the wallet's account with its next nonce and queue it,
+    /// returning its hash
+    pub fn send(&mut self, wallet: &Wallet, data: TransactionData) -> Result<String, TxError> {
+        let from = wallet.address().to_string();
+        let nonce = self.chain.next_nonce(&from);
+        let mut transaction = Transaction::new(self.chain.chain_id, from, data, nonce);
+        transaction.sign(wallet);
+
+        let hash = transaction.hash();
+        self.submit(transaction)?;
+        Ok(hash)
+    }
+
+    /// Will seal as many pending transactions as fit into a block. Nothing is produced if
+    /// there are no pending transactions, the transactions are dropped if the block is invalid.
+    pub fn step(&mut self) -> Result<Option<Block>, BlockError> {
+        let transactions = self.chain.take_pending_transactions();
+        if transactions.is_empty() {
+            return Ok(None);
+        }
+
+        let engine = self.chain.engine();
+        let mut block = engine.prepare_block(&self.chain);
+        for transaction in transactions {
+            block.add_transaction(transaction);
+        }
+        self.chain.commit_state(&mut block)?;
+
+        let mut result = Err(BlockError::NoValidators);
+        for signer in &self.signers {
+            result = engine.seal(&self.chain, &mut block, signer);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result?;
+
+        self.chain.append_block(block.clone())?;
+        Ok(Some(block))
+    }
+
+    /// Will run a step every period until `blocks` blocks were produced
+    pub fn run(&mut self, blocks: usize) -> Result<(), BlockError> {
+        let mut produced = 0;
+        while produced < blocks {
+            thread::sleep(self.period);
+            if self.step()?.is_some() {
+                produced += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
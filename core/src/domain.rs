@@ -0,0 +1,50 @@
+use sha2::{Digest, Sha256};
+
+/// Every hash starts with the tag of the hashed kind of object, so the hash of one kind
+/// (and any signature over it) can never be taken for the hash of another kind
+pub const BLOCK_TAG: &str = "eternal:block:v2";
+
+pub const TRANSACTION_TAG: &str = "eternal:transaction:v2";
+
+pub const TRANSACTION_LEAF_TAG: &str = "eternal:transaction-leaf:v1";
+
+pub const VOTE_TAG: &str = "eternal:vote:v2";
+
+pub const ACCOUNT_TAG: &str = "eternal:account:v2";
+
+pub const CONTRACT_ADDRESS_TAG: &str = "eternal:contract-address:v1";
+
+pub const STORE_TAG: &str = "eternal:store:v2";
+
+pub const TOKEN_TAG: &str = "eternal:token:v1";
+
+pub const MERKLE_TAG: &str = "eternal:merkle:v1";
+
+pub const AUTHORITY_TAG: &str = "eternal:authorities:v1";
+
+pub const SNAPSHOT_TAG: &str = "eternal:snapshot:v1";
+
+/// Will return a hasher already fed with the length prefixed tag
+pub fn tagged_hasher(tag: &str) -> Sha256 {
+    let mut hasher = Sha256::new();
+    hasher.update((tag.len() as u64).to_be_bytes());
+    hasher.update(tag.as_bytes());
+    hasher
+}
+
+/// Length prefixed, so neighbouring fields can not be shifted into each other
+pub(crate) fn update_field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// A presence byte followed by the length prefixed field, so a missing and an empty field differ
+pub(crate) fn update_optional(hasher: &mut Sha256, bytes: Option<&[u8]>) {
+    match bytes {
+        Some(bytes) => {
+            hasher.update([1]);
+            update_field(hasher, bytes);
+        }
+        None => hasher.update([0]),
+    }
+}
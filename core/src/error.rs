@@ -0,0 +1,532 @@
+use std::io;
+
+use eternal_account::{KeyError, StoreError};
+use eternal_vm::VmError;
+use thiserror::Error;
+
+/// Errors that can occur while appending or validating blocks
+#[derive(Debug, Error)]
+pub enum BlockError {
+    #[error("The block hash is mismatching!")]
+    HashMismatch,
+
+    #[error("The new block has to point to the previous block")]
+    InvalidPrev,
+
+    #[error("There has to be at least one transaction inside the block!")]
+    Empty,
+
+    #[error("Could not execute transaction {index} due to `{source}`. Rolling back")]
+    Transaction {
+        index: usize,
+        #[source]
+        source: TxError,
+    },
+
+    #[error("Stored hash for Block #{block} does not match calculated hash")]
+    StoredHashMismatch { block: usize },
+
+    #[error("The genesis block has a previous hash set which it shouldn't")]
+    GenesisHasPrev,
+
+    #[error("Block #{block} has no previous hash set")]
+    MissingPrev { block: usize },
+
+    #[error(
+        "Block #{block} is not connected to previous block \
+        (Hashes do not match. Should be `{expected}` but is `{actual}`)"
+    )]
+    NotConnected {
+        block: usize,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Transaction #{transaction} for Block #{block} has an invalid signature")]
+    InvalidSignature { block: usize, transaction: usize },
+
+    #[error("There are no validators with enough stake to propose a block")]
+    NoValidators,
+
+    #[error("The block has no proposer set")]
+    MissingProposer,
+
+    #[error("The block was proposed by `{actual}` but `{expected}` is selected for this slot")]
+    UnexpectedProposer { expected: String, actual: String },
+
+    #[error("The block is not signed by its proposer")]
+    InvalidProposerSignature,
+
+    #[error("Can not reorg at height {fork_height}, block {finalized} is finalized")]
+    ReorgBelowFinalized {
+        fork_height: usize,
+        finalized: usize,
+    },
+
+    #[error("The fork point {0} is not part of the chain")]
+    UnknownForkPoint(usize),
+
+    #[error("The block hash does not start with {difficulty} zeros")]
+    InsufficientWork { difficulty: u32 },
+
+    #[error("The configured authority `{0}` is no valid public key")]
+    InvalidAuthority(String),
+
+    #[error("The chain has no genesis block, it has to be created from a chain spec")]
+    MissingGenesis,
+
+    #[error("The block timestamp {timestamp} is before the median time past {median}")]
+    TimestampTooOld { timestamp: u64, median: u64 },
+
+    #[error(
+        "The block timestamp {timestamp} is more than {max_drift}ms ahead of the local time {now}"
+    )]
+    TimestampInFuture {
+        timestamp: u64,
+        now: u64,
+        max_drift: u64,
+    },
+
+    #[error("The block timestamp {timestamp} is before the end of the block period at {earliest}")]
+    PeriodNotElapsed { timestamp: u64, earliest: u64 },
+
+    #[error("The block contains {count} transactions, at most {max} are allowed")]
+    TooManyTransactions { count: usize, max: usize },
+
+    #[error("The block has {size} bytes, at most {max} are allowed")]
+    TooLarge { size: usize, max: usize },
+
+    #[error(
+        "The block commits to the state root `{expected}` but executing it results in `{actual}`"
+    )]
+    StateRootMismatch { expected: String, actual: String },
+}
+
+impl BlockError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            BlockError::HashMismatch => 93820394,
+            BlockError::InvalidPrev => 3948230,
+            BlockError::Empty => 9482930,
+            BlockError::Transaction { .. } => 38203984,
+            BlockError::StoredHashMismatch { .. } => 665234234,
+            BlockError::GenesisHasPrev => 394823098,
+            BlockError::MissingPrev { .. } => 394823099,
+            BlockError::NotConnected { .. } => 394823100,
+            BlockError::InvalidSignature { .. } => 4398239048,
+            BlockError::NoValidators => 5820394821,
+            BlockError::MissingProposer => 5820394822,
+            BlockError::UnexpectedProposer { .. } => 5820394823,
+            BlockError::InvalidProposerSignature => 5820394824,
+            BlockError::ReorgBelowFinalized { .. } => 6930284711,
+            BlockError::UnknownForkPoint(_) => 6930284712,
+            BlockError::InsufficientWork { .. } => 7493028471,
+            BlockError::InvalidAuthority(_) => 7493028472,
+            BlockError::MissingGenesis => 394823097,
+            BlockError::TimestampTooOld { .. } => 1029384756,
+            BlockError::TimestampInFuture { .. } => 1029384757,
+            BlockError::PeriodNotElapsed { .. } => 1029384758,
+            BlockError::TooManyTransactions { .. } => 5647382910,
+            BlockError::TooLarge { .. } => 5647382911,
+            BlockError::StateRootMismatch { .. } => 3305829471,
+        }
+    }
+}
+
+/// Errors that can occur while executing a transaction
+#[derive(Debug, Error)]
+pub enum TxError {
+    #[error("Account `{0}` does not exist")]
+    SenderNotFound(String),
+
+    #[error("Receiver Account `{0}` does not exist")]
+    ReceiverNotFound(String),
+
+    #[error("Account `{0}` does not exist")]
+    AccountNotFound(String),
+
+    #[error("Tokens can only be allocated in the chain spec")]
+    MintNotAllowed,
+
+    #[error("Overspent or Arithmetic error")]
+    Overspent,
+
+    #[error("The transaction does not contain a smart contract")]
+    MissingContract,
+
+    #[error("Token `{0}` does not exist")]
+    TokenNotFound(String),
+
+    #[error("`{0}` is not a transferable asset")]
+    NotTransferable(String),
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+
+    #[error("The transaction carries an invalid signature")]
+    InvalidSignature,
+
+    #[error("The transaction is not signed by the sender")]
+    Unsigned,
+
+    #[error("Only {valid} of {required} required owners signed the transaction")]
+    InsufficientSignatures { required: u8, valid: usize },
+
+    #[error("Invalid multisig account")]
+    InvalidMultisig(#[source] KeyError),
+
+    #[error("Account `{0}` can not stake tokens")]
+    NotStakeable(String),
+
+    #[error("Account `{0}` has not enough stake to withdraw")]
+    InsufficientStake(String),
+
+    #[error("Invalid evidence: {0}")]
+    InvalidEvidence(String),
+
+    #[error("Validator `{0}` is already jailed")]
+    AlreadyJailed(String),
+
+    #[error("Validator `{0}` is jailed, its stake is locked")]
+    Jailed(String),
+
+    #[error("`{0}` is no valid public key")]
+    InvalidPublicKey(String),
+
+    #[error("The genesis transaction is only valid inside the genesis block")]
+    GenesisOnly,
+
+    #[error("The transaction expired at {valid_until}, the block is from {timestamp}")]
+    Expired { valid_until: u64, timestamp: u64 },
+
+    #[error("The transaction has {size} bytes, at most {max} are allowed")]
+    TooLarge { size: usize, max: usize },
+
+    #[error("The store key has {size} bytes, at most {max} are allowed")]
+    StoreKeyTooLarge { size: usize, max: usize },
+
+    #[error("The store value has {size} bytes, at most {max} are allowed")]
+    StoreValueTooLarge { size: usize, max: usize },
+
+    #[error("The transaction is already pending")]
+    AlreadyPending,
+
+    #[error("The transaction is meant for chain {actual}, this is chain {expected}")]
+    WrongChain { expected: u64, actual: u64 },
+
+    #[error("The transaction has nonce {actual}, the sender's next nonce is {expected}")]
+    InvalidNonce { expected: u128, actual: u128 },
+
+    #[error(transparent)]
+    Vm(#[from] VmError),
+}
+
+impl TxError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            TxError::SenderNotFound(_) => 93482390,
+            TxError::ReceiverNotFound(_) => 23482309,
+            TxError::AccountNotFound(_) => 23423923,
+            TxError::MintNotAllowed => 2394233,
+            TxError::Overspent => 48239084203,
+            TxError::MissingContract => 48239084204,
+            TxError::TokenNotFound(_) => 48239084205,
+            TxError::NotTransferable(_) => 48239084206,
+            TxError::Store(err) => err.code(),
+            TxError::InvalidSignature => 4398239049,
+            TxError::Unsigned => 4398239052,
+            TxError::InsufficientSignatures { .. } => 4398239050,
+            TxError::InvalidMultisig(_) => 4398239051,
+            TxError::NotStakeable(_) => 5820394825,
+            TxError::InsufficientStake(_) => 5820394826,
+            TxError::InvalidEvidence(_) => 5820394827,
+            TxError::AlreadyJailed(_) => 5820394828,
+            TxError::Jailed(_) => 5820394829,
+            TxError::InvalidPublicKey(_) => 7493028473,
+            TxError::GenesisOnly => 2394234,
+            TxError::Expired { .. } => 1029384759,
+            TxError::TooLarge { .. } => 5647382912,
+            TxError::StoreKeyTooLarge { .. } => 5647382913,
+            TxError::StoreValueTooLarge { .. } => 5647382914,
+            TxError::AlreadyPending => 5647382915,
+            TxError::WrongChain { .. } => 1559238471,
+            TxError::InvalidNonce { .. } => 1559238472,
+            TxError::Vm(err) => err.code(),
+        }
+    }
+}
+
+/// Errors that can occur while processing finality votes
+#[derive(Debug, Error)]
+pub enum VoteError {
+    #[error("The vote is for unknown block `{0}`")]
+    UnknownBlock(String),
+
+    #[error("`{0}` is not an active validator")]
+    NotAValidator(String),
+
+    #[error("The vote signature is invalid")]
+    InvalidSignature,
+
+    #[error("Validator `{0}` voted for two different blocks in the same step")]
+    Equivocation(String),
+
+    #[error(
+        "Less than 2/3 of the stake prevoted for the block at height {height} in round {round}"
+    )]
+    NoPolka { height: usize, round: u32 },
+}
+
+impl VoteError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            VoteError::UnknownBlock(_) => 6930284713,
+            VoteError::NotAValidator(_) => 6930284714,
+            VoteError::InvalidSignature => 6930284715,
+            VoteError::Equivocation(_) => 6930284716,
+            VoteError::NoPolka { .. } => 6930284717,
+        }
+    }
+}
+
+/// Errors that can occur while loading a chain spec or building the genesis from it
+#[derive(Debug, Error)]
+pub enum SpecError {
+    #[error("Can not read the chain spec")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid JSON chain spec")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid TOML chain spec")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Unsupported chain spec format `{0}`, expected .toml or .json")]
+    UnsupportedFormat(String),
+
+    #[error("The allocation key `{0}` is no valid public key")]
+    InvalidPublicKey(String),
+
+    #[error("Invalid contract address")]
+    InvalidAddress(#[source] KeyError),
+
+    #[error("Account `{0}` is allocated twice")]
+    DuplicateAccount(String),
+
+    #[error("The publisher `{0}` of a genesis contract has no allocation")]
+    UnknownPublisher(String),
+
+    #[error("The genesis hash is `{actual}` but the chain spec expects `{expected}`")]
+    GenesisHashMismatch { expected: String, actual: String },
+}
+
+impl SpecError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            SpecError::Io(_) => 8120394851,
+            SpecError::Json(_) => 8120394852,
+            SpecError::Toml(_) => 8120394853,
+            SpecError::UnsupportedFormat(_) => 8120394854,
+            SpecError::InvalidPublicKey(_) => 8120394855,
+            SpecError::InvalidAddress(_) => 8120394856,
+            SpecError::DuplicateAccount(_) => 8120394857,
+            SpecError::UnknownPublisher(_) => 8120394858,
+            SpecError::GenesisHashMismatch { .. } => 8120394859,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Can not access the block storage")]
+    Io(#[from] io::Error),
+
+    #[error("Can not encode the block")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Corrupt block in line {line}")]
+    Corrupt {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+impl StorageError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            StorageError::Io(_) => 6620193841,
+            StorageError::Json(_) => 6620193842,
+            StorageError::Corrupt { .. } => 6620193843,
+        }
+    }
+}
+
+/// Errors that can occur while encoding or decoding raw transactions
+#[derive(Debug, Error)]
+pub enum RawError {
+    #[error("The raw transaction is empty")]
+    Empty,
+
+    #[error("Unsupported raw transaction version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Contract deployments have no raw form")]
+    Deploy,
+
+    #[error("Invalid transaction JSON")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid hex")]
+    Hex(#[from] hex::FromHexError),
+
+    #[error("Invalid base64")]
+    Base64(#[from] base64::DecodeError),
+}
+
+impl RawError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            RawError::Empty => 7720481931,
+            RawError::UnsupportedVersion(_) => 7720481932,
+            RawError::Deploy => 7720481933,
+            RawError::Json(_) => 7720481934,
+            RawError::Hex(_) => 7720481935,
+            RawError::Base64(_) => 7720481936,
+        }
+    }
+}
+
+/// Errors that can occur while restoring a state snapshot
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("The snapshot belongs to chain {actual} instead of {expected}")]
+    WrongChain { expected: u64, actual: u64 },
+
+    #[error("The snapshot is of block `{actual}` instead of `{expected}` at height {height}")]
+    WrongBlock {
+        height: usize,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("The snapshot has {actual} chunks instead of {expected}")]
+    ChunkCount { expected: usize, actual: usize },
+
+    #[error("Chunk {0} does not match its hash")]
+    ChunkHash(usize),
+
+    #[error("Corrupt chunk {index}")]
+    CorruptChunk {
+        index: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("The block commits to the state root `{expected}` but the snapshot has `{actual}`")]
+    StateRootMismatch { expected: String, actual: String },
+
+    #[error("Invalid header at height {height}")]
+    Header {
+        height: usize,
+        #[source]
+        source: BlockError,
+    },
+
+    #[error("Invalid chain spec")]
+    Spec(#[from] SpecError),
+
+    #[error("The block at height {height} is sealed by `{proposer}`, which is neither a sealer of the genesis block nor of the snapshot")]
+    UnknownSealer { height: usize, proposer: String },
+
+    #[error("The contract `{0}` is not one of the chain spec, its code can not be restored")]
+    UnrestorableContract(String),
+}
+
+impl SnapshotError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            SnapshotError::UnsupportedVersion(_) => 2281937461,
+            SnapshotError::WrongChain { .. } => 2281937462,
+            SnapshotError::WrongBlock { .. } => 2281937463,
+            SnapshotError::ChunkCount { .. } => 2281937464,
+            SnapshotError::ChunkHash(_) => 2281937465,
+            SnapshotError::CorruptChunk { .. } => 2281937466,
+            SnapshotError::StateRootMismatch { .. } => 2281937467,
+            SnapshotError::Header { .. } => 2281937468,
+            SnapshotError::UnknownSealer { .. } => 2281937470,
+            SnapshotError::UnrestorableContract(_) => 2281937471,
+            SnapshotError::Spec(err) => err.code(),
+        }
+    }
+}
+
+/// Errors of lookups hitting data the chain pruned, see [`crate::pruning::Pruning`]
+#[derive(Debug, Error)]
+pub enum LookupError {
+    #[error("Block {height} was pruned, the blocks from height {first} on are stored")]
+    BlockPruned { height: usize, first: usize },
+
+    #[error("The state of block {height} was pruned, the state of the blocks from height {first} on is kept")]
+    StatePruned { height: usize, first: usize },
+
+    #[error("Unknown transaction, the blocks before height {first} were pruned")]
+    TransactionPruned { first: usize },
+
+    #[error("Can not re-execute the kept blocks")]
+    Block(#[from] BlockError),
+}
+
+impl LookupError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            LookupError::BlockPruned { .. } => 9184720361,
+            LookupError::StatePruned { .. } => 9184720362,
+            LookupError::TransactionPruned { .. } => 9184720363,
+            LookupError::Block(err) => err.code(),
+        }
+    }
+}
+
+/// Errors of proofs not matching the header they are checked against
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("The proof is of block {actual} instead of {expected} at height {height}")]
+    WrongBlock {
+        height: usize,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("The header does not commit to a state")]
+    MissingStateRoot,
+
+    #[error("The proof does not lead to the root of the header")]
+    InvalidProof,
+
+    #[error("The proof does not show the requested entry `{0}`")]
+    MissingEntry(String),
+}
+
+impl ProofError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            ProofError::WrongBlock { .. } => 1730264851,
+            ProofError::MissingStateRoot => 1730264852,
+            ProofError::InvalidProof => 1730264853,
+            ProofError::MissingEntry(_) => 1730264854,
+        }
+    }
+}
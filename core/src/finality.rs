@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use eternal_account::{Account, Wallet};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::{
+    domain::{tagged_hasher, update_field, VOTE_TAG},
+    error::VoteError,
+    pos::ValidatorSet,
+};
+
+/// The two voting steps of a round: a block needs more than 2/3 of the stake
+/// prevoting for it before validators precommit, and more than 2/3 of the stake
+/// precommitting for it to become final
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum VoteKind {
+    Prevote,
+    Precommit,
+}
+
+/// A validator's signed vote for a block hash
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Vote {
+    pub kind: VoteKind,
+    pub height: usize,
+    pub round: u32,
+    pub block_hash: String,
+    pub validator: String,
+    pub signature: String,
+}
+
+/// A finalized block, the chain can never be reorganized below it
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Checkpoint {
+    pub height: usize,
+    pub hash: String,
+}
+
+impl Vote {
+    /// Will create a vote signed by the wallet
+    pub fn new(
+        kind: VoteKind,
+        height: usize,
+        round: u32,
+        block_hash: String,
+        wallet: &Wallet,
+    ) -> Self {
+        let mut vote = Self {
+            kind,
+            height,
+            round,
+            block_hash,
+            validator: wallet.address().to_string(),
+            signature: String::new(),
+        };
+        vote.signature = wallet.sign(&vote.signing_payload());
+        vote
+    }
+
+    /// Will return the bytes covered by the signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut hasher = tagged_hasher(VOTE_TAG);
+        hasher.update([match self.kind {
+            VoteKind::Prevote => 0,
+            VoteKind::Precommit => 1,
+        }]);
+        hasher.update((self.height as u64).to_be_bytes());
+        hasher.update(self.round.to_be_bytes());
+        update_field(&mut hasher, self.block_hash.as_bytes());
+
+        hasher.finalize().to_vec()
+    }
+
+    /// Checks the signature against the validator's account
+    pub fn verify(&self, account: &Account) -> bool {
+        account.verify(&self.signing_payload(), &self.signature)
+    }
+}
+
+/// Collects votes and tells once a block gathered more than 2/3 of the stake
+#[derive(Debug, Clone, Default)]
+pub struct FinalityGadget {
+    /// The vote of each validator per height, round and step
+    votes: HashMap<(usize, u32, VoteKind), HashMap<String, Vote>>,
+}
+
+impl FinalityGadget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Will record a vote whose signature was already checked.
+    /// Returns the stake voting the same way in the same step.
+    pub fn add_vote(&mut self, vote: Vote, validators: &ValidatorSet) -> Result<u128, VoteError> {
+        if validators.stake_of(&vote.validator).is_none() {
+            return Err(VoteError::NotAValidator(vote.validator));
+        }
+
+        let step = (vote.height, vote.round, vote.kind);
+        let votes = self.votes.entry(step).or_default();
+        if let Some(previous) = votes.get(&vote.validator) {
+            if previous.block_hash != vote.block_hash {
+                return Err(VoteError::Equivocation(vote.validator));
+            }
+        } else {
+            votes.insert(vote.validator.clone(), vote.clone());
+        }
+
+        Ok(self.stake_for(
+            validators,
+            vote.height,
+            vote.round,
+            vote.kind,
+            &vote.block_hash,
+        ))
+    }
+
+    /// Will return the stake having voted for the block hash in the given step
+    pub fn stake_for(
+        &self,
+        validators: &ValidatorSet,
+        height: usize,
+        round: u32,
+        kind: VoteKind,
+        block_hash: &str,
+    ) -> u128 {
+        self.votes
+            .get(&(height, round, kind))
+            .map(|votes| {
+                votes
+                    .values()
+                    .filter(|vote| vote.block_hash == block_hash)
+                    .filter_map(|vote| validators.stake_of(&vote.validator))
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    /// True if more than 2/3 of the stake prevoted for the block, so validators may precommit
+    pub fn has_polka(
+        &self,
+        validators: &ValidatorSet,
+        height: usize,
+        round: u32,
+        block_hash: &str,
+    ) -> bool {
+        let stake = self.stake_for(validators, height, round, VoteKind::Prevote, block_hash);
+        is_supermajority(stake, validators.total_stake())
+    }
+
+    /// Will forget all votes up to the given height
+    pub fn prune(&mut self, height: usize) {
+        self.votes
+            .retain(|(vote_height, _, _), _| *vote_height > height);
+    }
+}
+
+/// True if the stake is more than 2/3 of the total stake
+pub fn is_supermajority(stake: u128, total_stake: u128) -> bool {
+    total_stake > 0 && stake > total_stake / 3 * 2 + total_stake % 3 * 2 / 3
+}
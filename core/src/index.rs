@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{Block, BlockHeader},
+    transaction::{Transaction, TransactionData},
+};
+
+/// Where a transaction is stored in the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TxLocation {
+    /// Height of the block, the genesis block has height 0
+    pub height: usize,
+    /// Position of the transaction inside the block
+    pub index: usize,
+}
+
+/// Confirms that a transaction was included, transactions failing to execute
+/// never make it into a block
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Receipt {
+    pub transaction_hash: String,
+    pub block_hash: String,
+    pub location: TxLocation,
+    /// Unix timestamp in milliseconds of the block
+    pub timestamp: u64,
+}
+
+/// Lookup tables over the blocks of a chain, kept up to date as blocks are added
+#[derive(Debug, Clone, Default)]
+pub struct ChainIndex {
+    /// Block hash to height, also of the blocks only known by their header
+    blocks: HashMap<String, usize>,
+    /// Transaction hash to location, the nonces keep the hashes of a chain unique
+    transactions: HashMap<String, TxLocation>,
+    /// Address to the locations of the transactions sent or received, oldest first
+    accounts: HashMap<String, Vec<TxLocation>>,
+}
+
+impl ChainIndex {
+    /// Will index the blocks, the first one being stored at the given height
+    pub fn from_blocks(first_height: usize, blocks: &[Block]) -> Self {
+        let mut index = Self::default();
+        for (height, block) in (first_height..).zip(blocks) {
+            index.insert_block(height, block);
+        }
+        index
+    }
+
+    /// Will index the block stored at the given height
+    pub fn insert_block(&mut self, height: usize, block: &Block) {
+        if let Some(hash) = &block.hash {
+            self.blocks.insert(hash.clone(), height);
+        }
+
+        for (index, transaction) in block.transactions.iter().enumerate() {
+            let location = TxLocation { height, index };
+            self.transactions.insert(transaction.hash(), location);
+
+            for address in involved(transaction) {
+                self.accounts
+                    .entry(address.clone())
+                    .or_default()
+                    .push(location);
+            }
+        }
+    }
+
+    /// Will index a block which is only known by its header
+    pub fn insert_header(&mut self, height: usize, header: &BlockHeader) {
+        if let Some(hash) = &header.hash {
+            self.blocks.insert(hash.clone(), height);
+        }
+    }
+
+    /// Will forget the transactions of a pruned block, its hash stays known
+    pub fn remove_transactions(&mut self, height: usize, block: &Block) {
+        for transaction in &block.transactions {
+            self.transactions.remove(&transaction.hash());
+
+            for address in involved(transaction) {
+                if let Some(locations) = self.accounts.get_mut(address) {
+                    locations.retain(|location| location.height != height);
+                    if locations.is_empty() {
+                        self.accounts.remove(address);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Will return the height of the block with the given hash
+    pub fn block_height(&self, hash: &str) -> Option<usize> {
+        self.blocks.get(hash).copied()
+    }
+
+    /// Will return the location of the transaction with the given hash
+    pub fn transaction_location(&self, hash: &str) -> Option<TxLocation> {
+        self.transactions.get(hash).copied()
+    }
+
+    /// Will return the locations of the transactions sent or received by the address
+    pub fn account_history(&self, address: &str) -> &[TxLocation] {
+        self.accounts
+            .get(address)
+            .map(|l| l.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Will return the sender and the receiver of the transaction
+fn involved(transaction: &Transaction) -> Vec<&String> {
+    let mut involved = vec![&transaction.from];
+    if let Some(receiver) = receiver(transaction) {
+        if receiver != &transaction.from {
+            involved.push(receiver);
+        }
+    }
+    involved
+}
+
+/// Will return the account receiving something through the transaction
+fn receiver(transaction: &Transaction) -> Option<&String> {
+    match &transaction.data {
+        TransactionData::Transfer { to, .. } | TransactionData::TransferToken { to, .. } => {
+            Some(to)
+        }
+        TransactionData::MintTokens { receiver, .. } => Some(receiver),
+        _ => None,
+    }
+}
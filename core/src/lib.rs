@@ -1,4 +1,23 @@
 pub use eternal_account as account;
 pub mod block;
 pub mod blockchain;
+pub mod consensus;
+pub mod devnet;
+pub mod domain;
+pub mod error;
+pub mod finality;
+pub mod index;
+pub mod limits;
+pub mod merkle;
+pub mod pos;
+pub mod proof;
+pub mod pruning;
+pub mod raw;
+pub mod replay;
+pub mod slashing;
+pub mod snapshot;
+pub mod spec;
+pub mod state;
+pub mod storage;
+pub mod time;
 pub mod transaction;
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::Block,
+    error::{BlockError, TxError},
+    transaction::{Transaction, TransactionData},
+};
+
+/// Room left for the block header when filling a block with transactions, in bytes
+pub const BLOCK_HEADER_RESERVE: usize = 1024;
+
+/// Consensus limits keeping blocks small enough for every node, sizes are in bytes
+/// of the serialized (JSON) form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    pub max_block_size: usize,
+    pub max_block_transactions: usize,
+    pub max_transaction_size: usize,
+    pub max_store_key_size: usize,
+    pub max_store_value_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_block_size: 1024 * 1024,
+            max_block_transactions: 1_000,
+            max_transaction_size: 64 * 1024,
+            max_store_key_size: 64,
+            max_store_value_size: 4 * 1024,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the size of a transaction and of the store entries it writes
+    pub fn check_transaction(&self, transaction: &Transaction) -> Result<(), TxError> {
+        let size = serialized_size(transaction);
+        if size > self.max_transaction_size {
+            return Err(TxError::TooLarge {
+                size,
+                max: self.max_transaction_size,
+            });
+        }
+
+        if let TransactionData::ChangeStoreValue { key, value } = &transaction.data {
+            if key.len() > self.max_store_key_size {
+                return Err(TxError::StoreKeyTooLarge {
+                    size: key.len(),
+                    max: self.max_store_key_size,
+                });
+            }
+            if value.len() > self.max_store_value_size {
+                return Err(TxError::StoreValueTooLarge {
+                    size: value.len(),
+                    max: self.max_store_value_size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the transaction count and size of a block and each of its transactions
+    pub fn check_block(&self, block: &Block) -> Result<(), BlockError> {
+        let count = block.get_transaction_count();
+        if count > self.max_block_transactions {
+            return Err(BlockError::TooManyTransactions {
+                count,
+                max: self.max_block_transactions,
+            });
+        }
+
+        let size = serialized_size(block);
+        if size > self.max_block_size {
+            return Err(BlockError::TooLarge {
+                size,
+                max: self.max_block_size,
+            });
+        }
+
+        for (i, transaction) in block.transactions.iter().enumerate() {
+            self.check_transaction(transaction)
+                .map_err(|source| BlockError::Transaction {
+                    index: i + 1,
+                    source,
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Will return the length of the serialized value in bytes
+pub fn serialized_size<T: Serialize>(value: &T) -> usize {
+    // Blocks and transactions are plain data, serializing them can not fail
+    serde_json::to_vec(value)
+        .map(|v| v.len())
+        .unwrap_or(usize::MAX)
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::domain::{tagged_hasher, MERKLE_TAG};
+
+pub type Hash = [u8; 32];
+
+/// Shows that a leaf is part of the tree with a given root. The position of the leaf
+/// decides which levels have a sibling, see [`root`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Position of the leaf
+    pub index: usize,
+    /// The amount of leaves of the tree
+    pub leaves: usize,
+    /// The siblings from the leaf up to the root as uppercase hex
+    pub siblings: Vec<String>,
+}
+
+/// The root of a tree without leaves
+pub const EMPTY_ROOT: Hash = [0; 32];
+
+/// Will return the root of the binary Merkle tree over the leaves. A node without a
+/// sibling is carried up unchanged instead of being paired with itself.
+pub fn root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return EMPTY_ROOT;
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node(left, right),
+                _ => pair[0],
+            })
+            .collect();
+    }
+
+    level[0]
+}
+
+/// Will return the proof of the leaf at the given position
+pub fn proof(leaves: &[Hash], index: usize) -> Option<MerkleProof> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut siblings = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            siblings.push(to_hex(sibling));
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node(left, right),
+                _ => pair[0],
+            })
+            .collect();
+        position /= 2;
+    }
+
+    Some(MerkleProof {
+        index,
+        leaves: leaves.len(),
+        siblings,
+    })
+}
+
+impl MerkleProof {
+    /// True if the proof leads from the leaf to the root
+    pub fn verify(&self, leaf: &Hash, root: &Hash) -> bool {
+        if self.index >= self.leaves {
+            return false;
+        }
+
+        let mut siblings = self.siblings.iter();
+        let (mut hash, mut position, mut width) = (*leaf, self.index, self.leaves);
+        while width > 1 {
+            // The last node of an odd level is carried up without a sibling
+            if position % 2 == 1 || position + 1 < width {
+                let Some(sibling) = siblings.next().and_then(|s| from_hex(s)) else {
+                    return false;
+                };
+                hash = match position % 2 {
+                    0 => node(&hash, &sibling),
+                    _ => node(&sibling, &hash),
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+
+        siblings.next().is_none() && &hash == root
+    }
+}
+
+/// Will return the hash of an inner node
+pub fn node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = tagged_hasher(MERKLE_TAG);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Will return the hash as uppercase hex, the same format as block hashes
+pub fn to_hex(hash: &Hash) -> String {
+    hex::encode_upper(hash)
+}
+
+/// Will parse a hash given as hex
+pub fn from_hex(hex: &str) -> Option<Hash> {
+    hex::decode(hex).ok()?.try_into().ok()
+}
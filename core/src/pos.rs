@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use eternal_account::{Account, AccountType};
+use sha2::{Digest, Sha256};
+
+/// Blocks unstaked tokens stay locked, and slashable, before they are released
+pub const UNBONDING_BLOCKS: u64 = 100;
+
+/// Time after the parent block from which the next fallback proposer may propose, in
+/// milliseconds. Keeps the chain going if the selected proposer is offline.
+pub const PROPOSER_TIMEOUT_MS: u64 = 4_000;
+
+/// The validators allowed to propose blocks, derived from the staked accounts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidatorSet {
+    /// Address and stake of each validator, sorted by address
+    validators: Vec<(String, u128)>,
+}
+
+impl ValidatorSet {
+    /// Will collect every validator account having at least `min_stake` staked and not being jailed
+    pub fn from_accounts(accounts: &HashMap<String, Account>, min_stake: u128) -> Self {
+        let mut validators: Vec<(String, u128)> = accounts
+            .iter()
+            .filter_map(|(address, account)| match account.acc_type {
+                AccountType::Validator { stake, jailed, .. }
+                    if !jailed && stake > 0 && stake >= min_stake =>
+                {
+                    Some((address.clone(), stake))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // HashMap iteration order is random, the selection has to be deterministic
+        validators.sort();
+
+        Self { validators }
+    }
+
+    /// Will return the validators with their stake
+    pub fn validators(&self) -> &[(String, u128)] {
+        &self.validators
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Will return the stake of a validator if it is part of the set
+    pub fn stake_of(&self, address: &str) -> Option<u128> {
+        self.validators
+            .iter()
+            .find(|(validator, _)| validator == address)
+            .map(|(_, stake)| *stake)
+    }
+
+    /// Will return the sum of all stakes
+    pub fn total_stake(&self) -> u128 {
+        self.validators.iter().map(|(_, stake)| stake).sum()
+    }
+
+    /// Will pick the proposer of a slot, weighted by stake.
+    ///
+    /// The seed (usually the hash of the previous block) and the slot are hashed into
+    /// a number below the total stake, the validator owning that range is selected.
+    pub fn proposer(&self, seed: &str, slot: u64) -> Option<&String> {
+        self.proposers(seed, slot, 1).into_iter().next()
+    }
+
+    /// Will pick the proposer of a slot followed by its fallbacks, at most `count`
+    /// validators. Each pick is weighted by the stake of the validators not picked yet,
+    /// so every validator shows up once.
+    pub fn proposers(&self, seed: &str, slot: u64, count: usize) -> Vec<&String> {
+        let mut remaining: Vec<&(String, u128)> = self.validators.iter().collect();
+        let mut picked = Vec::new();
+
+        for rank in 0..count.min(remaining.len()) as u64 {
+            let total_stake: u128 = remaining.iter().map(|(_, stake)| stake).sum();
+            if total_stake == 0 {
+                break;
+            }
+
+            let mut hasher = Sha256::new();
+            hasher.update(seed.as_bytes());
+            hasher.update(slot.to_be_bytes());
+            hasher.update(rank.to_be_bytes());
+            let hash = hasher.finalize();
+
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(&hash[..16]);
+            let mut target = u128::from_be_bytes(bytes) % total_stake;
+
+            let position = remaining
+                .iter()
+                .position(|(_, stake)| {
+                    let owned = target < *stake;
+                    target = target.saturating_sub(*stake);
+                    owned
+                })
+                .unwrap_or(remaining.len() - 1);
+            picked.push(&remaining.remove(position).0);
+        }
+
+        picked
+    }
+}
+
+/// Will move the unbonding tokens released up to the height back into the balances
+pub fn release_unbonded(accounts: &mut HashMap<String, Account>, height: u64) {
+    for account in accounts.values_mut() {
+        let AccountType::Validator { unbonding, .. } = &mut account.acc_type else {
+            continue;
+        };
+
+        let released: u128 = unbonding
+            .iter()
+            .filter(|entry| entry.release_height <= height)
+            .map(|entry| entry.amount)
+            .sum();
+        unbonding.retain(|entry| entry.release_height > height);
+        account.tokens = account.tokens.saturating_add(released);
+    }
+}
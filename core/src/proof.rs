@@ -0,0 +1,213 @@
+//! Proofs letting a client which only follows the block headers check what a full node
+//! tells it.
+//!
+//! A [`TransactionProof`] leads from a transaction to the transactions root of its
+//! block. An [`AccountProof`] and an [`AuthorityProof`] lead from a leaf of the state,
+//! see [`crate::state`], to the state root of a block. An account proof may carry
+//! [`StorageProof`]s of selected store entries, leading to the account's store root, and
+//! [`BalanceProof`]s of token balances, leading to the account's token root. Accounts,
+//! store entries and balances are only proven to exist, a full node answering that there
+//! is no such account, key or balance can not be checked. Clients therefore check the
+//! requested entries with [`AccountProof::verify_requested`], which fails for every one
+//! the proof lacks.
+
+use eternal_account::StoreKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{transaction_leaf, BlockHeader},
+    consensus::AuthoritySet,
+    error::ProofError,
+    merkle::{self, Hash, MerkleProof},
+    snapshot::AccountEntry,
+    state::{authority_leaf, entry_leaf, store_leaf, token_leaf},
+    transaction::Transaction,
+};
+
+/// Shows that a transaction was included in a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionProof {
+    pub transaction: Transaction,
+    pub height: usize,
+    pub block_hash: String,
+    pub proof: MerkleProof,
+}
+
+/// Shows an account as of the state a block commits to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountProof {
+    pub height: usize,
+    pub block_hash: String,
+    /// The account, without its store and token balances
+    pub account: AccountEntry,
+    /// The root over the account's store entries as uppercase hex
+    pub store_root: String,
+    /// The root over the account's token balances as uppercase hex
+    pub token_root: String,
+    pub proof: MerkleProof,
+    /// The requested store entries, entries missing from the store are left out, see
+    /// [`AccountProof::verify_requested`]
+    #[serde(default)]
+    pub storage: Vec<StorageProof>,
+    /// The balances of the requested token contracts the account holds tokens of
+    #[serde(default)]
+    pub balances: Vec<BalanceProof>,
+}
+
+/// Shows an entry of an account's store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    #[serde(flatten)]
+    pub key: StoreKey,
+    #[serde(with = "hex::serde")]
+    pub value: Vec<u8>,
+    pub proof: MerkleProof,
+}
+
+/// Shows a token balance of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceProof {
+    pub contract: String,
+    pub balance: u128,
+    pub proof: MerkleProof,
+}
+
+/// Shows the authorities of a proof of authority chain as of the state a block commits
+/// to, they sealed that block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorityProof {
+    pub height: usize,
+    pub block_hash: String,
+    pub authorities: AuthoritySet,
+    pub proof: MerkleProof,
+}
+
+impl TransactionProof {
+    /// Checks the proof against the header of the block at its height
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), ProofError> {
+        check_block(self.height, &self.block_hash, header)?;
+
+        let root = merkle::from_hex(&header.transactions_root).ok_or(ProofError::InvalidProof)?;
+        check_leaf(&self.proof, &transaction_leaf(&self.transaction), &root)
+    }
+}
+
+impl AccountProof {
+    /// Checks the proof against the header of the block at its height
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), ProofError> {
+        check_block(self.height, &self.block_hash, header)?;
+
+        let store_root = merkle::from_hex(&self.store_root).ok_or(ProofError::InvalidProof)?;
+        let token_root = merkle::from_hex(&self.token_root).ok_or(ProofError::InvalidProof)?;
+        let leaf = entry_leaf(&self.account, &store_root, &token_root);
+        check_leaf(&self.proof, &leaf, &state_root(header)?)?;
+
+        for entry in &self.storage {
+            entry.verify(&store_root)?;
+        }
+        for balance in &self.balances {
+            balance.verify(&token_root)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the proof like [`AccountProof::verify`] and that it shows every requested
+    /// store entry and token balance. A missing entry can not be told apart from one the
+    /// full node withholds, so it fails too.
+    pub fn verify_requested(
+        &self,
+        header: &BlockHeader,
+        keys: &[StoreKey],
+        contracts: &[String],
+    ) -> Result<(), ProofError> {
+        self.verify(header)?;
+
+        for key in keys {
+            if self.value(&key.namespace, &key.key).is_none() {
+                let key = format!("{}:{}", key.namespace, hex::encode(&key.key));
+                return Err(ProofError::MissingEntry(key));
+            }
+        }
+        for contract in contracts {
+            if self.balance(contract).is_none() {
+                return Err(ProofError::MissingEntry(contract.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Will return the proven value of the store entry
+    pub fn value(&self, namespace: &str, key: &[u8]) -> Option<&[u8]> {
+        self.storage
+            .iter()
+            .find(|entry| entry.key.namespace == namespace && entry.key.key == key)
+            .map(|entry| entry.value.as_slice())
+    }
+
+    /// Will return the proven token balance of the contract
+    pub fn balance(&self, contract: &str) -> Option<u128> {
+        self.balances
+            .iter()
+            .find(|balance| balance.contract == contract)
+            .map(|balance| balance.balance)
+    }
+}
+
+impl BalanceProof {
+    /// Checks the proof against the root of the account's token balances
+    pub fn verify(&self, token_root: &Hash) -> Result<(), ProofError> {
+        let leaf = token_leaf(&self.contract, self.balance);
+        check_leaf(&self.proof, &leaf, token_root)
+    }
+}
+
+impl StorageProof {
+    /// Checks the proof against the root of the account's store
+    pub fn verify(&self, store_root: &Hash) -> Result<(), ProofError> {
+        let leaf = store_leaf(&self.key.namespace, &self.key.key, &self.value);
+        check_leaf(&self.proof, &leaf, store_root)
+    }
+}
+
+impl AuthorityProof {
+    /// Checks the proof against the header of the block at its height
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), ProofError> {
+        check_block(self.height, &self.block_hash, header)?;
+
+        // The authorities are always the last leaf
+        if self.proof.index + 1 != self.proof.leaves {
+            return Err(ProofError::InvalidProof);
+        }
+        let leaf = authority_leaf(&self.authorities);
+        check_leaf(&self.proof, &leaf, &state_root(header)?)
+    }
+}
+
+fn check_block(height: usize, block_hash: &str, header: &BlockHeader) -> Result<(), ProofError> {
+    let expected = header.hash.clone().unwrap_or_default();
+    if block_hash != expected {
+        return Err(ProofError::WrongBlock {
+            height,
+            expected,
+            actual: block_hash.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn state_root(header: &BlockHeader) -> Result<Hash, ProofError> {
+    let root = header
+        .state_root
+        .as_ref()
+        .ok_or(ProofError::MissingStateRoot)?;
+    merkle::from_hex(root).ok_or(ProofError::InvalidProof)
+}
+
+fn check_leaf(proof: &MerkleProof, leaf: &Hash, root: &Hash) -> Result<(), ProofError> {
+    if !proof.verify(leaf, root) {
+        return Err(ProofError::InvalidProof);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// The amount of recent blocks kept by default
+pub const DEFAULT_RECENT_BLOCKS: usize = 128;
+
+/// What a chain keeps of its history. The headers are always kept.
+///
+/// State is only pruned at snapshot heights, see
+/// [`crate::blockchain::Blockchain::snapshot_interval`]: the oldest kept state moves on
+/// to the newest snapshot which still leaves enough recent blocks. A chain keeps
+/// somewhat more than asked for, and everything if it takes no snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Pruning {
+    /// Keeps every block and the state of every block
+    #[default]
+    Archive,
+    /// Keeps every block, but the state only of at least the last `state` blocks
+    Full {
+        #[serde(default = "default_recent_blocks")]
+        state: usize,
+    },
+    /// Keeps the blocks and their state only of at least the last `blocks` blocks
+    Pruned {
+        #[serde(default = "default_recent_blocks")]
+        blocks: usize,
+    },
+}
+
+impl Pruning {
+    /// Will return the amount of recent blocks whose state has to be kept, none if
+    /// everything is kept
+    pub fn recent_state(&self) -> Option<usize> {
+        match *self {
+            Pruning::Archive => None,
+            Pruning::Full { state } => Some(state),
+            Pruning::Pruned { blocks } => Some(blocks),
+        }
+    }
+
+    /// True if old blocks are dropped together with their state
+    pub fn prunes_blocks(&self) -> bool {
+        matches!(self, Pruning::Pruned { .. })
+    }
+}
+
+fn default_recent_blocks() -> usize {
+    DEFAULT_RECENT_BLOCKS
+}
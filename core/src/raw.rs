@@ -0,0 +1,116 @@
+//! The raw transaction format, the form in which transactions leave the machine that
+//! built or signed them.
+//!
+//! A raw transaction is a version byte followed by the transaction as compact UTF-8
+//! JSON, the same JSON the JSON-RPC API uses:
+//!
+//! ```text
+//! raw = version (1 byte, currently 0x01) || json(Transaction)
+//! ```
+//!
+//! - `created_at` is `{"secs_since_epoch": u64, "nanos_since_epoch": u32}`, its Unix
+//!   milliseconds are part of the signed hash
+//! - Amounts and nonces are JSON numbers, even above `u64::MAX`
+//! - `signatures` is empty for an unsigned transaction, signing only appends to it
+//! - Contract deployments carry code which can not be encoded and are rejected
+//!
+//! As text a raw transaction is written as `0x` prefixed lowercase hex or as standard
+//! base64 with padding. Decoders ignore surrounding whitespace.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{
+    error::{RawError, TxError},
+    limits::Limits,
+    transaction::{Transaction, TransactionData},
+};
+
+/// The version of the raw format written by [`encode`]
+pub const RAW_VERSION: u8 = 1;
+
+/// Will encode the transaction into the raw format
+pub fn encode(transaction: &Transaction) -> Result<Vec<u8>, RawError> {
+    if let TransactionData::DeploySmartContract { .. } = transaction.data {
+        return Err(RawError::Deploy);
+    }
+
+    let mut raw = vec![RAW_VERSION];
+    serde_json::to_writer(&mut raw, transaction)?;
+    Ok(raw)
+}
+
+/// Will decode a transaction from the raw format
+pub fn decode(raw: &[u8]) -> Result<Transaction, RawError> {
+    let (&version, json) = raw.split_first().ok_or(RawError::Empty)?;
+    if version != RAW_VERSION {
+        return Err(RawError::UnsupportedVersion(version));
+    }
+
+    let transaction: Transaction = serde_json::from_slice(json)?;
+    if let TransactionData::DeploySmartContract { .. } = transaction.data {
+        return Err(RawError::Deploy);
+    }
+    Ok(transaction)
+}
+
+/// Will encode the transaction as `0x` prefixed lowercase hex
+pub fn to_hex(transaction: &Transaction) -> Result<String, RawError> {
+    Ok(format!("0x{}", hex::encode(encode(transaction)?)))
+}
+
+/// Will decode a transaction from hex, the `0x` prefix is optional
+pub fn from_hex(text: &str) -> Result<Transaction, RawError> {
+    let text = text.trim();
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    decode(&hex::decode(digits)?)
+}
+
+/// Will encode the transaction as standard base64
+pub fn to_base64(transaction: &Transaction) -> Result<String, RawError> {
+    Ok(STANDARD.encode(encode(transaction)?))
+}
+
+/// Will decode a transaction from standard base64
+pub fn from_base64(text: &str) -> Result<Transaction, RawError> {
+    decode(&STANDARD.decode(text.trim())?)
+}
+
+/// Will decode a transaction from text, hex if it starts with `0x` and base64 otherwise
+pub fn from_text(text: &str) -> Result<Transaction, RawError> {
+    let text = text.trim();
+    if text.starts_with("0x") {
+        from_hex(text)
+    } else {
+        from_base64(text)
+    }
+}
+
+/// Will run every check which does not need the chain state: the limits, the
+/// signatures, expiry at the given Unix timestamp in milliseconds and the kind of
+/// transaction. A transaction passing these can still fail on a node, e.g. because
+/// the sender does not exist or did not sign it.
+pub fn check(transaction: &Transaction, limits: &Limits, timestamp: u64) -> Result<(), TxError> {
+    limits.check_transaction(transaction)?;
+
+    match transaction.data {
+        TransactionData::Genesis { .. } => return Err(TxError::GenesisOnly),
+        TransactionData::MintTokens { .. } => return Err(TxError::MintNotAllowed),
+        TransactionData::DeploySmartContract { .. } => return Err(TxError::MissingContract),
+        _ => {}
+    }
+
+    if transaction.is_signed() && !transaction.check_signature() {
+        return Err(TxError::InvalidSignature);
+    }
+
+    if let Some(valid_until) = transaction.valid_until {
+        if transaction.is_expired(timestamp) {
+            return Err(TxError::Expired {
+                valid_until,
+                timestamp,
+            });
+        }
+    }
+
+    Ok(())
+}
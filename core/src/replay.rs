@@ -0,0 +1,246 @@
+//! Re-executes a block log from the genesis spec to find where a state diverged.
+//!
+//! Only the state is re-derived: headers, signatures and the consensus rules are not
+//! checked again. Every transaction runs through [`Transaction::execute`] and the state
+//! root is taken after each of them, which is slow but tells where exactly two traces,
+//! e.g. written by different nodes or versions, part ways.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::Block, blockchain::Blockchain, error::SpecError, spec::ChainSpec,
+    transaction::Transaction,
+};
+
+/// The state after a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionTrace {
+    pub hash: String,
+    pub state_root: String,
+}
+
+/// The state after a block, before the consensus engine's bookkeeping
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockTrace {
+    pub height: usize,
+    pub hash: String,
+    /// The state root the block commits to
+    pub expected_root: Option<String>,
+    pub state_root: String,
+    pub transactions: Vec<TransactionTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DivergenceKind {
+    /// The transaction can not be executed on the replayed state
+    Failed { error: String, code: u64 },
+    /// The state differs from the one the block commits to
+    StateRoot {
+        expected: Option<String>,
+        actual: String,
+    },
+    /// The state differs from the one of the reference trace
+    Trace { expected: String, actual: String },
+    /// The reference trace contains a different block at this height
+    Block { expected: String },
+}
+
+/// The first block, and transaction if known, after which the states differ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    pub height: usize,
+    pub block_hash: String,
+    /// Index of the transaction within the block
+    pub transaction: Option<usize>,
+    pub transaction_hash: Option<String>,
+    pub kind: DivergenceKind,
+}
+
+/// The result of a replay
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// The state up to the last block which was replayed without divergence
+    pub chain: Blockchain,
+    pub trace: Vec<BlockTrace>,
+    pub divergence: Option<Divergence>,
+}
+
+/// Will rebuild the state from the spec by executing the blocks, which may start with
+/// the genesis block of the spec. Stops at the first divergence.
+pub fn replay(spec: &ChainSpec, blocks: &[Block]) -> Result<Replay, SpecError> {
+    let mut chain = Blockchain::from_spec(spec)?;
+    let genesis = chain.blocks[0].clone();
+
+    let blocks = match blocks.split_first() {
+        Some((first, rest)) if first.prev.is_none() => {
+            if first.hash != genesis.hash {
+                return Err(SpecError::GenesisHashMismatch {
+                    expected: genesis.hash.unwrap_or_default(),
+                    actual: first.hash.clone().unwrap_or_default(),
+                });
+            }
+            rest
+        }
+        _ => blocks,
+    };
+
+    let mut trace = vec![BlockTrace {
+        height: 0,
+        hash: genesis.hash.clone().unwrap_or_default(),
+        expected_root: genesis.state_root.clone(),
+        state_root: chain.state_root(),
+        transactions: Vec::new(),
+    }];
+
+    for (height, block) in (1..).zip(blocks) {
+        let block_hash = block.hash.clone().unwrap_or_default();
+        let divergence = |transaction: Option<&Transaction>, index, kind| Divergence {
+            height,
+            block_hash: block_hash.clone(),
+            transaction: transaction.map(|_| index),
+            transaction_hash: transaction.map(Transaction::hash),
+            kind,
+        };
+
+        // The state is only kept once the whole block passed
+        let old_state = chain.current_state();
+        chain.begin_block();
+
+        let mut transactions = Vec::new();
+        for (index, transaction) in block.transactions.iter().enumerate() {
+            if let Err(err) = chain.execute_transaction(transaction, block.timestamp) {
+                // Kept in the trace, so it can still be compared up to the failure
+                trace.push(BlockTrace {
+                    height,
+                    hash: block_hash.clone(),
+                    expected_root: block.state_root.clone(),
+                    state_root: chain.state_root(),
+                    transactions,
+                });
+                (chain.accounts, chain.smart_contracts, chain.ledger) = old_state;
+
+                let kind = DivergenceKind::Failed {
+                    error: err.to_string(),
+                    code: err.code(),
+                };
+                return Ok(Replay {
+                    chain,
+                    trace,
+                    divergence: Some(divergence(Some(transaction), index, kind)),
+                });
+            }
+
+            transactions.push(TransactionTrace {
+                hash: transaction.hash(),
+                state_root: chain.state_root(),
+            });
+        }
+
+        let state_root = chain.state_root();
+        trace.push(BlockTrace {
+            height,
+            hash: block_hash.clone(),
+            expected_root: block.state_root.clone(),
+            state_root: state_root.clone(),
+            transactions,
+        });
+
+        if block.state_root.as_ref() != Some(&state_root) {
+            (chain.accounts, chain.smart_contracts, chain.ledger) = old_state;
+
+            let kind = DivergenceKind::StateRoot {
+                expected: block.state_root.clone(),
+                actual: state_root,
+            };
+            return Ok(Replay {
+                chain,
+                trace,
+                divergence: Some(divergence(None, 0, kind)),
+            });
+        }
+
+        chain.engine().finalize(&mut chain, block);
+        chain.push_block(block.clone());
+    }
+
+    Ok(Replay {
+        chain,
+        trace,
+        divergence: None,
+    })
+}
+
+/// Will compare a trace with a reference trace, e.g. one written by another node, and
+/// return the first transaction after which the states differ. Only the heights both
+/// traces cover are compared.
+pub fn compare(trace: &[BlockTrace], reference: &[BlockTrace]) -> Option<Divergence> {
+    for (block, expected) in trace.iter().zip(reference) {
+        let divergence = |transaction: Option<&TransactionTrace>, index, kind| Divergence {
+            height: block.height,
+            block_hash: block.hash.clone(),
+            transaction: transaction.map(|_| index),
+            transaction_hash: transaction.map(|t| t.hash.clone()),
+            kind,
+        };
+
+        if block.hash != expected.hash {
+            let kind = DivergenceKind::Block {
+                expected: expected.hash.clone(),
+            };
+            return Some(divergence(None, 0, kind));
+        }
+
+        let pairs = block.transactions.iter().zip(&expected.transactions);
+        for (index, (transaction, expected)) in pairs.enumerate() {
+            if transaction.state_root != expected.state_root {
+                let kind = DivergenceKind::Trace {
+                    expected: expected.state_root.clone(),
+                    actual: transaction.state_root.clone(),
+                };
+                return Some(divergence(Some(transaction), index, kind));
+            }
+        }
+
+        // Either trace may end inside the block, e.g. at a failing transaction
+        if block.state_root != expected.state_root
+            && block.transactions.len() == expected.transactions.len()
+        {
+            let kind = DivergenceKind::Trace {
+                expected: expected.state_root.clone(),
+                actual: block.state_root.clone(),
+            };
+            return Some(divergence(None, 0, kind));
+        }
+    }
+
+    None
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block {} (`{}`)", self.height, self.block_hash)?;
+        if let (Some(index), Some(hash)) = (self.transaction, &self.transaction_hash) {
+            write!(f, ", transaction {} (`{}`)", index, hash)?;
+        }
+
+        match &self.kind {
+            DivergenceKind::Failed { error, code } => write!(f, " failed: {} ({})", error, code),
+            DivergenceKind::StateRoot { expected, actual } => write!(
+                f,
+                ": the block commits to the state root `{}` but the state root is `{}`",
+                expected.as_deref().unwrap_or("-"),
+                actual
+            ),
+            DivergenceKind::Trace { expected, actual } => write!(
+                f,
+                ": the reference has the state root `{}` but the state root is `{}`",
+                expected, actual
+            ),
+            DivergenceKind::Block { expected } => {
+                write!(f, ": the reference has the block `{}`", expected)
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use eternal_account::AccountType;
+use eternal_vm::WorldState;
+use serde::{Deserialize, Serialize};
+
+use crate::{block::Block, error::TxError};
+
+/// Percentage of the stake burned for signing two blocks at the same height
+pub const DOUBLE_SIGN_SLASH_PERCENT: u128 = 10;
+
+/// Percentage of the stake burned for proposing an invalid block
+pub const INVALID_BLOCK_SLASH_PERCENT: u128 = 5;
+
+/// Proof that a validator misbehaved, submitted through a transaction
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Evidence {
+    /// Two different blocks on top of the same parent, both signed by the same proposer
+    DoubleSign {
+        first: Box<Block>,
+        second: Box<Block>,
+    },
+    /// A signed block breaking rules that can be checked without any state
+    InvalidBlock { block: Box<Block> },
+}
+
+impl Evidence {
+    /// Will check the evidence against the current state and return the offending validator
+    pub fn verify<T: WorldState>(&self, world_state: &T) -> Result<String, TxError> {
+        let invalid = |reason: &str| Err(TxError::InvalidEvidence(reason.to_string()));
+
+        match self {
+            Evidence::DoubleSign { first, second } => {
+                let offender = signed_by(world_state, first)?;
+                if signed_by(world_state, second)? != offender {
+                    return invalid("the blocks were proposed by different validators");
+                }
+                if first.prev != second.prev {
+                    return invalid("the blocks are not at the same height");
+                }
+                if first.hash == second.hash {
+                    return invalid("the blocks are identical");
+                }
+
+                Ok(offender)
+            }
+            // The signed hash commits to the transactions and their signatures, so only
+            // the proposer can have produced an empty block or one with a bad signature
+            Evidence::InvalidBlock { block } => {
+                let offender = signed_by(world_state, block)?;
+
+                // Every account has to sign its transactions
+                let invalid_transaction = block.transactions.iter().any(|t| !t.check_signature());
+                if block.get_transaction_count() > 0 && !invalid_transaction {
+                    return invalid("the block is valid");
+                }
+
+                Ok(offender)
+            }
+        }
+    }
+
+    /// Will return the percentage of stake burned for this offence
+    pub fn slash_percent(&self) -> u128 {
+        match self {
+            Evidence::DoubleSign { .. } => DOUBLE_SIGN_SLASH_PERCENT,
+            Evidence::InvalidBlock { .. } => INVALID_BLOCK_SLASH_PERCENT,
+        }
+    }
+}
+
+/// Will burn a share of the validator's stake and of its unbonding tokens, jail it and
+/// count the offence. Returns the amount slashed.
+pub fn slash<T: WorldState>(
+    world_state: &mut T,
+    validator: &String,
+    percent: u128,
+) -> Result<u128, TxError> {
+    let account = world_state
+        .get_account_by_id_mut(validator)
+        .ok_or_else(|| TxError::AccountNotFound(validator.clone()))?;
+
+    let AccountType::Validator {
+        stake,
+        incorrectly_validated_blocks,
+        jailed,
+        unbonding,
+        ..
+    } = &mut account.acc_type
+    else {
+        return Err(TxError::InvalidEvidence(format!(
+            "`{}` is not a validator",
+            validator
+        )));
+    };
+    if *jailed {
+        return Err(TxError::AlreadyJailed(validator.clone()));
+    }
+
+    // Split the amounts to avoid overflowing on huge stakes
+    let percent = percent.min(100);
+    let share = |amount: u128| amount / 100 * percent + amount % 100 * percent / 100;
+
+    let mut penalty = share(*stake);
+    *stake -= penalty;
+    for entry in unbonding.iter_mut() {
+        let burned = share(entry.amount);
+        entry.amount -= burned;
+        penalty += burned;
+    }
+    *incorrectly_validated_blocks += 1;
+    *jailed = true;
+
+    Ok(penalty)
+}
+
+/// Will return the proposer of a block if the block hash matches its content and the
+/// proposer's signature over it is valid
+fn signed_by<T: WorldState>(world_state: &T, block: &Block) -> Result<String, TxError> {
+    let invalid = |reason: &str| TxError::InvalidEvidence(reason.to_string());
+
+    if !block.verify_own_hash() {
+        return Err(invalid("the block hash does not match its content"));
+    }
+
+    let proposer = block
+        .proposer
+        .as_ref()
+        .ok_or_else(|| invalid("the block has no proposer"))?;
+    let (Some(hash), Some(signature)) = (&block.hash, &block.signature) else {
+        return Err(invalid("the block is not signed"));
+    };
+
+    let account = world_state
+        .get_account_by_id(proposer)
+        .ok_or_else(|| TxError::AccountNotFound(proposer.clone()))?;
+    if !account.verify(hash.as_bytes(), signature) {
+        return Err(invalid("the block signature is invalid"));
+    }
+
+    Ok(proposer.clone())
+}
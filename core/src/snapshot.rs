@@ -0,0 +1,505 @@
+//! State snapshots, letting a new node start from a recent state instead of executing
+//! every block since genesis.
+//!
+//! A snapshot holds the state a block commits to by its state root, i.e. after its
+//! transactions but before the consensus engine's bookkeeping. It consists of a
+//! manifest and chunks:
+//!
+//! - The manifest names the format version, the chain, the block (height and hash),
+//!   its state root, the authority set and the hash of every chunk
+//! - A chunk is the compact JSON encoding of up to [`CHUNK_ACCOUNTS`] accounts sorted
+//!   by address, each with its token balances and its contract if it is one. The chunk
+//!   hash is taken over the exact bytes, which are the same on every node for the same
+//!   state
+//!
+//! Restoring checks the chunks against the manifest and the recomputed state root
+//! against the block header, which itself has to be the end of a header chain starting
+//! at the genesis block, see [`verify_headers`]. Contract code is not part of the
+//! state: only the contracts of the chain spec can be restored, as [`token_contract`]s
+//! just like at genesis. Snapshots holding deployed contracts are rejected.
+
+use std::collections::{BTreeMap, HashMap};
+
+use eternal_account::{
+    secp256k1::PublicKey, verify_signature, Account, AccountType, Address, Store,
+};
+use eternal_vm::{
+    ledger::TokenLedger,
+    smart_contract::{SmartContract, SmartContractApi, SmartContractStanderd},
+};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::{
+    block::BlockHeader,
+    blockchain::{Blockchain, Consensus},
+    consensus::{AuthoritySet, ProofOfWork},
+    domain::{tagged_hasher, SNAPSHOT_TAG},
+    error::{BlockError, SnapshotError, SpecError},
+    pos::ValidatorSet,
+    spec::{token_contract, ChainSpec},
+    state::state_root,
+};
+
+/// The version of the snapshot format written by [`Snapshot::new`]
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+/// The amount of accounts per chunk
+pub const CHUNK_ACCOUNTS: usize = 1000;
+
+/// Describes a snapshot and commits to its chunks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub chain_id: u64,
+    /// Height of the block whose state the snapshot holds
+    pub height: usize,
+    pub block_hash: String,
+    pub state_root: String,
+    /// The authorities (proof of authority only), part of the state root
+    pub authorities: AuthoritySet,
+    /// Hashes of the chunks in order, see [`chunk_hash`]
+    pub chunks: Vec<String>,
+}
+
+/// An account as stored in a chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEntry {
+    pub address: String,
+    pub public_key: String,
+    pub public_key_bytes: Vec<u8>,
+    pub acc_type: AccountType,
+    pub tokens: u128,
+    #[serde(default)]
+    pub nonce: u128,
+    /// Sorted by namespace and key, so the chunk encoding does not depend on the node
+    pub store: Store,
+    /// The token balances by contract
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub balances: BTreeMap<String, u128>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<ContractEntry>,
+}
+
+/// The state of a contract, without its code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEntry {
+    pub standard: SmartContractStanderd,
+    pub publisher: String,
+    pub total_supply: u128,
+}
+
+/// A snapshot with the encoded chunks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub manifest: SnapshotManifest,
+    pub chunks: Vec<Vec<u8>>,
+}
+
+/// The state a block commits to, restored from a snapshot or re-derived by
+/// [`crate::blockchain::Blockchain::state_at`]
+#[derive(Debug, Clone)]
+pub struct SnapshotState {
+    pub accounts: HashMap<String, Account>,
+    pub smart_contracts: HashMap<String, SmartContract>,
+    pub ledger: TokenLedger,
+    pub authorities: AuthoritySet,
+}
+
+impl Snapshot {
+    /// Will take a snapshot of the chain's current state, which the block at `height`
+    /// commits to
+    pub fn new(
+        chain: &Blockchain,
+        height: usize,
+        header: &BlockHeader,
+        chunk_accounts: usize,
+    ) -> Self {
+        let mut addresses: Vec<&String> = chain.accounts.keys().collect();
+        addresses.sort();
+
+        let entries: Vec<AccountEntry> = addresses
+            .into_iter()
+            .map(|address| {
+                let contract = chain.smart_contracts.get(address);
+                entry(address, &chain.accounts[address], contract, &chain.ledger)
+            })
+            .collect();
+        // Only plain data, encoding can not fail
+        let chunks: Vec<Vec<u8>> = entries
+            .chunks(chunk_accounts.max(1))
+            .map(|chunk| serde_json::to_vec(chunk).unwrap_or_default())
+            .collect();
+
+        Self {
+            manifest: SnapshotManifest {
+                version: SNAPSHOT_VERSION,
+                chain_id: chain.chain_id,
+                height,
+                block_hash: header.hash.clone().unwrap_or_default(),
+                state_root: chain.state_root(),
+                authorities: chain.authorities.clone(),
+                chunks: chunks.iter().map(|chunk| chunk_hash(chunk)).collect(),
+            },
+            chunks,
+        }
+    }
+
+    /// Will check the snapshot against the header of the block at `height` and decode
+    /// the state
+    pub fn restore(
+        &self,
+        chain_id: u64,
+        height: usize,
+        header: &BlockHeader,
+    ) -> Result<SnapshotState, SnapshotError> {
+        let manifest = &self.manifest;
+        if manifest.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(manifest.version));
+        }
+        if manifest.chain_id != chain_id {
+            return Err(SnapshotError::WrongChain {
+                expected: chain_id,
+                actual: manifest.chain_id,
+            });
+        }
+        let block_hash = header.hash.clone().unwrap_or_default();
+        if manifest.height != height || manifest.block_hash != block_hash {
+            return Err(SnapshotError::WrongBlock {
+                height,
+                expected: block_hash,
+                actual: manifest.block_hash.clone(),
+            });
+        }
+        if manifest.chunks.len() != self.chunks.len() {
+            return Err(SnapshotError::ChunkCount {
+                expected: manifest.chunks.len(),
+                actual: self.chunks.len(),
+            });
+        }
+
+        let mut accounts = HashMap::new();
+        let mut smart_contracts = HashMap::new();
+        let mut ledger = TokenLedger::default();
+        for (index, (chunk, expected)) in self.chunks.iter().zip(&manifest.chunks).enumerate() {
+            if &chunk_hash(chunk) != expected {
+                return Err(SnapshotError::ChunkHash(index));
+            }
+
+            let entries: Vec<AccountEntry> = serde_json::from_slice(chunk)
+                .map_err(|source| SnapshotError::CorruptChunk { index, source })?;
+            for entry in entries {
+                // Balances which do not add up are caught by the state root below
+                for (contract, balance) in &entry.balances {
+                    let _ = ledger.credit(contract, &entry.address, *balance);
+                }
+                if let Some(contract) = entry.contract {
+                    smart_contracts.insert(
+                        entry.address.clone(),
+                        token_contract(
+                            contract.standard,
+                            contract.publisher,
+                            contract.total_supply,
+                        ),
+                    );
+                }
+                accounts.insert(
+                    entry.address,
+                    Account {
+                        public_key: entry.public_key,
+                        public_key_bytes: entry.public_key_bytes,
+                        store: entry.store,
+                        acc_type: entry.acc_type,
+                        tokens: entry.tokens,
+                        nonce: entry.nonce,
+                    },
+                );
+            }
+        }
+
+        // Covers the authorities of the manifest as well
+        let actual = state_root(&accounts, &smart_contracts, &ledger, &manifest.authorities);
+        let expected = header.state_root.clone().unwrap_or_default();
+        if actual != expected || actual != manifest.state_root {
+            return Err(SnapshotError::StateRootMismatch { expected, actual });
+        }
+
+        Ok(SnapshotState {
+            accounts,
+            smart_contracts,
+            ledger,
+            authorities: manifest.authorities.clone(),
+        })
+    }
+}
+
+/// Will return the hash of an encoded chunk as uppercase hex
+pub fn chunk_hash(chunk: &[u8]) -> String {
+    let mut hasher = tagged_hasher(SNAPSHOT_TAG);
+    hasher.update(chunk);
+    format!("{:X}", hasher.finalize())
+}
+
+/// Checks that the headers form a chain from the genesis block of the spec: every
+/// header hash matches, links to its predecessor and carries the work of a proof of
+/// work chain or the seal of an account allowed to seal it.
+///
+/// Who may seal a proof of authority or proof of stake block follows from the state
+/// before it, which the headers do not hold. A seal is accepted from the authorities or
+/// validators of the genesis state and of the snapshot's `state`, the block of the
+/// snapshot has to be sealed by the authority in turn with the snapshot's authorities.
+/// A chain whose sealers changed to ones in neither set in between can not be started
+/// from the snapshot, such a node has to execute every block.
+pub fn verify_headers<'a, I>(
+    spec: &ChainSpec,
+    headers: I,
+    state: &SnapshotState,
+) -> Result<(), SnapshotError>
+where
+    I: IntoIterator<Item = &'a BlockHeader>,
+{
+    let sealers = sealers(spec, state)?;
+    let (height, last) = check_headers(spec, headers, Some(&sealers))?;
+
+    if let Consensus::ProofOfAuthority { .. } = spec.consensus {
+        let expected = state
+            .authorities
+            .in_turn(height)
+            .and_then(|key| key.parse::<PublicKey>().ok())
+            .map(|key| address(&key))
+            .ok_or(SnapshotError::Header {
+                height,
+                source: BlockError::NoValidators,
+            })?;
+        let actual = last.proposer.clone().unwrap_or_default();
+        if actual != expected {
+            let source = BlockError::UnexpectedProposer { expected, actual };
+            return Err(SnapshotError::Header { height, source });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the headers like [`verify_headers`] except for the seals, for headers the
+/// node already checked, e.g. the ones of blocks it pruned
+pub fn link_headers<'a, I>(spec: &ChainSpec, headers: I) -> Result<(), SnapshotError>
+where
+    I: IntoIterator<Item = &'a BlockHeader>,
+{
+    check_headers(spec, headers, None).map(|_| ())
+}
+
+/// Checks that every contract of the state is one of the chain spec, the code of any
+/// other contract is lost
+pub(crate) fn check_contracts(
+    spec: &ChainSpec,
+    state: &SnapshotState,
+) -> Result<(), SnapshotError> {
+    let (_, genesis_contracts, _) = spec.genesis_state()?;
+    for (address, contract) in &state.smart_contracts {
+        let known = genesis_contracts
+            .get(address)
+            .is_some_and(|genesis| contract_entry(genesis) == contract_entry(contract));
+        if !known {
+            return Err(SnapshotError::UnrestorableContract(address.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// The accounts allowed to seal the headers after the genesis block
+enum Sealers {
+    /// Proof of work blocks are not sealed
+    None,
+    /// The dev authority seals every block
+    Dev {
+        authority: String,
+        key: Option<PublicKey>,
+    },
+    /// Any of the authorities or validators, by address
+    Any(HashMap<String, PublicKey>),
+}
+
+/// Will collect the accounts allowed to seal the headers of the chain, see [`verify_headers`]
+fn sealers(spec: &ChainSpec, state: &SnapshotState) -> Result<Sealers, SnapshotError> {
+    let (genesis_accounts, ..) = spec.genesis_state()?;
+
+    let sealers = match &spec.consensus {
+        Consensus::ProofOfWork { .. } => Sealers::None,
+        Consensus::Dev { authority } => Sealers::Dev {
+            authority: authority.clone(),
+            key: genesis_accounts
+                .get(authority)
+                .and_then(|account| PublicKey::from_slice(&account.public_key_bytes).ok()),
+        },
+        Consensus::ProofOfAuthority { .. } => {
+            let genesis = spec.consensus.authority_set();
+            let keys = genesis
+                .authorities()
+                .iter()
+                .chain(state.authorities.authorities())
+                .filter_map(|key| key.parse::<PublicKey>().ok())
+                .map(|key| (address(&key), key))
+                .collect();
+            Sealers::Any(keys)
+        }
+        Consensus::ProofOfStake { min_stake } => {
+            let mut keys = HashMap::new();
+            for accounts in [&genesis_accounts, &state.accounts] {
+                let validators = ValidatorSet::from_accounts(accounts, *min_stake);
+                for (validator, _) in validators.validators() {
+                    let key = accounts
+                        .get(validator)
+                        .and_then(|account| PublicKey::from_slice(&account.public_key_bytes).ok());
+                    if let Some(key) = key {
+                        keys.insert(validator.clone(), key);
+                    }
+                }
+            }
+            Sealers::Any(keys)
+        }
+    };
+
+    Ok(sealers)
+}
+
+/// Will check the headers and return the last one with its height. The seals are only
+/// checked if the sealers are given.
+fn check_headers<'a, I>(
+    spec: &ChainSpec,
+    headers: I,
+    sealers: Option<&Sealers>,
+) -> Result<(usize, &'a BlockHeader), SnapshotError>
+where
+    I: IntoIterator<Item = &'a BlockHeader>,
+{
+    let genesis_hash = match &spec.genesis_hash {
+        Some(hash) => hash.clone(),
+        None => spec.genesis_block()?.hash.unwrap_or_default(),
+    };
+    let work = match spec.consensus {
+        Consensus::ProofOfWork { difficulty } => Some(ProofOfWork::new(difficulty)),
+        _ => None,
+    };
+    let invalid = |height, source| SnapshotError::Header { height, source };
+
+    let mut last: Option<(usize, &BlockHeader)> = None;
+    for (height, header) in headers.into_iter().enumerate() {
+        if !header.verify_own_hash() {
+            return Err(invalid(height, BlockError::HashMismatch));
+        }
+
+        let hash = header.hash.as_deref().unwrap_or_default();
+        match last {
+            None if hash != genesis_hash => {
+                let actual = hash.to_string();
+                return Err(SpecError::GenesisHashMismatch {
+                    expected: genesis_hash,
+                    actual,
+                }
+                .into());
+            }
+            None => {}
+            Some((_, prev)) => {
+                if header.prev != prev.hash {
+                    return Err(invalid(height, BlockError::InvalidPrev));
+                }
+                if let Some(work) = work {
+                    if !work.meets_difficulty(hash) {
+                        let difficulty = work.difficulty;
+                        return Err(invalid(height, BlockError::InsufficientWork { difficulty }));
+                    }
+                }
+                if let Some(sealers) = sealers {
+                    check_seal(height, header, sealers)?;
+                }
+            }
+        }
+        last = Some((height, header));
+    }
+
+    last.ok_or(invalid(0, BlockError::MissingGenesis))
+}
+
+/// Checks that the header was proposed and signed by one of the sealers
+fn check_seal(height: usize, header: &BlockHeader, sealers: &Sealers) -> Result<(), SnapshotError> {
+    let invalid = |source| SnapshotError::Header { height, source };
+    let proposer = header.proposer.as_ref();
+
+    let key = match sealers {
+        Sealers::None => return Ok(()),
+        Sealers::Dev { authority, key } => {
+            let proposer = proposer.ok_or(invalid(BlockError::MissingProposer))?;
+            if proposer != authority {
+                return Err(invalid(BlockError::UnexpectedProposer {
+                    expected: authority.clone(),
+                    actual: proposer.clone(),
+                }));
+            }
+            key.as_ref()
+        }
+        Sealers::Any(keys) => {
+            let proposer = proposer.ok_or(invalid(BlockError::MissingProposer))?;
+            let key = keys.get(proposer).ok_or(SnapshotError::UnknownSealer {
+                height,
+                proposer: proposer.clone(),
+            })?;
+            Some(key)
+        }
+    };
+
+    let valid_signature = match (key, &header.signature, &header.hash) {
+        (Some(key), Some(signature), Some(hash)) => {
+            verify_signature(key, hash.as_bytes(), signature)
+        }
+        _ => false,
+    };
+    if !valid_signature {
+        return Err(invalid(BlockError::InvalidProposerSignature));
+    }
+
+    Ok(())
+}
+
+fn address(key: &PublicKey) -> String {
+    Address::from_public_key_bytes(&key.serialize()).to_string()
+}
+
+/// Will return the entry of an account as stored in a chunk
+pub fn entry(
+    address: &str,
+    account: &Account,
+    contract: Option<&SmartContract>,
+    ledger: &TokenLedger,
+) -> AccountEntry {
+    AccountEntry {
+        address: address.to_string(),
+        public_key: account.public_key.clone(),
+        public_key_bytes: account.public_key_bytes.clone(),
+        acc_type: account.acc_type.clone(),
+        tokens: account.tokens,
+        nonce: account.nonce,
+        store: account.store.clone(),
+        balances: ledger
+            .tokens(address)
+            .map(|(contract, balance)| (contract.to_string(), balance))
+            .collect(),
+        contract: contract.map(contract_entry),
+    }
+}
+
+/// Will return the state of the contract as stored in a chunk
+fn contract_entry(contract: &SmartContract) -> ContractEntry {
+    let SmartContractApi::ESC20 {
+        publisher,
+        total_suply,
+        ..
+    } = &contract.api;
+    ContractEntry {
+        standard: contract.r#type.clone(),
+        publisher: publisher.clone(),
+        total_supply: *total_suply,
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use eternal_account::{
+    hd::{parse_mnemonic, HdWallet},
+    secp256k1::PublicKey,
+    Account, AccountType, Address, Store, Wallet,
+};
+use eternal_vm::{
+    ledger::TokenLedger,
+    smart_contract::{SmartContract, SmartContractApi, SmartContractStanderd},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    block::Block,
+    consensus::Consensus,
+    error::SpecError,
+    limits::Limits,
+    state::state_root,
+    transaction::{Transaction, TransactionData},
+};
+
+/// The sender of the genesis transaction, no account can ever have this address
+pub const GENESIS_SENDER: &str = "genesis";
+
+/// The well known mnemonic of the development accounts, never use it for real funds
+pub const DEV_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// The accounts, contracts and token balances created by the genesis block
+pub type GenesisState = (
+    HashMap<String, Account>,
+    HashMap<String, SmartContract>,
+    TokenLedger,
+);
+
+/// Tokens given to the account of a public key, a stake turns it into a validator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub public_key: String,
+    pub tokens: u128,
+    #[serde(default)]
+    pub stake: u128,
+}
+
+/// A token contract existing from the genesis block on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenesisContract {
+    pub address: String,
+    pub standard: SmartContractStanderd,
+    /// Receives the whole supply, has to be allocated as well
+    pub publisher: String,
+    pub total_supply: u128,
+}
+
+/// Everything needed to build the genesis block and state of a chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainSpec {
+    pub name: String,
+    pub chain_id: u64,
+    /// Unix timestamp in milliseconds
+    pub genesis_timestamp: u64,
+    pub consensus: Consensus,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub allocations: Vec<Allocation>,
+    #[serde(default)]
+    pub contracts: Vec<GenesisContract>,
+    /// The expected genesis hash, checked when the chain is started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<String>,
+}
+
+impl ChainSpec {
+    /// Will load a `.toml` or `.json` chain spec
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SpecError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            other => Err(SpecError::UnsupportedFormat(
+                other.unwrap_or_default().to_string(),
+            )),
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, SpecError> {
+        // TOML can not deserialize into u128, the JSON representation of the values can
+        let value: toml::Value = toml::from_str(content)?;
+        Ok(serde_json::from_value(serde_json::to_value(value)?)?)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, SpecError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, SpecError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// A single node development chain, sealed by the first development account.
+    /// The accounts are derived from [`DEV_MNEMONIC`], see [`dev_wallets`].
+    pub fn dev() -> Self {
+        let wallets = dev_wallets(2);
+        let allocation = |wallet: &Wallet, tokens| Allocation {
+            public_key: wallet.public_key().to_string(),
+            tokens,
+            stake: 0,
+        };
+
+        Self {
+            name: "eternal-dev".to_string(),
+            chain_id: 1337,
+            genesis_timestamp: 0,
+            consensus: Consensus::Dev {
+                authority: wallets[0].address().to_string(),
+            },
+            limits: Limits::default(),
+            allocations: vec![
+                allocation(&wallets[0], 100_000),
+                allocation(&wallets[1], 10_000),
+            ],
+            contracts: Vec::new(),
+            genesis_hash: None,
+        }
+    }
+
+    /// Will return the hash of the spec, the expected genesis hash is not part of it
+    pub fn hash(&self) -> String {
+        let spec = Self {
+            genesis_hash: None,
+            ..self.clone()
+        };
+        // Only plain data and vectors, serializing can not fail and is deterministic
+        let json = serde_json::to_vec(&spec).unwrap_or_default();
+
+        format!("{:X}", Sha256::digest(json))
+    }
+
+    /// Will build the genesis block, which commits to the whole spec and the genesis state
+    pub fn genesis_block(&self) -> Result<Block, SpecError> {
+        let mut transaction = Transaction::new(
+            self.chain_id,
+            GENESIS_SENDER.to_string(),
+            TransactionData::Genesis {
+                chain_id: self.chain_id,
+                spec_hash: self.hash(),
+            },
+            0,
+        );
+        transaction.created_at = UNIX_EPOCH + Duration::from_millis(self.genesis_timestamp);
+
+        let (accounts, smart_contracts, ledger) = self.genesis_state()?;
+
+        let mut block = Block::new(None);
+        block.add_transaction(transaction);
+        block.set_timestamp(self.genesis_timestamp);
+        block.set_state_root(state_root(
+            &accounts,
+            &smart_contracts,
+            &ledger,
+            &self.consensus.authority_set(),
+        ));
+        Ok(block)
+    }
+
+    /// Will build the accounts, contracts and token balances existing at genesis
+    pub fn genesis_state(&self) -> Result<GenesisState, SpecError> {
+        let mut accounts = HashMap::new();
+        for allocation in &self.allocations {
+            let public_key = allocation
+                .public_key
+                .parse::<PublicKey>()
+                .map_err(|_| SpecError::InvalidPublicKey(allocation.public_key.clone()))?;
+
+            let acc_type = if allocation.stake > 0 {
+                AccountType::Validator {
+                    stake: allocation.stake,
+                    correctly_validated_blocks: 0,
+                    incorrectly_validated_blocks: 0,
+                    jailed: false,
+                    unbonding: Vec::new(),
+                }
+            } else {
+                AccountType::User
+            };
+            let mut account = Account::from_public_key(&public_key, acc_type);
+            account.tokens = allocation.tokens;
+
+            let address = account.generate_adress();
+            if accounts.insert(address.clone(), account).is_some() {
+                return Err(SpecError::DuplicateAccount(address));
+            }
+        }
+
+        let mut smart_contracts = HashMap::new();
+        let mut ledger = TokenLedger::default();
+        for contract in &self.contracts {
+            let address = contract
+                .address
+                .parse::<Address>()
+                .map_err(SpecError::InvalidAddress)?
+                .to_string();
+            if accounts.contains_key(&address) {
+                return Err(SpecError::DuplicateAccount(address));
+            }
+
+            if !accounts.contains_key(&contract.publisher) {
+                return Err(SpecError::UnknownPublisher(contract.publisher.clone()));
+            }
+            // A fresh contract, the supply can not overflow
+            let _ = ledger.credit(&address, &contract.publisher, contract.total_supply);
+
+            // Contracts have no key, their address is given by the spec
+            accounts.insert(
+                address.clone(),
+                Account {
+                    public_key: String::new(),
+                    public_key_bytes: Vec::new(),
+                    store: Store::default(),
+                    acc_type: AccountType::SmartContract,
+                    tokens: 0,
+                    nonce: 0,
+                },
+            );
+            smart_contracts.insert(
+                address,
+                token_contract(
+                    contract.standard.clone(),
+                    contract.publisher.clone(),
+                    contract.total_supply,
+                ),
+            );
+        }
+
+        Ok((accounts, smart_contracts, ledger))
+    }
+}
+
+/// Will create a token contract without code of its own, the balances are kept in the
+/// [`TokenLedger`]. Genesis contracts and contracts restored from a snapshot are
+/// created this way.
+pub fn token_contract(
+    standard: SmartContractStanderd,
+    publisher: String,
+    total_supply: u128,
+) -> SmartContract {
+    SmartContract::new(
+        standard,
+        SmartContractApi::ESC20 {
+            publisher,
+            total_suply: total_supply,
+            transfer: |_, _, _, _| Ok(()),
+        },
+    )
+}
+
+/// Will derive the first `count` development wallets from [`DEV_MNEMONIC`]
+pub fn dev_wallets(count: u32) -> Vec<Wallet> {
+    // The mnemonic is a constant, deriving from it can not fail
+    let mnemonic = parse_mnemonic(DEV_MNEMONIC).expect("valid dev mnemonic");
+    let wallet = HdWallet::from_mnemonic(&mnemonic, "").expect("valid dev seed");
+
+    (0..count)
+        .map(|index| wallet.account(index).expect("valid dev account"))
+        .collect()
+}
@@ -0,0 +1,294 @@
+//! The state root, committing to every account, its store, the contracts and the
+//! authorities.
+//!
+//! Every account is a leaf of a binary Merkle tree, sorted by address. A leaf hashes
+//! the address, the key, the type (its variant and fields), balance and nonce of the
+//! account, the root of its store, the root of its token balances and, for contract
+//! accounts, the contract. The store root is the Merkle root over the store entries
+//! sorted by namespace and key, the token root the one over the account's balances in
+//! the [`TokenLedger`] sorted by contract. Proof of authority chains add the authority
+//! set and its open proposals as the last leaf.
+
+use std::collections::HashMap;
+
+use eternal_account::{Account, AccountType, Store, StoreKey};
+use eternal_vm::{
+    ledger::TokenLedger,
+    smart_contract::{SmartContract, SmartContractApi},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    consensus::AuthoritySet,
+    domain::{tagged_hasher, update_field, ACCOUNT_TAG, AUTHORITY_TAG, STORE_TAG, TOKEN_TAG},
+    merkle::{self, Hash, MerkleProof},
+    snapshot::AccountEntry,
+};
+
+/// Will return the root over the accounts, contracts, token balances and authorities as
+/// uppercase hex
+pub fn state_root(
+    accounts: &HashMap<String, Account>,
+    contracts: &HashMap<String, SmartContract>,
+    ledger: &TokenLedger,
+    authorities: &AuthoritySet,
+) -> String {
+    let (_, leaves) = leaves(accounts, contracts, ledger, authorities);
+    merkle::to_hex(&merkle::root(&leaves))
+}
+
+/// Will return the proof of the account's leaf, none if there is no such account
+pub fn account_proof(
+    accounts: &HashMap<String, Account>,
+    contracts: &HashMap<String, SmartContract>,
+    ledger: &TokenLedger,
+    authorities: &AuthoritySet,
+    address: &str,
+) -> Option<MerkleProof> {
+    let (addresses, leaves) = leaves(accounts, contracts, ledger, authorities);
+    let index = addresses.binary_search(&address).ok()?;
+    merkle::proof(&leaves, index)
+}
+
+/// Will return the proof of the authority leaf, none if there are no authorities
+pub fn authority_proof(
+    accounts: &HashMap<String, Account>,
+    contracts: &HashMap<String, SmartContract>,
+    ledger: &TokenLedger,
+    authorities: &AuthoritySet,
+) -> Option<MerkleProof> {
+    if authorities.is_empty() {
+        return None;
+    }
+
+    let (_, leaves) = leaves(accounts, contracts, ledger, authorities);
+    merkle::proof(&leaves, leaves.len() - 1)
+}
+
+/// Will return the sorted addresses and the leaves of the state
+fn leaves<'a>(
+    accounts: &'a HashMap<String, Account>,
+    contracts: &HashMap<String, SmartContract>,
+    ledger: &TokenLedger,
+    authorities: &AuthoritySet,
+) -> (Vec<&'a str>, Vec<Hash>) {
+    let mut addresses: Vec<&str> = accounts.keys().map(String::as_str).collect();
+    addresses.sort();
+
+    let mut leaves: Vec<Hash> = addresses
+        .iter()
+        .map(|address| {
+            let contract = contracts.get(*address);
+            account_leaf(address, &accounts[*address], contract, ledger)
+        })
+        .collect();
+    if !authorities.is_empty() {
+        leaves.push(authority_leaf(authorities));
+    }
+
+    (addresses, leaves)
+}
+
+/// Will return the leaf of an account
+pub fn account_leaf(
+    address: &str,
+    account: &Account,
+    contract: Option<&SmartContract>,
+    ledger: &TokenLedger,
+) -> Hash {
+    let contract = contract.map(|contract| {
+        let SmartContractApi::ESC20 {
+            publisher,
+            total_suply,
+            ..
+        } = &contract.api;
+        (contract.r#type.to(), publisher.as_str(), *total_suply)
+    });
+
+    let store_root = store_root(&account.store);
+    leaf(
+        address,
+        account,
+        &store_root,
+        &token_root(ledger, address),
+        contract,
+    )
+}
+
+/// Will return the leaf of an account entry whose store and token balances are only
+/// known by their roots
+pub fn entry_leaf(entry: &AccountEntry, store_root: &Hash, token_root: &Hash) -> Hash {
+    let account = Account {
+        public_key: entry.public_key.clone(),
+        public_key_bytes: entry.public_key_bytes.clone(),
+        store: Store::default(),
+        acc_type: entry.acc_type.clone(),
+        tokens: entry.tokens,
+        nonce: entry.nonce,
+    };
+    let contract = entry.contract.as_ref().map(|contract| {
+        (
+            contract.standard.to(),
+            contract.publisher.as_str(),
+            contract.total_supply,
+        )
+    });
+
+    leaf(&entry.address, &account, store_root, token_root, contract)
+}
+
+/// The store and the token balances are committed to by their roots. The functions of a
+/// contract are not part of the state, only its standard, publisher and total supply.
+fn leaf(
+    address: &str,
+    account: &Account,
+    store_root: &Hash,
+    token_root: &Hash,
+    contract: Option<(&str, &str, u128)>,
+) -> Hash {
+    let mut hasher = tagged_hasher(ACCOUNT_TAG);
+    update_field(&mut hasher, address.as_bytes());
+    update_field(&mut hasher, account.public_key.as_bytes());
+    update_field(&mut hasher, &account.public_key_bytes);
+    hash_account_type(&mut hasher, &account.acc_type);
+    hasher.update(account.tokens.to_be_bytes());
+    hasher.update(account.nonce.to_be_bytes());
+    hasher.update(store_root);
+    hasher.update(token_root);
+
+    if let Some((standard, publisher, total_supply)) = contract {
+        update_field(&mut hasher, standard.as_bytes());
+        update_field(&mut hasher, publisher.as_bytes());
+        hasher.update(total_supply.to_be_bytes());
+    }
+
+    hasher.finalize().into()
+}
+
+/// Will feed the variant of the account type and its fields into the hasher
+fn hash_account_type(hasher: &mut Sha256, acc_type: &AccountType) {
+    match acc_type {
+        AccountType::User => hasher.update([0]),
+        AccountType::Contract => hasher.update([1]),
+        AccountType::Node => hasher.update([2]),
+        AccountType::Token { init_supply, burn } => {
+            hasher.update([3]);
+            hasher.update(init_supply.to_be_bytes());
+            hasher.update([*burn as u8]);
+        }
+        AccountType::SmartContract => hasher.update([4]),
+        AccountType::Multisig {
+            public_keys,
+            threshold,
+        } => {
+            hasher.update([5]);
+            hasher.update((public_keys.len() as u64).to_be_bytes());
+            for public_key in public_keys {
+                update_field(hasher, public_key.as_bytes());
+            }
+            hasher.update([*threshold]);
+        }
+        AccountType::Validator {
+            stake,
+            correctly_validated_blocks,
+            incorrectly_validated_blocks,
+            jailed,
+            unbonding,
+        } => {
+            hasher.update([6]);
+            hasher.update(stake.to_be_bytes());
+            hasher.update(correctly_validated_blocks.to_be_bytes());
+            hasher.update(incorrectly_validated_blocks.to_be_bytes());
+            hasher.update([*jailed as u8]);
+            hasher.update((unbonding.len() as u64).to_be_bytes());
+            for entry in unbonding {
+                hasher.update(entry.amount.to_be_bytes());
+                hasher.update(entry.release_height.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// Will return the root over the store entries
+pub fn store_root(store: &Store) -> Hash {
+    let leaves: Vec<Hash> = store
+        .entries()
+        .map(|(namespace, key, value)| store_leaf(namespace, key, value))
+        .collect();
+    merkle::root(&leaves)
+}
+
+/// Will return the proof of the store entry, none if there is no such entry
+pub fn store_proof(store: &Store, key: &StoreKey) -> Option<MerkleProof> {
+    let mut index = None;
+    let leaves: Vec<Hash> = store
+        .entries()
+        .enumerate()
+        .map(|(i, (namespace, entry_key, value))| {
+            if namespace == key.namespace && entry_key == key.key {
+                index = Some(i);
+            }
+            store_leaf(namespace, entry_key, value)
+        })
+        .collect();
+    merkle::proof(&leaves, index?)
+}
+
+/// Will return the leaf of a store entry
+pub fn store_leaf(namespace: &str, key: &[u8], value: &[u8]) -> Hash {
+    let mut hasher = tagged_hasher(STORE_TAG);
+    update_field(&mut hasher, namespace.as_bytes());
+    update_field(&mut hasher, key);
+    update_field(&mut hasher, value);
+    hasher.finalize().into()
+}
+
+/// Will return the root over the token balances of the holder
+pub fn token_root(ledger: &TokenLedger, holder: &str) -> Hash {
+    let (_, leaves) = token_leaves(ledger, holder);
+    merkle::root(&leaves)
+}
+
+/// Will return the proof of the holder's balance, none if it holds none of the tokens
+pub fn token_proof(ledger: &TokenLedger, holder: &str, contract: &str) -> Option<MerkleProof> {
+    let (contracts, leaves) = token_leaves(ledger, holder);
+    let index = contracts.binary_search(&contract).ok()?;
+    merkle::proof(&leaves, index)
+}
+
+/// Will return the contracts the holder has tokens of, sorted, and the leaves of the balances
+fn token_leaves<'a>(ledger: &'a TokenLedger, holder: &'a str) -> (Vec<&'a str>, Vec<Hash>) {
+    ledger
+        .tokens(holder)
+        .map(|(contract, balance)| (contract, token_leaf(contract, balance)))
+        .unzip()
+}
+
+/// Will return the leaf of a token balance
+pub fn token_leaf(contract: &str, balance: u128) -> Hash {
+    let mut hasher = tagged_hasher(TOKEN_TAG);
+    update_field(&mut hasher, contract.as_bytes());
+    hasher.update(balance.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Will return the leaf of the authority set, the proposals are kept in the order they were opened
+pub fn authority_leaf(authorities: &AuthoritySet) -> Hash {
+    let mut hasher = tagged_hasher(AUTHORITY_TAG);
+    hasher.update((authorities.authorities().len() as u64).to_be_bytes());
+    for public_key in authorities.authorities() {
+        update_field(&mut hasher, public_key.as_bytes());
+    }
+
+    hasher.update((authorities.proposals().len() as u64).to_be_bytes());
+    for proposal in authorities.proposals() {
+        update_field(&mut hasher, proposal.public_key.as_bytes());
+        hasher.update([proposal.add as u8]);
+        hasher.update((proposal.voters.len() as u64).to_be_bytes());
+        for voter in &proposal.voters {
+            update_field(&mut hasher, voter.as_bytes());
+        }
+    }
+
+    hasher.finalize().into()
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{block::Block, error::StorageError};
+
+/// An append only file of blocks, one JSON encoded block per line.
+/// Used for the node's storage as well as for block exports.
+#[derive(Debug)]
+pub struct BlockLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl BlockLog {
+    /// Will open the log for appending, the file is created if missing. A block cut off
+    /// by a crash while it was written is removed, see [`BlockLog::read`].
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let content = fs::read(&path)?;
+        let (complete, torn) = split_torn_line(&content);
+        match torn {
+            Some(line) if serde_json::from_slice::<Block>(line).is_ok() => file.write_all(b"\n")?,
+            Some(_) => file.set_len(complete.len() as u64)?,
+            None => {}
+        }
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a block, it is only guaranteed to be on disk after [`BlockLog::flush`]
+    pub fn append(&mut self, block: &Block) -> Result<(), StorageError> {
+        serde_json::to_writer(&mut self.writer, block)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Will write the buffered blocks and sync the file to disk
+    pub fn flush(&mut self) -> Result<(), StorageError> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()?;
+        Ok(())
+    }
+
+    /// Will read every block of the log, oldest first. A last line without its line break
+    /// is left by a crash while the block was written, it is skipped unless it holds the
+    /// whole block.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Block>, StorageError> {
+        let content = fs::read(path)?;
+        let (complete, torn) = split_torn_line(&content);
+
+        let mut blocks = Vec::new();
+        for (index, line) in complete.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let block = serde_json::from_str(&line).map_err(|source| StorageError::Corrupt {
+                line: index + 1,
+                source,
+            })?;
+            blocks.push(block);
+        }
+        if let Some(Ok(block)) = torn.map(serde_json::from_slice) {
+            blocks.push(block);
+        }
+
+        Ok(blocks)
+    }
+
+    /// Will create or truncate the file and write the blocks to it
+    pub fn write<P: AsRef<Path>>(path: P, blocks: &[Block]) -> Result<(), StorageError> {
+        File::create(&path)?;
+
+        let mut log = Self::open(path)?;
+        for block in blocks {
+            log.append(block)?;
+        }
+        log.flush()
+    }
+}
+
+/// Will split the content after its last line break, the rest is returned if there is any
+fn split_torn_line(content: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let end = content
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |position| position + 1);
+
+    match content.split_at(end) {
+        (complete, []) => (complete, None),
+        (complete, torn) => (complete, Some(torn)),
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::block::BlockHeader;
+
+/// The median timestamp of this many previous blocks is the earliest a new block may have
+pub const MEDIAN_TIME_BLOCKS: usize = 11;
+
+/// How far a block timestamp may be ahead of the local clock, in milliseconds
+pub const MAX_FUTURE_DRIFT_MS: u64 = 15_000;
+
+/// Will return the current Unix time in milliseconds
+pub fn now_millis() -> u64 {
+    unix_millis(SystemTime::now())
+}
+
+/// Will return the time as Unix milliseconds, times before the epoch count as 0
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Will return the median timestamp of the last [`MEDIAN_TIME_BLOCKS`] blocks
+pub fn median_time_past(headers: &[BlockHeader]) -> Option<u64> {
+    let start = headers.len().saturating_sub(MEDIAN_TIME_BLOCKS);
+    let mut timestamps: Vec<u64> = headers[start..].iter().map(|h| h.timestamp).collect();
+    timestamps.sort_unstable();
+
+    timestamps.get(timestamps.len() / 2).copied()
+}
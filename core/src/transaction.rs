@@ -1,24 +1,58 @@
 use std::time::SystemTime;
 
-use eternal_account::AccountType;
-use eternal_vm::smart_contract::{self, SmartContract, SmartContractStanderd};
+use eternal_account::{
+    secp256k1::PublicKey, verify_signature, Account, AccountType, Address, Unbonding, Wallet,
+};
+use eternal_vm::smart_contract::{self, ContractStorage, SmartContract, SmartContractStanderd};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use eternal_vm::WorldState;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::{
+    block::Block,
+    domain::{tagged_hasher, update_field, update_optional, TRANSACTION_TAG},
+    error::TxError,
+    pos::UNBONDING_BLOCKS,
+    slashing::{self, Evidence},
+    time::unix_millis,
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Transaction {
+    /// The chain the transaction is meant for, signed to prevent replays on other chains
+    pub chain_id: u64,
     pub nonce: u128,
     pub from: String,
     pub created_at: SystemTime,
+    /// Unix timestamp in milliseconds after which the transaction can not be included anymore
+    pub valid_until: Option<u64>,
     pub data: TransactionData,
-    pub signature: Option<String>,
+    pub signatures: Vec<TransactionSignature>,
+}
+
+/// A signature over [`Transaction::calculate_hash`] together with the key that created it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TransactionSignature {
+    pub public_key: String,
+    pub signature: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum TransactionData {
-    CreateUserAccount,
+    /// The only transaction of the genesis block, committing to the chain spec
+    Genesis {
+        chain_id: u64,
+        spec_hash: String,
+    },
+    /// Registers the account of a key, its address is derived from the public key
+    CreateUserAccount {
+        public_key: String,
+    },
+    CreateMultisigAccount {
+        public_keys: Vec<String>,
+        threshold: u8,
+    },
     ChangeStoreValue {
         key: String,
         value: String,
@@ -36,6 +70,21 @@ pub enum TransactionData {
         receiver: String,
         amount: u128,
     },
+    /// Moves tokens into stake, turning the sender into a validator
+    Stake {
+        amount: u128,
+    },
+    /// Moves staked tokens back into the spendable balance after [`UNBONDING_BLOCKS`]
+    Unstake {
+        amount: u128,
+    },
+    /// Reports a misbehaving validator, which gets slashed and jailed
+    SubmitEvidence(Evidence),
+    /// An authority's vote to add or remove an authority (proof of authority only)
+    VoteAuthority {
+        public_key: String,
+        add: bool,
+    },
     DeploySmartContract {
         publisher: String,
         #[serde(skip_serializing, skip_deserializing)]
@@ -44,169 +93,455 @@ pub enum TransactionData {
 }
 
 impl Transaction {
-    pub fn new(from: String, transaction_data: TransactionData, nonce: u128) -> Self {
+    pub fn new(
+        chain_id: u64,
+        from: String,
+        transaction_data: TransactionData,
+        nonce: u128,
+    ) -> Self {
         Self {
+            chain_id,
             from,
             nonce,
             data: transaction_data,
             created_at: SystemTime::now(),
-            signature: None,
+            valid_until: None,
+            signatures: Vec::new(),
         }
     }
 
-    pub fn execute<T: WorldState>(
-        &self,
-        world_state: &mut T,
-        is_initial: &bool,
-    ) -> Result<String, &'static str> {
-        if let Some(_account) = world_state.get_account_by_id(&self.from) {
-            // Do some more checkups later on...
-        } else {
-            if !is_initial {
-                return Err("Account does not exist (Code: 93482390)");
+    /// Will limit the transaction to blocks up to the given Unix timestamp in milliseconds
+    pub fn with_valid_until(mut self, valid_until: u64) -> Self {
+        self.valid_until = Some(valid_until);
+        self
+    }
+
+    /// True if the transaction can not be included in a block with the given timestamp
+    pub fn is_expired(&self, timestamp: u64) -> bool {
+        self.valid_until
+            .map(|valid_until| timestamp > valid_until)
+            .unwrap_or(false)
+    }
+
+    /// Will add a signature of the wallet. Multisig transactions are signed once per owner
+    pub fn sign(&mut self, wallet: &Wallet) {
+        let signature = wallet.sign(&self.calculate_hash());
+        self.signatures.push(TransactionSignature {
+            public_key: wallet.public_key().to_string(),
+            signature,
+        });
+    }
+
+    /// Will check that the transaction is signed by the owner(s) of the sending account.
+    ///
+    /// Multisig accounts need valid signatures of at least `threshold` distinct owners,
+    /// every other account the signature of its key. Accounts without a key, e.g. the ones
+    /// of contracts, can not send transactions.
+    pub fn check_authorization(&self, account: &Account) -> Result<(), TxError> {
+        if !self.signatures.is_empty() && !self.check_signature() {
+            return Err(TxError::InvalidSignature);
+        }
+
+        match &account.acc_type {
+            AccountType::Multisig {
+                public_keys,
+                threshold,
+            } => {
+                let mut signers: Vec<&String> = self
+                    .signatures
+                    .iter()
+                    .map(|s| &s.public_key)
+                    .filter(|key| public_keys.contains(key))
+                    .collect();
+                signers.sort();
+                signers.dedup();
+
+                if signers.len() < *threshold as usize {
+                    return Err(TxError::InsufficientSignatures {
+                        required: *threshold,
+                        valid: signers.len(),
+                    });
+                }
+            }
+            _ => {
+                if !self.is_signed() {
+                    return Err(TxError::Unsigned);
+                }
+
+                let owner = account.address();
+                let signed_by_owner = self.signatures.iter().any(|s| {
+                    s.public_key
+                        .parse::<PublicKey>()
+                        .map(|key| Address::from_public_key_bytes(&key.serialize()) == owner)
+                        .unwrap_or(false)
+                });
+                if !signed_by_owner {
+                    return Err(TxError::InvalidSignature);
+                }
             }
         }
 
+        Ok(())
+    }
+
+    pub fn execute<T: WorldState>(&self, world_state: &mut T) -> Result<String, TxError> {
+        let account = world_state
+            .get_account_by_id(&self.from)
+            .ok_or_else(|| TxError::SenderNotFound(self.from.clone()))?;
+        self.check_authorization(account)?;
+
         return match &self.data {
-            TransactionData::CreateUserAccount => {
-                world_state.create_account(AccountType::User).unwrap();
-                Ok("Created Account".to_string())
+            // The genesis block is built from the chain spec and never executed
+            TransactionData::Genesis { .. } => Err(TxError::GenesisOnly),
+
+            TransactionData::CreateUserAccount { public_key } => {
+                let public_key = public_key
+                    .parse::<PublicKey>()
+                    .map_err(|_| TxError::InvalidPublicKey(public_key.clone()))?;
+                let account = Account::from_public_key(&public_key, AccountType::User);
+
+                Ok(world_state.create_account(account)?)
             }
 
-            TransactionData::MintTokens { receiver, amount } => {
-                if !is_initial {
-                    return Err(
-                        "Token creation is only available on initial creation (Code: 2394233)",
-                    );
-                }
+            TransactionData::CreateMultisigAccount {
+                public_keys,
+                threshold,
+            } => {
+                let account = Account::multisig(public_keys.clone(), *threshold)
+                    .map_err(TxError::InvalidMultisig)?;
 
-                return if let Some(account) = world_state.get_account_by_id_mut(receiver) {
-                    account.tokens += *amount;
-                    Ok("Minted".to_string())
-                } else {
-                    Err("Receiver Account does not exist (Code: 23482309)")
-                };
+                Ok(world_state.create_account(account)?)
             }
 
+            // Tokens are only created by the allocations of the chain spec
+            TransactionData::MintTokens { .. } => Err(TxError::MintNotAllowed),
+
             TransactionData::Transfer { to, amount } => {
                 let recv_tokens: u128;
                 let sender_tokens: u128;
 
-                if let Some(recv) = world_state.get_account_by_id_mut(to) {
+                if let Some(recv) = world_state.get_account_by_id(to) {
                     // Be extra careful here, even in the genesis block the sender account has to exist
                     recv_tokens = recv.tokens;
                 } else {
-                    return Err("Receiver Account does not exist! (Code: 3242342380)");
+                    return Err(TxError::ReceiverNotFound(to.clone()));
                 }
 
-                if let Some(sender) = world_state.get_account_by_id_mut(&self.from) {
+                if let Some(sender) = world_state.get_account_by_id(&self.from) {
                     sender_tokens = sender.tokens;
                 } else {
-                    return Err("That account does not exist! (Code: 23423923)");
+                    return Err(TxError::SenderNotFound(self.from.clone()));
                 }
 
-                let balance_recv_new = recv_tokens.checked_add(*amount);
-                let balance_sender_new = sender_tokens.checked_sub(*amount);
+                let (Some(balance_sender_new), Some(balance_recv_new)) = (
+                    sender_tokens.checked_sub(*amount),
+                    recv_tokens.checked_add(*amount),
+                ) else {
+                    return Err(TxError::Overspent);
+                };
 
-                if balance_recv_new.is_some() && balance_sender_new.is_some() {
-                    world_state
-                        .get_account_by_id_mut(&self.from)
-                        .unwrap()
-                        .tokens = balance_sender_new.unwrap();
-                    world_state.get_account_by_id_mut(&to).unwrap().tokens =
-                        balance_recv_new.unwrap();
+                // Both balances were read before either is written, a transfer to
+                // oneself would otherwise credit the amount on top of the old balance
+                if *to == self.from {
                     return Ok("Trasnferd".to_string());
-                } else {
-                    return Err("Overspent or Arithmetic error (Code: 48239084203)");
                 }
+
+                // Both accounts were looked up above, so the lookups can not fail here
+                if let Some(sender) = world_state.get_account_by_id_mut(&self.from) {
+                    sender.tokens = balance_sender_new;
+                }
+                if let Some(recv) = world_state.get_account_by_id_mut(to) {
+                    recv.tokens = balance_recv_new;
+                }
+
+                Ok("Trasnferd".to_string())
             }
 
-            TransactionData::DeploySmartContract { publisher, sc } => {
-                match sc.clone().unwrap().api {
-                    smart_contract::SmartContractApi::ESC20 { mut publisher, .. } => {
-                        publisher = publisher;
-                        publisher
+            TransactionData::Stake { amount } => {
+                let account = world_state
+                    .get_account_by_id_mut(&self.from)
+                    .ok_or_else(|| TxError::SenderNotFound(self.from.clone()))?;
+
+                let tokens = account
+                    .tokens
+                    .checked_sub(*amount)
+                    .ok_or(TxError::Overspent)?;
+                match &mut account.acc_type {
+                    AccountType::User => {
+                        account.acc_type = AccountType::Validator {
+                            stake: *amount,
+                            correctly_validated_blocks: 0,
+                            incorrectly_validated_blocks: 0,
+                            jailed: false,
+                            unbonding: Vec::new(),
+                        };
+                    }
+                    AccountType::Validator { stake, .. } => {
+                        *stake = stake.checked_add(*amount).ok_or(TxError::Overspent)?;
                     }
+                    _ => return Err(TxError::NotStakeable(self.from.clone())),
+                }
+                account.tokens = tokens;
+
+                Ok("Staked".to_string())
+            }
+
+            TransactionData::Unstake { amount } => {
+                let height = world_state.block_height();
+                let account = world_state
+                    .get_account_by_id_mut(&self.from)
+                    .ok_or_else(|| TxError::SenderNotFound(self.from.clone()))?;
+
+                let AccountType::Validator {
+                    stake,
+                    jailed,
+                    unbonding,
+                    ..
+                } = &mut account.acc_type
+                else {
+                    return Err(TxError::InsufficientStake(self.from.clone()));
                 };
-                let contract_addr = &world_state
-                    .create_smart_contact(sc.clone().unwrap())
-                    .unwrap();
-                for (_, account) in world_state.get_accounts() {
-                    account
-                        .store
-                        .insert(contract_addr.clone(), (0 as u128).to_string());
+                if *jailed {
+                    return Err(TxError::Jailed(self.from.clone()));
                 }
+                *stake = stake
+                    .checked_sub(*amount)
+                    .ok_or_else(|| TxError::InsufficientStake(self.from.clone()))?;
+
+                // Released by the chain once the unbonding period is over
+                let release_height = height + UNBONDING_BLOCKS;
+                unbonding.push(Unbonding {
+                    amount: *amount,
+                    release_height,
+                });
+
+                Ok(format!("Unbonding until block {}", release_height))
+            }
+
+            TransactionData::SubmitEvidence(evidence) => {
+                let offender = evidence.verify(world_state)?;
+                let penalty = slashing::slash(world_state, &offender, evidence.slash_percent())?;
+
+                Ok(format!("Slashed {} by {}", offender, penalty))
+            }
+
+            // The votes are counted by the consensus engine once the block is accepted
+            TransactionData::VoteAuthority { public_key, .. } => {
+                if public_key.parse::<PublicKey>().is_err() {
+                    return Err(TxError::InvalidPublicKey(public_key.clone()));
+                }
+
+                Ok("Authority vote cast".to_string())
+            }
+
+            TransactionData::DeploySmartContract { publisher, sc } => {
+                let sc = sc.as_ref().ok_or(TxError::MissingContract)?;
+
+                if world_state.get_account_by_id(publisher).is_none() {
+                    return Err(TxError::AccountNotFound(publisher.clone()));
+                }
+
+                let contract_addr = world_state.create_smart_contact(sc.clone())?;
 
-                let total_supply = match sc.clone().unwrap().api {
-                    smart_contract::SmartContractApi::ESC20 { total_suply, .. } => total_suply,
+                // Only the publisher holds tokens, nobody else is touched
+                let total_supply = match &sc.api {
+                    smart_contract::SmartContractApi::ESC20 { total_suply, .. } => *total_suply,
                 };
-                let account = world_state.get_account_by_id_mut(publisher).unwrap();
-                account
-                    .store
-                    .insert(contract_addr.clone(), total_supply.to_string());
+                world_state
+                    .get_token_ledger()
+                    .credit(&contract_addr, publisher, total_supply)?;
 
-                Ok(contract_addr.clone())
+                Ok(contract_addr)
             }
 
             TransactionData::TransferToken { token, to, amount } => {
+                if world_state.get_account_by_id(to).is_none() {
+                    return Err(TxError::AccountNotFound(to.clone()));
+                }
+                // The contract writes to a copy of the sender's store, kept if the call succeeds
+                let mut store = world_state
+                    .get_account_by_id(&self.from)
+                    .ok_or_else(|| TxError::SenderNotFound(self.from.clone()))?
+                    .store
+                    .clone();
                 let smart_contract = world_state.get_smart_contacts().get_mut(token);
 
                 match smart_contract {
                     Some(sc) => {
                         if sc.r#type == SmartContractStanderd::ESC20 {
-                            sc.execute_fn("transfer", vec![&self.from, to, &amount.to_string()]);
+                            let mut storage = ContractStorage::new(token, &mut store);
+                            sc.execute_fn(
+                                &mut storage,
+                                "transfer",
+                                vec![&self.from, to, &amount.to_string()],
+                            )?;
+                            world_state
+                                .get_token_ledger()
+                                .transfer(token, &self.from, to, *amount)?;
                         } else if sc.r#type == SmartContractStanderd::ESC721 {
                         } else {
-                            return Err("Not a transferable assest");
+                            return Err(TxError::NotTransferable(token.clone()));
                         }
                     }
-                    None => return Err("Token does not exist"),
+                    None => return Err(TxError::TokenNotFound(token.clone())),
+                }
+
+                if let Some(sender) = world_state.get_account_by_id_mut(&self.from) {
+                    sender.store = store;
                 }
 
                 Ok("Token transfer success".to_string())
             }
 
             TransactionData::ChangeStoreValue { key, value } => {
-                let acc = world_state.get_account_by_id_mut(&self.from).unwrap();
-
-                if key.starts_with("etnl:") {
-                    return Err("Can not change store related to a smart contract");
-                }
-
-                acc.store.insert(key.clone(), value.clone());
+                let acc = world_state
+                    .get_account_by_id_mut(&self.from)
+                    .ok_or_else(|| TxError::SenderNotFound(self.from.clone()))?;
+
+                // Accounts write to their own namespace, contracts keep theirs apart
+                acc.store.set_bytes(
+                    &self.from,
+                    &self.from,
+                    key.as_bytes(),
+                    value.as_bytes().to_vec(),
+                )?;
 
                 Ok("Store updated".to_string())
             }
-            // _ => Err("Unknown Transaction type (not implemented) (Code: 487289724389)"),
         };
     }
 
-    /// Will calculate the hash using Blake2 hasher
+    /// Will calculate the hash which is signed. It covers every field but the signatures,
+    /// the creation time as Unix milliseconds
     pub fn calculate_hash(&self) -> Vec<u8> {
-        let mut hasher = Sha256::new();
-
-        let transaction_as_string = format!(
-            "{:?}",
-            (&self.created_at, &self.data, &self.from, &self.nonce)
-        );
-
-        hasher.update(&transaction_as_string);
+        let mut hasher = tagged_hasher(TRANSACTION_TAG);
+        hasher.update(self.chain_id.to_be_bytes());
+        hasher.update(unix_millis(self.created_at).to_be_bytes());
+        match self.valid_until {
+            Some(valid_until) => {
+                hasher.update([1]);
+                hasher.update(valid_until.to_be_bytes());
+            }
+            None => hasher.update([0]),
+        }
+        self.data.hash_into(&mut hasher);
+        update_field(&mut hasher, self.from.as_bytes());
+        hasher.update(self.nonce.to_be_bytes());
 
-        let hash: &[u8] = &*hasher.finalize();
+        hasher.finalize().to_vec()
+    }
 
-        return Vec::from(hash);
+    /// Will return the hash as uppercase hex, the same format as block hashes
+    pub fn hash(&self) -> String {
+        self.calculate_hash()
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect()
     }
 
+    /// Will check that every attached signature is valid for the transaction
     pub fn check_signature(&self) -> bool {
         if !(self.is_signed()) {
             return false;
         }
 
-        // check signature
-        false
+        let hash = self.calculate_hash();
+        self.signatures
+            .iter()
+            .all(|s| match s.public_key.parse::<PublicKey>() {
+                Ok(public_key) => verify_signature(&public_key, &hash, &s.signature),
+                Err(_) => false,
+            })
     }
 
     pub fn is_signed(&self) -> bool {
-        self.signature.is_some()
+        !self.signatures.is_empty()
     }
 }
+
+impl TransactionData {
+    /// Will feed the variant and its length prefixed fields into the hasher
+    fn hash_into(&self, hasher: &mut Sha256) {
+        match self {
+            TransactionData::Genesis {
+                chain_id,
+                spec_hash,
+            } => {
+                hasher.update([0]);
+                hasher.update(chain_id.to_be_bytes());
+                update_field(hasher, spec_hash.as_bytes());
+            }
+            TransactionData::CreateUserAccount { public_key } => {
+                hasher.update([1]);
+                update_field(hasher, public_key.as_bytes());
+            }
+            TransactionData::CreateMultisigAccount {
+                public_keys,
+                threshold,
+            } => {
+                hasher.update([2]);
+                hasher.update((public_keys.len() as u64).to_be_bytes());
+                for public_key in public_keys {
+                    update_field(hasher, public_key.as_bytes());
+                }
+                hasher.update([*threshold]);
+            }
+            TransactionData::ChangeStoreValue { key, value } => {
+                hasher.update([3]);
+                update_field(hasher, key.as_bytes());
+                update_field(hasher, value.as_bytes());
+            }
+            TransactionData::TransferToken { token, to, amount } => {
+                hasher.update([4]);
+                update_field(hasher, token.as_bytes());
+                update_field(hasher, to.as_bytes());
+                hasher.update(amount.to_be_bytes());
+            }
+            TransactionData::Transfer { to, amount } => {
+                hasher.update([5]);
+                update_field(hasher, to.as_bytes());
+                hasher.update(amount.to_be_bytes());
+            }
+            TransactionData::MintTokens { receiver, amount } => {
+                hasher.update([6]);
+                update_field(hasher, receiver.as_bytes());
+                hasher.update(amount.to_be_bytes());
+            }
+            TransactionData::Stake { amount } => {
+                hasher.update([7]);
+                hasher.update(amount.to_be_bytes());
+            }
+            TransactionData::Unstake { amount } => {
+                hasher.update([8]);
+                hasher.update(amount.to_be_bytes());
+            }
+            TransactionData::SubmitEvidence(Evidence::DoubleSign { first, second }) => {
+                hasher.update([9, 0]);
+                hash_block_into(hasher, first);
+                hash_block_into(hasher, second);
+            }
+            TransactionData::SubmitEvidence(Evidence::InvalidBlock { block }) => {
+                hasher.update([9, 1]);
+                hash_block_into(hasher, block);
+            }
+            TransactionData::VoteAuthority { public_key, add } => {
+                hasher.update([10]);
+                update_field(hasher, public_key.as_bytes());
+                hasher.update([*add as u8]);
+            }
+            // The code is not serialized either, contracts are only deployed locally
+            TransactionData::DeploySmartContract { publisher, .. } => {
+                hasher.update([11]);
+                update_field(hasher, publisher.as_bytes());
+            }
+        }
+    }
+}
+
+/// Will feed a block of evidence into the hasher. Its header hash covers the transactions
+/// with their signatures, the stored hash and the signature are added as they are.
+fn hash_block_into(hasher: &mut Sha256, block: &Block) {
+    update_field(hasher, block.calculate_hash().as_bytes());
+    update_optional(hasher, block.hash.as_deref().map(str::as_bytes));
+    update_optional(hasher, block.signature.as_deref().map(str::as_bytes));
+}
@@ -0,0 +1,96 @@
+use eternal_core::{
+    account::{AccountType, Wallet},
+    blockchain::Blockchain,
+    devnet::Devnet,
+    error::{BlockError, TxError},
+    spec::{dev_wallets, ChainSpec},
+    transaction::TransactionData,
+};
+use eternal_vm::VmError;
+
+/// A dev chain sealed by the first dev wallet
+fn setup() -> (Devnet, Vec<Wallet>) {
+    let wallets = dev_wallets(3);
+    let chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+
+    (Devnet::new(chain, vec![wallets[0].clone()]), wallets)
+}
+
+fn create(public_key: String) -> TransactionData {
+    TransactionData::CreateUserAccount { public_key }
+}
+
+#[test]
+fn user_accounts_live_at_the_address_of_their_key() {
+    let (mut devnet, wallets) = setup();
+    let address = wallets[2].address().to_string();
+    assert!(!devnet.chain.accounts.contains_key(&address));
+
+    devnet
+        .send(&wallets[1], create(wallets[2].public_key().to_string()))
+        .unwrap();
+    devnet.step().unwrap().unwrap();
+    let account = &devnet.chain.accounts[&address];
+    assert!(matches!(account.acc_type, AccountType::User));
+    assert_eq!(account.public_key, wallets[2].public_key().to_string());
+    assert_eq!(account.tokens, 0);
+
+    // The key owner can spend what the account receives
+    let transfer = TransactionData::Transfer {
+        to: address.clone(),
+        amount: 10,
+    };
+    devnet.send(&wallets[0], transfer).unwrap();
+    devnet.step().unwrap().unwrap();
+    let back = TransactionData::Transfer {
+        to: wallets[0].address().to_string(),
+        amount: 4,
+    };
+    devnet.send(&wallets[2], back).unwrap();
+    devnet.step().unwrap().unwrap();
+    assert_eq!(devnet.chain.accounts[&address].tokens, 6);
+}
+
+#[test]
+fn existing_accounts_and_invalid_keys_are_rejected() {
+    let (mut devnet, wallets) = setup();
+    let taken = wallets[1].address().to_string();
+
+    devnet
+        .send(&wallets[0], create(wallets[1].public_key().to_string()))
+        .unwrap();
+    assert!(matches!(
+        devnet.step(),
+        Err(BlockError::Transaction {
+            source: TxError::Vm(VmError::AccountExists(address)),
+            ..
+        }) if address == taken
+    ));
+
+    let (mut devnet, wallets) = setup();
+    devnet
+        .send(&wallets[0], create("not a key".to_string()))
+        .unwrap();
+    assert!(matches!(
+        devnet.step(),
+        Err(BlockError::Transaction {
+            source: TxError::InvalidPublicKey(key),
+            ..
+        }) if key == "not a key"
+    ));
+}
+
+#[test]
+fn transfers_to_oneself_keep_the_balance() {
+    let (mut devnet, wallets) = setup();
+    let address = wallets[0].address().to_string();
+    let before = devnet.chain.accounts[&address].tokens;
+
+    let transfer = TransactionData::Transfer {
+        to: address.clone(),
+        amount: 10,
+    };
+    devnet.send(&wallets[0], transfer).unwrap();
+    devnet.step().unwrap().unwrap();
+    assert_eq!(devnet.chain.accounts[&address].tokens, before);
+}
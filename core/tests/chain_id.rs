@@ -0,0 +1,90 @@
+use eternal_core::{
+    block::Block,
+    blockchain::Blockchain,
+    error::{BlockError, TxError},
+    spec::{dev_wallets, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// Two dev chains with the same accounts, only their chain ids differ
+fn setup() -> (Blockchain, Blockchain) {
+    let spec = ChainSpec::dev();
+    let mut other = ChainSpec::dev();
+    other.chain_id = spec.chain_id + 1;
+
+    (
+        Blockchain::from_spec(&spec).unwrap(),
+        Blockchain::from_spec(&other).unwrap(),
+    )
+}
+
+/// Will return the first transfer of the dev authority signed for the chain id
+fn transfer(chain_id: u64) -> Transaction {
+    let wallets = dev_wallets(2);
+    let data = TransactionData::Transfer {
+        to: wallets[1].address().to_string(),
+        amount: 10,
+    };
+    let mut transaction = Transaction::new(chain_id, wallets[0].address().to_string(), data, 1);
+    transaction.sign(&wallets[0]);
+    transaction
+}
+
+/// Will build the next block of the transaction without executing it, sealed by the
+/// dev authority
+fn block(chain: &Blockchain, transaction: Transaction) -> Block {
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    block.sign(&dev_wallets(1)[0]);
+    block
+}
+
+#[test]
+fn transactions_of_other_chains_are_rejected() {
+    let (mut chain, mut other) = setup();
+    let transaction = transfer(chain.chain_id);
+
+    let expected = (other.chain_id, chain.chain_id);
+    assert!(matches!(
+        other.submit_transaction(transaction.clone()),
+        Err(TxError::WrongChain { expected: e, actual: a }) if (e, a) == expected
+    ));
+    assert!(matches!(
+        other.append_block(block(&other, transaction.clone())),
+        Err(BlockError::Transaction {
+            index: 1,
+            source: TxError::WrongChain { .. },
+        })
+    ));
+
+    chain.submit_transaction(transaction).unwrap();
+    let transactions = chain.take_pending_transactions();
+    chain
+        .produce_block(transactions, &dev_wallets(1)[0])
+        .unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(other.len(), 1);
+}
+
+#[test]
+fn the_chain_id_is_signed() {
+    let (_, mut other) = setup();
+
+    // The same transfer has a different hash on every chain
+    let transaction = transfer(other.chain_id - 1);
+    assert_ne!(
+        transaction.calculate_hash(),
+        transfer(other.chain_id).calculate_hash()
+    );
+
+    // Relabeling breaks the signature
+    let mut relabeled = transaction;
+    relabeled.chain_id = other.chain_id;
+    assert!(matches!(
+        other.append_block(block(&other, relabeled)),
+        Err(BlockError::Transaction {
+            source: TxError::InvalidSignature,
+            ..
+        })
+    ));
+}
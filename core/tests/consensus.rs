@@ -0,0 +1,211 @@
+use eternal_core::{
+    account::Wallet,
+    block::Block,
+    blockchain::{Blockchain, Consensus},
+    consensus::{ConsensusEngine, DevAuthority, ProofOfAuthority, ProofOfStake, ProofOfWork},
+    error::BlockError,
+    limits::Limits,
+    spec::{Allocation, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+const MIN_STAKE: u128 = 100;
+
+/// A chain with the given consensus where the first two wallets may seal blocks (just
+/// the first one for the dev authority, no one needs to for proof of work), the third
+/// one is funded but may not
+fn setup(consensus: impl Fn(&[Wallet]) -> Consensus) -> (Blockchain, Vec<Wallet>) {
+    let wallets: Vec<Wallet> = (0..3).map(|_| Wallet::generate()).collect();
+    let spec = ChainSpec {
+        name: "consensus".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: consensus(&wallets),
+        limits: Limits::default(),
+        allocations: wallets
+            .iter()
+            .enumerate()
+            .map(|(i, wallet)| Allocation {
+                public_key: wallet.public_key().to_string(),
+                tokens: 100,
+                stake: if i < 2 { MIN_STAKE } else { 0 },
+            })
+            .collect(),
+        contracts: Vec::new(),
+        genesis_hash: None,
+    };
+
+    (Blockchain::from_spec(&spec).unwrap(), wallets)
+}
+
+fn proof_of_work(_: &[Wallet]) -> Consensus {
+    Consensus::ProofOfWork { difficulty: 1 }
+}
+
+fn proof_of_stake(_: &[Wallet]) -> Consensus {
+    Consensus::ProofOfStake {
+        min_stake: MIN_STAKE,
+    }
+}
+
+fn proof_of_authority(wallets: &[Wallet]) -> Consensus {
+    Consensus::ProofOfAuthority {
+        authorities: wallets[..2]
+            .iter()
+            .map(|wallet| wallet.public_key().to_string())
+            .collect(),
+        period_ms: 0,
+    }
+}
+
+fn dev(wallets: &[Wallet]) -> Consensus {
+    Consensus::Dev {
+        authority: wallets[0].address().to_string(),
+    }
+}
+
+/// A block on top of the head with a transaction of the third wallet and the state
+/// committed, but not sealed yet
+fn unsealed(chain: &Blockchain, wallets: &[Wallet]) -> Block {
+    let from = wallets[2].address().to_string();
+    let nonce = chain.next_nonce(&from);
+    let data = TransactionData::ChangeStoreValue {
+        key: "key".to_string(),
+        value: chain.len().to_string(),
+    };
+    let mut transaction = Transaction::new(chain.chain_id, from, data, nonce);
+    transaction.sign(&wallets[2]);
+
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    chain.commit_state(&mut block).unwrap();
+    block
+}
+
+/// Will append `count` blocks, each sealed by the first wallet allowed to
+fn extend(chain: &mut Blockchain, wallets: &[Wallet], count: usize) {
+    for _ in 0..count {
+        let block = unsealed(chain, wallets);
+        let engine = chain.engine();
+        let sealed = wallets[..2].iter().find_map(|wallet| {
+            let mut block = block.clone();
+            engine.seal(chain, &mut block, wallet).ok().map(|()| block)
+        });
+        chain.append_block(sealed.unwrap()).unwrap();
+    }
+}
+
+/// Checks that the engine neither seals nor accepts a block of the third wallet, and
+/// no block named after an allowed proposer but signed by the third wallet
+fn rejects_wrong_signer(engine: &dyn ConsensusEngine, chain: &Blockchain, wallets: &[Wallet]) {
+    let outsider = &wallets[2];
+
+    let mut block = unsealed(chain, wallets);
+    assert!(matches!(
+        engine.seal(chain, &mut block, outsider),
+        Err(BlockError::UnexpectedProposer { .. })
+    ));
+    block.sign(outsider);
+    assert!(matches!(
+        engine.verify_header(chain, &block),
+        Err(BlockError::UnexpectedProposer { .. })
+    ));
+
+    let mut block = unsealed(chain, wallets);
+    let sealed_by = wallets[..2]
+        .iter()
+        .find(|wallet| engine.seal(chain, &mut block, wallet).is_ok())
+        .unwrap();
+    engine.verify_header(chain, &block).unwrap();
+    block.signature = Some(outsider.sign(block.hash.clone().unwrap().as_bytes()));
+    assert_eq!(
+        block.proposer.as_deref(),
+        Some(sealed_by.address().to_string().as_str())
+    );
+    assert!(matches!(
+        engine.verify_header(chain, &block),
+        Err(BlockError::InvalidProposerSignature)
+    ));
+}
+
+#[test]
+fn proof_of_stake_rejects_wrong_signers() {
+    let (mut chain, wallets) = setup(proof_of_stake);
+    extend(&mut chain, &wallets, 2);
+    rejects_wrong_signer(&ProofOfStake::new(MIN_STAKE), &chain, &wallets);
+}
+
+#[test]
+fn proof_of_authority_rejects_wrong_signers() {
+    let (mut chain, wallets) = setup(proof_of_authority);
+    extend(&mut chain, &wallets, 2);
+    rejects_wrong_signer(&ProofOfAuthority::new(0), &chain, &wallets);
+}
+
+#[test]
+fn dev_authority_rejects_wrong_signers() {
+    let (mut chain, wallets) = setup(dev);
+    extend(&mut chain, &wallets, 2);
+    let engine = DevAuthority::new(wallets[0].address().to_string());
+    rejects_wrong_signer(&engine, &chain, &wallets);
+
+    // Not even the other funded wallet may seal
+    let mut block = unsealed(&chain, &wallets);
+    assert!(matches!(
+        engine.seal(&chain, &mut block, &wallets[1]),
+        Err(BlockError::UnexpectedProposer { expected, .. }) if expected == engine.authority
+    ));
+}
+
+#[test]
+fn proof_of_work_rejects_blocks_without_work() {
+    let (mut chain, wallets) = setup(proof_of_work);
+    extend(&mut chain, &wallets, 2);
+    let engine = ProofOfWork::new(4);
+
+    // A signature is no replacement for work
+    let mut block = unsealed(&chain, &wallets);
+    while engine.meets_difficulty(block.hash.as_deref().unwrap_or_default()) {
+        block.set_nonce(block.nonce + 1);
+    }
+    block.sign(&wallets[0]);
+    assert!(matches!(
+        engine.verify_header(&chain, &block),
+        Err(BlockError::InsufficientWork { difficulty: 4 })
+    ));
+
+    engine.seal(&chain, &mut block, &wallets[2]).unwrap();
+    engine.verify_header(&chain, &block).unwrap();
+}
+
+#[test]
+fn the_longest_chain_wins() {
+    for consensus in [proof_of_work, proof_of_stake, proof_of_authority] {
+        let (mut chain, wallets) = setup(consensus);
+        extend(&mut chain, &wallets, 2);
+        assert_eq!(chain.len(), 3);
+        let engine = chain.engine();
+        let fork = |len: usize| vec![Block::new(None); len];
+
+        // Replacing the last two blocks with three
+        assert!(engine.fork_choice(&chain, 1, &fork(3)));
+        assert!(engine.fork_choice(&chain, 3, &fork(1)));
+        // The current chain is kept on a tie
+        assert!(!engine.fork_choice(&chain, 1, &fork(2)));
+        assert!(!engine.fork_choice(&chain, 3, &fork(0)));
+        assert!(!engine.fork_choice(&chain, 1, &fork(1)));
+    }
+}
+
+#[test]
+fn the_dev_authority_never_switches_forks() {
+    let (mut chain, wallets) = setup(dev);
+    extend(&mut chain, &wallets, 2);
+    let engine = chain.engine();
+
+    // There is a single authority, a competing fork can only be an attack or a bug
+    for (fork_height, len) in [(1, 3), (3, 1), (1, 100), (1, 2), (3, 0)] {
+        let fork = vec![Block::new(None); len];
+        assert!(!engine.fork_choice(&chain, fork_height, &fork));
+    }
+}
@@ -0,0 +1,243 @@
+use eternal_core::{
+    account::Wallet,
+    block::Block,
+    blockchain::{Blockchain, Consensus},
+    error::{BlockError, VoteError},
+    finality::{Checkpoint, FinalityGadget, Vote, VoteKind},
+    limits::Limits,
+    spec::{Allocation, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// Stakes of the simulated validators, the first three make up more than 2/3
+const STAKES: [u128; 4] = [40, 30, 20, 10];
+
+/// A proof of stake chain with the validators staked in the genesis block
+fn setup() -> (Blockchain, Vec<Wallet>) {
+    let wallets: Vec<Wallet> = STAKES.iter().map(|_| Wallet::generate()).collect();
+    let spec = ChainSpec {
+        name: "finality".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: Consensus::ProofOfStake { min_stake: 1 },
+        limits: Limits::default(),
+        allocations: wallets
+            .iter()
+            .zip(STAKES)
+            .map(|(wallet, stake)| Allocation {
+                public_key: wallet.public_key().to_string(),
+                tokens: 0,
+                stake,
+            })
+            .collect(),
+        contracts: Vec::new(),
+        genesis_hash: None,
+    };
+
+    (Blockchain::from_spec(&spec).unwrap(), wallets)
+}
+
+/// Will build the next block, signed by the selected proposer
+fn next_block(bc: &Blockchain, wallets: &[Wallet], value: &str) -> Block {
+    let proposer = bc.next_proposer().unwrap();
+    let wallet = wallets
+        .iter()
+        .find(|w| w.address().to_string() == proposer)
+        .unwrap();
+
+    let nonce = bc.next_nonce(&proposer);
+    let data = TransactionData::ChangeStoreValue {
+        key: "key".to_string(),
+        value: value.to_string(),
+    };
+    let mut transaction = Transaction::new(bc.chain_id, proposer, data, nonce);
+    transaction.sign(wallet);
+
+    let mut block = Block::new(bc.get_last_block_hash());
+    block.add_transaction(transaction);
+    bc.commit_state(&mut block).unwrap();
+    block.sign(wallet);
+    block
+}
+
+/// Simulates one round for the block at the given height: every online validator
+/// prevotes, then precommits once it saw more than 2/3 of the stake prevote
+fn run_round(
+    bc: &mut Blockchain,
+    wallets: &[Wallet],
+    online: &[bool],
+    height: usize,
+    round: u32,
+) -> Option<Checkpoint> {
+    let hash = bc.blocks[height].hash.clone().unwrap();
+    let voters: Vec<&Wallet> = wallets
+        .iter()
+        .zip(online)
+        .filter(|(_, online)| **online)
+        .map(|(wallet, _)| wallet)
+        .collect();
+
+    for wallet in &voters {
+        let vote = Vote::new(VoteKind::Prevote, height, round, hash.clone(), wallet);
+        assert_eq!(bc.add_vote(vote).unwrap(), None);
+    }
+
+    let validators = bc.validator_set();
+    if !bc.finality.has_polka(&validators, height, round, &hash) {
+        return None;
+    }
+
+    let mut checkpoint = None;
+    for wallet in &voters {
+        let vote = Vote::new(VoteKind::Precommit, height, round, hash.clone(), wallet);
+        if let Some(finalized) = bc.add_vote(vote).unwrap() {
+            checkpoint = Some(finalized);
+        }
+    }
+    checkpoint
+}
+
+#[test]
+fn block_is_finalized_with_supermajority() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+
+    let checkpoint = run_round(&mut bc, &wallets, &[true, true, true, false], 1, 0).unwrap();
+
+    assert_eq!(checkpoint.height, 1);
+    assert_eq!(Some(&checkpoint.hash), bc.blocks[1].hash.as_ref());
+    assert_eq!(bc.finalized, Some(checkpoint));
+}
+
+#[test]
+fn block_is_not_finalized_without_supermajority() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+
+    // Without the biggest validator only 60% of the stake is online
+    assert_eq!(
+        run_round(&mut bc, &wallets, &[false, true, true, true], 1, 0),
+        None
+    );
+    assert_eq!(bc.finalized, None);
+
+    // The validator coming back online in the next round finalizes the block
+    assert!(run_round(&mut bc, &wallets, &[true, true, true, true], 1, 1).is_some());
+}
+
+#[test]
+fn reorg_past_finalized_block_is_refused() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+    run_round(&mut bc, &wallets, &[true; 4], 1, 0).unwrap();
+    bc.append_block(next_block(&bc, &wallets, "b")).unwrap();
+
+    // Replacing the finalized block is refused
+    let err = bc.reorg(1, vec![]).unwrap_err();
+    assert!(matches!(
+        err,
+        BlockError::ReorgBelowFinalized {
+            fork_height: 1,
+            finalized: 1
+        }
+    ));
+    assert_eq!(bc.len(), 3);
+
+    // Blocks after the checkpoint can still be replaced
+    let mut fork = bc.clone();
+    fork.reorg(2, vec![]).unwrap();
+    let replacement = next_block(&fork, &wallets, "c");
+    bc.reorg(2, vec![replacement.clone()]).unwrap();
+
+    assert_eq!(bc.len(), 3);
+    assert_eq!(bc.blocks[2], replacement);
+    assert_eq!(bc.finalized.as_ref().unwrap().height, 1);
+}
+
+#[test]
+fn conflicting_votes_are_rejected() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+    let hash = bc.blocks[1].hash.clone().unwrap();
+    let validators = bc.validator_set();
+
+    let mut gadget = FinalityGadget::new();
+    let vote = Vote::new(VoteKind::Prevote, 1, 0, hash.clone(), &wallets[0]);
+    assert_eq!(gadget.add_vote(vote.clone(), &validators).unwrap(), 40);
+
+    // Repeating the same vote is harmless, voting for another block is not
+    assert_eq!(gadget.add_vote(vote, &validators).unwrap(), 40);
+    let conflicting = Vote::new(VoteKind::Prevote, 1, 0, "other".to_string(), &wallets[0]);
+    assert!(matches!(
+        gadget.add_vote(conflicting, &validators).unwrap_err(),
+        VoteError::Equivocation(_)
+    ));
+
+    // The chain only accepts votes for blocks it knows at that height
+    let genesis_hash = bc.blocks[0].hash.clone().unwrap();
+    let err = bc
+        .add_vote(Vote::new(
+            VoteKind::Prevote,
+            1,
+            0,
+            genesis_hash,
+            &wallets[0],
+        ))
+        .unwrap_err();
+    assert!(matches!(err, VoteError::UnknownBlock(_)));
+    assert_eq!(
+        bc.finality
+            .stake_for(&validators, 1, 0, VoteKind::Prevote, &hash),
+        0
+    );
+}
+
+#[test]
+fn forged_and_foreign_votes_are_rejected() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+    let hash = bc.blocks[1].hash.clone().unwrap();
+
+    let mut forged = Vote::new(VoteKind::Precommit, 1, 0, hash.clone(), &wallets[1]);
+    forged.validator = wallets[0].address().to_string();
+    assert!(matches!(
+        bc.add_vote(forged).unwrap_err(),
+        VoteError::InvalidSignature
+    ));
+
+    let stranger = Wallet::generate();
+    assert!(matches!(
+        bc.add_vote(Vote::new(VoteKind::Precommit, 1, 0, hash, &stranger))
+            .unwrap_err(),
+        VoteError::NotAValidator(_)
+    ));
+}
+
+#[test]
+fn precommits_need_a_polka() {
+    let (mut bc, wallets) = setup();
+    bc.append_block(next_block(&bc, &wallets, "a")).unwrap();
+    let hash = bc.blocks[1].hash.clone().unwrap();
+
+    // Without any prevotes nobody may precommit, even with all of the stake
+    for wallet in &wallets {
+        let vote = Vote::new(VoteKind::Precommit, 1, 0, hash.clone(), wallet);
+        let err = bc.add_vote(vote).unwrap_err();
+        assert!(matches!(
+            err,
+            VoteError::NoPolka {
+                height: 1,
+                round: 0
+            }
+        ));
+    }
+
+    // 60% of the stake prevoting is not enough either
+    assert!(run_round(&mut bc, &wallets, &[false, true, true, true], 1, 0).is_none());
+    let vote = Vote::new(VoteKind::Precommit, 1, 0, hash, &wallets[1]);
+    assert!(matches!(
+        bc.add_vote(vote).unwrap_err(),
+        VoteError::NoPolka { .. }
+    ));
+    assert_eq!(bc.finalized, None);
+}
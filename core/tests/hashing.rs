@@ -0,0 +1,74 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use eternal_core::{
+    account::Wallet,
+    block::Block,
+    finality::{Vote, VoteKind},
+    transaction::{Transaction, TransactionData},
+};
+
+fn store(key: &str, value: &str) -> Transaction {
+    let mut transaction = Transaction::new(
+        1,
+        "from".to_string(),
+        TransactionData::ChangeStoreValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        },
+        0,
+    );
+    transaction.created_at = UNIX_EPOCH + Duration::from_millis(1_000);
+    transaction
+}
+
+#[test]
+fn fields_can_not_be_shifted_into_each_other() {
+    assert_ne!(store("ab", "c").hash(), store("a", "bc").hash());
+
+    let mut first = store("key", "value");
+    first.from = "ab".to_string();
+    first.nonce = 1;
+    let mut second = first.clone();
+    second.from = "a".to_string();
+    second.nonce = 0;
+    assert_ne!(first.hash(), second.hash());
+
+    let mut block = Block::new(None);
+    block.set_timestamp(0);
+    let mut empty = block.clone();
+    empty.prev = Some(String::new());
+    assert_ne!(block.calculate_hash(), empty.calculate_hash());
+}
+
+#[test]
+fn creation_times_are_hashed_as_unix_millis() {
+    let transaction = store("key", "value");
+
+    let mut nanos = transaction.clone();
+    nanos.created_at += Duration::from_nanos(999_999);
+    assert_eq!(nanos.hash(), transaction.hash());
+
+    let mut millis = transaction.clone();
+    millis.created_at += Duration::from_millis(1);
+    assert_ne!(millis.hash(), transaction.hash());
+
+    // A deadline is not the same as none at all
+    let deadline = transaction.clone().with_valid_until(0);
+    assert_ne!(deadline.hash(), transaction.hash());
+}
+
+#[test]
+fn votes_commit_to_every_field() {
+    let wallet = Wallet::generate();
+    let vote = Vote::new(VoteKind::Prevote, 1, 0, "hash".to_string(), &wallet);
+    let payload = vote.signing_payload();
+
+    for other in [
+        Vote::new(VoteKind::Precommit, 1, 0, "hash".to_string(), &wallet),
+        Vote::new(VoteKind::Prevote, 2, 0, "hash".to_string(), &wallet),
+        Vote::new(VoteKind::Prevote, 1, 1, "hash".to_string(), &wallet),
+        Vote::new(VoteKind::Prevote, 1, 0, "other".to_string(), &wallet),
+    ] {
+        assert_ne!(other.signing_payload(), payload);
+    }
+}
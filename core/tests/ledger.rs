@@ -0,0 +1,278 @@
+use eternal_core::{
+    account::{StoreError, Wallet},
+    blockchain::Blockchain,
+    error::{BlockError, SnapshotError, TxError},
+    spec::{dev_wallets, token_contract, ChainSpec, GenesisContract},
+    transaction::{Transaction, TransactionData},
+};
+use eternal_vm::{
+    smart_contract::{ContractStorage, SmartContract, SmartContractApi, SmartContractStanderd},
+    VmError,
+};
+
+/// The address of the genesis token contract
+const TOKEN: &str = "etnl:0000000000000000000000000000000000000001";
+
+/// A dev chain with a genesis token contract published by bob
+fn setup() -> (Blockchain, String, String) {
+    let wallets: Vec<Wallet> = dev_wallets(2);
+    let (bob, alice) = (
+        wallets[0].address().to_string(),
+        wallets[1].address().to_string(),
+    );
+
+    let mut chain = Blockchain::from_spec(&spec()).unwrap();
+    chain.snapshot_interval = 2;
+    (chain, bob, alice)
+}
+
+/// The spec of the chain built by [`setup`]
+fn spec() -> ChainSpec {
+    let mut spec = ChainSpec::dev();
+    spec.contracts.push(GenesisContract {
+        address: TOKEN.to_string(),
+        standard: SmartContractStanderd::ESC20,
+        publisher: dev_wallets(1)[0].address().to_string(),
+        total_supply: 1_000,
+    });
+    spec
+}
+
+/// Will append a block with just the transaction, sealed by the dev authority
+fn execute(chain: &mut Blockchain, from: &str, data: TransactionData) -> Result<(), TxError> {
+    let nonce = chain.next_nonce(from);
+    let mut transaction = Transaction::new(chain.chain_id, from.to_string(), data, nonce);
+    transaction.sign(&wallet(from));
+
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    let result = chain.commit_state(&mut block).and_then(|()| {
+        block.sign(&dev_wallets(1)[0]);
+        chain.append_block(block)
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(BlockError::Transaction { source, .. }) => Err(source),
+        Err(err) => panic!("unexpected block error: {}", err),
+    }
+}
+
+/// Will return the dev wallet of the address
+fn wallet(address: &str) -> Wallet {
+    dev_wallets(2)
+        .into_iter()
+        .find(|wallet| wallet.address().to_string() == address)
+        .unwrap()
+}
+
+fn transfer(token: &str, to: &str, amount: u128) -> TransactionData {
+    TransactionData::TransferToken {
+        token: token.to_string(),
+        to: to.to_string(),
+        amount,
+    }
+}
+
+#[test]
+fn genesis_contracts_credit_the_publisher() {
+    let (chain, bob, alice) = setup();
+    let ledger = &chain.ledger;
+
+    assert_eq!(ledger.balance(TOKEN, &bob), 1_000);
+    assert_eq!(ledger.balance(TOKEN, &alice), 0);
+    assert_eq!(
+        ledger.holders(TOKEN).collect::<Vec<_>>(),
+        [(bob.as_str(), 1_000)]
+    );
+    assert_eq!(ledger.tokens(&alice).count(), 0);
+    assert!(chain.accounts[&bob].store.is_empty());
+}
+
+#[test]
+fn transfers_move_balances_and_keep_the_indexes() {
+    let (mut chain, bob, alice) = setup();
+
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 300)).unwrap();
+    let ledger = &chain.ledger;
+    assert_eq!(ledger.balance(TOKEN, &bob), 700);
+    assert_eq!(ledger.balance(TOKEN, &alice), 300);
+    let mut holders: Vec<_> = ledger.holders(TOKEN).collect();
+    holders.sort();
+    let mut expected = vec![(alice.as_str(), 300), (bob.as_str(), 700)];
+    expected.sort();
+    assert_eq!(holders, expected);
+    assert_eq!(ledger.tokens(&alice).collect::<Vec<_>>(), [(TOKEN, 300)]);
+
+    // Emptied balances leave the indexes
+    execute(&mut chain, &alice, transfer(TOKEN, &bob, 300)).unwrap();
+    let ledger = &chain.ledger;
+    assert_eq!(
+        ledger.holders(TOKEN).collect::<Vec<_>>(),
+        [(bob.as_str(), 1_000)]
+    );
+    assert_eq!(ledger.tokens(&alice).count(), 0);
+
+    let result = execute(&mut chain, &alice, transfer(TOKEN, &bob, 1));
+    assert!(matches!(
+        result,
+        Err(TxError::Vm(VmError::InsufficientTokens {
+            balance: 0,
+            amount: 1,
+            ..
+        }))
+    ));
+    let result = execute(&mut chain, &bob, transfer(TOKEN, "etnl:00", 1));
+    assert!(matches!(result, Err(TxError::AccountNotFound(_))));
+    assert_eq!(chain.ledger.balance(TOKEN, &bob), 1_000);
+}
+
+#[test]
+fn deploys_only_touch_the_publisher() {
+    let (mut chain, bob, alice) = setup();
+    let accounts = chain.accounts.len();
+
+    let sc = token_contract(SmartContractStanderd::ESC20, alice.clone(), 50);
+    let data = TransactionData::DeploySmartContract {
+        publisher: alice.clone(),
+        sc: Some(sc),
+    };
+    execute(&mut chain, &alice, data).unwrap();
+
+    assert_eq!(chain.accounts.len(), accounts + 1);
+    let contract = chain.smart_contracts.keys().find(|c| *c != TOKEN).unwrap();
+    assert_eq!(chain.ledger.balance(contract, &alice), 50);
+    assert_eq!(chain.ledger.holders(contract).count(), 1);
+    assert_eq!(
+        chain.ledger.tokens(&bob).collect::<Vec<_>>(),
+        [(TOKEN, 1_000)]
+    );
+    assert!(chain
+        .accounts
+        .values()
+        .all(|account| account.store.is_empty()));
+}
+
+#[test]
+fn balances_are_part_of_the_state() {
+    let (mut chain, bob, alice) = setup();
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 300)).unwrap();
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 200)).unwrap();
+
+    let contracts = [TOKEN.to_string(), "etnl:00".to_string()];
+    let proof = chain
+        .prove_account(&alice, &[], &contracts, 2)
+        .unwrap()
+        .unwrap();
+    proof.verify(&chain.headers[2]).unwrap();
+    assert_eq!(proof.balance(TOKEN), Some(500));
+    assert_eq!(proof.balances.len(), 1);
+    assert!(proof.account.balances.is_empty());
+
+    let mut forged = proof;
+    forged.balances[0].balance = 5_000;
+    assert!(forged.verify(&chain.headers[2]).is_err());
+
+    // Snapshots carry the ledger
+    let snapshot = chain.take_snapshots().remove(0);
+    let state = snapshot
+        .restore(chain.chain_id, 2, &chain.headers[2])
+        .unwrap();
+    assert_eq!(state.ledger, chain.ledger);
+}
+
+/// Will deploy a token contract published by the sender whose transfers run the function
+fn deploy(
+    chain: &mut Blockchain,
+    publisher: &str,
+    transfer: fn(&mut ContractStorage, String, String, u128) -> Result<(), VmError>,
+) -> String {
+    let sc = SmartContract::new(
+        SmartContractStanderd::ESC20,
+        SmartContractApi::ESC20 {
+            publisher: publisher.to_string(),
+            total_suply: 100,
+            transfer,
+        },
+    );
+    let data = TransactionData::DeploySmartContract {
+        publisher: publisher.to_string(),
+        sc: Some(sc),
+    };
+    let known: Vec<String> = chain.smart_contracts.keys().cloned().collect();
+    execute(chain, publisher, data).unwrap();
+
+    chain
+        .smart_contracts
+        .keys()
+        .find(|contract| !known.contains(contract))
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn contracts_only_write_their_own_namespace() {
+    let (mut chain, bob, alice) = setup();
+
+    // Counts the transfers of the sender in the contract's namespace
+    let counter = deploy(&mut chain, &bob, |storage, _, _, _| {
+        let contract = storage.contract().to_string();
+        let count = storage
+            .get_bytes(&contract, b"transfers")
+            .map_or(0, |v| v[0]);
+        storage.set_bytes(&contract, b"transfers", vec![count + 1])?;
+        Ok(())
+    });
+    execute(&mut chain, &bob, transfer(&counter, &alice, 10)).unwrap();
+    execute(&mut chain, &bob, transfer(&counter, &alice, 10)).unwrap();
+    let store = &chain.accounts[&bob].store;
+    assert_eq!(store.get_bytes(&counter, b"transfers"), Some(&[2][..]));
+    assert!(chain.accounts[&alice].store.is_empty());
+
+    // Tries to change what the genesis token keeps about the sender
+    let intruder = deploy(&mut chain, &bob, |storage, _, _, _| {
+        storage.set_bytes(TOKEN, b"transfers", vec![0])?;
+        Ok(())
+    });
+    let result = execute(&mut chain, &bob, transfer(&intruder, &alice, 10));
+    assert!(matches!(
+        result,
+        Err(TxError::Vm(VmError::Store(StoreError::AccessDenied { .. })))
+    ));
+    assert_eq!(chain.ledger.balance(&intruder, &bob), 100);
+    assert_eq!(
+        chain.accounts[&bob].store.get_bytes(TOKEN, b"transfers"),
+        None
+    );
+    assert_eq!(chain.accounts[&bob].store.len(), 1);
+}
+
+#[test]
+fn only_contracts_of_the_spec_are_restored() {
+    let (mut chain, bob, alice) = setup();
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 10)).unwrap();
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 10)).unwrap();
+    let snapshot = chain.take_snapshots().remove(0);
+    let restored = Blockchain::from_snapshot(
+        &spec(),
+        &chain.headers[..2],
+        chain.blocks[2].clone(),
+        &snapshot,
+    )
+    .unwrap();
+    assert_eq!(restored.ledger, chain.ledger);
+
+    // The code of a deployed contract is not part of the snapshot
+    let deployed = deploy(&mut chain, &bob, |_, _, _, _| Ok(()));
+    execute(&mut chain, &bob, transfer(TOKEN, &alice, 10)).unwrap();
+    let snapshot = chain.take_snapshots().remove(0);
+    let result = Blockchain::from_snapshot(
+        &spec(),
+        &chain.headers[..4],
+        chain.blocks[4].clone(),
+        &snapshot,
+    );
+    assert!(matches!(
+        result,
+        Err(SnapshotError::UnrestorableContract(address)) if address == deployed
+    ));
+}
@@ -0,0 +1,118 @@
+use eternal_core::{
+    block::Block,
+    blockchain::Blockchain,
+    error::{BlockError, TxError},
+    limits::{serialized_size, Limits},
+    spec::{dev_wallets, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// A dev chain taking two transactions per block and short store entries
+fn setup() -> Blockchain {
+    let mut spec = ChainSpec::dev();
+    spec.limits = Limits {
+        max_block_transactions: 2,
+        max_store_key_size: 8,
+        max_store_value_size: 64,
+        ..Limits::default()
+    };
+
+    Blockchain::from_spec(&spec).unwrap()
+}
+
+/// Will return a store change of the dev authority
+fn store(chain: &Blockchain, nonce: u128, key: &str, value: &str) -> Transaction {
+    let wallet = &dev_wallets(1)[0];
+    let data = TransactionData::ChangeStoreValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let mut transaction =
+        Transaction::new(chain.chain_id, wallet.address().to_string(), data, nonce);
+    transaction.sign(wallet);
+    transaction
+}
+
+/// Will build the next block of the transactions, sealed by the dev authority
+fn block(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let mut block = chain.engine().prepare_block(chain);
+    for transaction in transactions {
+        block.add_transaction(transaction);
+    }
+    chain.commit_state(&mut block).unwrap();
+    block.sign(&dev_wallets(1)[0]);
+    block
+}
+
+#[test]
+fn the_mempool_rejects_oversized_transactions() {
+    let mut chain = setup();
+
+    assert!(matches!(
+        chain.submit_transaction(store(&chain, 1, "a-long-key", "value")),
+        Err(TxError::StoreKeyTooLarge { size: 10, max: 8 })
+    ));
+    assert!(matches!(
+        chain.submit_transaction(store(&chain, 0, "key", &"v".repeat(65))),
+        Err(TxError::StoreValueTooLarge { size: 65, max: 64 })
+    ));
+
+    let transaction = store(&chain, 1, "key", &"v".repeat(64));
+    let size = serialized_size(&transaction);
+    chain.limits.max_transaction_size = size - 1;
+    assert!(matches!(
+        chain.submit_transaction(transaction.clone()),
+        Err(TxError::TooLarge { size: s, .. }) if s == size
+    ));
+    chain.limits.max_transaction_size = size;
+    chain.submit_transaction(transaction).unwrap();
+}
+
+#[test]
+fn pending_transactions_are_split_into_blocks() {
+    let mut chain = setup();
+    for nonce in 1..=3 {
+        let transaction = store(&chain, nonce, "key", &nonce.to_string());
+        chain.submit_transaction(transaction).unwrap();
+    }
+
+    let transactions = chain.take_pending_transactions();
+    assert_eq!(transactions.len(), 2);
+    chain.append_block(block(&chain, transactions)).unwrap();
+    assert_eq!(chain.take_pending_transactions().len(), 1);
+}
+
+#[test]
+fn blocks_over_the_limits_are_rejected() {
+    let mut chain = setup();
+    let transactions: Vec<Transaction> = (1..=3)
+        .map(|nonce| store(&chain, nonce, "key", &nonce.to_string()))
+        .collect();
+
+    assert!(matches!(
+        chain.append_block(block(&chain, transactions.clone())),
+        Err(BlockError::TooManyTransactions { count: 3, max: 2 })
+    ));
+
+    let two = block(&chain, transactions[..2].to_vec());
+    let size = serialized_size(&two);
+    chain.limits.max_block_size = size - 1;
+    assert!(matches!(
+        chain.append_block(two.clone()),
+        Err(BlockError::TooLarge { size: s, .. }) if s == size
+    ));
+    chain.limits.max_block_size = size;
+
+    // Each transaction is checked as well, not only when it was submitted
+    let long = block(&chain, vec![store(&chain, 1, "a-long-key", "value")]);
+    assert!(matches!(
+        chain.append_block(long),
+        Err(BlockError::Transaction {
+            index: 1,
+            source: TxError::StoreKeyTooLarge { .. },
+        })
+    ));
+
+    chain.append_block(two).unwrap();
+    assert_eq!(chain.len(), 2);
+}
@@ -0,0 +1,138 @@
+use eternal_core::{
+    account::{Account, Wallet},
+    blockchain::Blockchain,
+    error::{BlockError, TxError},
+    spec::{dev_wallets, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// A dev chain where bob created a 2-of-3 multisig account of alice, carol and dave and
+/// funded it with 500 tokens
+fn setup() -> (Blockchain, Vec<Wallet>, String) {
+    let wallets = dev_wallets(4);
+    let owners: Vec<String> = wallets[1..]
+        .iter()
+        .map(|wallet| wallet.public_key().to_string())
+        .collect();
+    let multisig = Account::multisig(owners.clone(), 2)
+        .unwrap()
+        .address()
+        .to_string();
+
+    let mut chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+    let bob = wallets[0].address().to_string();
+    let create = TransactionData::CreateMultisigAccount {
+        public_keys: owners,
+        threshold: 2,
+    };
+    let fund = TransactionData::Transfer {
+        to: multisig.clone(),
+        amount: 500,
+    };
+    for (nonce, data) in (1..).zip([create, fund]) {
+        let mut transaction = Transaction::new(chain.chain_id, bob.clone(), data, nonce);
+        transaction.sign(&wallets[0]);
+        execute(&mut chain, transaction).unwrap();
+    }
+
+    (chain, wallets, multisig)
+}
+
+/// Will append a block with just the transaction, sealed by the dev authority
+fn execute(chain: &mut Blockchain, transaction: Transaction) -> Result<(), TxError> {
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    let result = chain.commit_state(&mut block).and_then(|()| {
+        block.sign(&dev_wallets(1)[0]);
+        chain.append_block(block)
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(BlockError::Transaction { source, .. }) => Err(source),
+        Err(err) => panic!("unexpected block error: {}", err),
+    }
+}
+
+/// The first transfer of 100 tokens from the multisig account, signed by the wallets
+fn spend(chain_id: u64, multisig: &str, to: &str, signers: &[&Wallet]) -> Transaction {
+    let data = TransactionData::Transfer {
+        to: to.to_string(),
+        amount: 100,
+    };
+    let mut transaction = Transaction::new(chain_id, multisig.to_string(), data, 1);
+    for signer in signers {
+        transaction.sign(signer);
+    }
+    transaction
+}
+
+#[test]
+fn threshold_of_owners_has_to_sign() {
+    let (mut chain, wallets, multisig) = setup();
+    let (chain_id, bob) = (chain.chain_id, wallets[0].address().to_string());
+    let [_, alice, carol, dave] = &wallets[..] else {
+        unreachable!()
+    };
+
+    let rejected = [
+        (vec![], 0),
+        (vec![alice], 1),
+        // The same owner twice counts once
+        (vec![alice, alice], 1),
+        // Signatures of anyone else do not count
+        (vec![alice, &wallets[0]], 1),
+    ];
+    for (signers, valid) in rejected {
+        let result = execute(&mut chain, spend(chain_id, &multisig, &bob, &signers));
+        assert!(
+            matches!(
+                result,
+                Err(TxError::InsufficientSignatures { required: 2, valid: v }) if v == valid
+            ),
+            "{:?}",
+            result
+        );
+    }
+    assert_eq!(chain.accounts[&multisig].tokens, 500);
+
+    execute(&mut chain, spend(chain_id, &multisig, &bob, &[carol, dave])).unwrap();
+    assert_eq!(chain.accounts[&multisig].tokens, 400);
+}
+
+#[test]
+fn invalid_signatures_are_rejected() {
+    let (mut chain, wallets, multisig) = setup();
+    let (chain_id, bob) = (chain.chain_id, wallets[0].address().to_string());
+
+    let mut forged = spend(chain_id, &multisig, &bob, &[&wallets[1], &wallets[2]]);
+    forged.signatures[1].signature = forged.signatures[0].signature.clone();
+    assert!(matches!(
+        execute(&mut chain, forged),
+        Err(TxError::InvalidSignature)
+    ));
+    assert_eq!(chain.accounts[&multisig].tokens, 500);
+}
+
+#[test]
+fn keyed_accounts_need_the_owners_signature() {
+    let (mut chain, wallets, _) = setup();
+    let bob = wallets[0].address().to_string();
+    let alice = wallets[1].address().to_string();
+    let data = TransactionData::Transfer {
+        to: alice,
+        amount: 1,
+    };
+
+    let unsigned = Transaction::new(chain.chain_id, bob.clone(), data.clone(), 3);
+    assert!(matches!(
+        execute(&mut chain, unsigned),
+        Err(TxError::Unsigned)
+    ));
+
+    let mut foreign = Transaction::new(chain.chain_id, bob, data, 3);
+    foreign.sign(&wallets[1]);
+    assert!(matches!(
+        execute(&mut chain, foreign),
+        Err(TxError::InvalidSignature)
+    ));
+}
@@ -0,0 +1,88 @@
+use eternal_core::{
+    account::Wallet,
+    blockchain::Blockchain,
+    devnet::Devnet,
+    error::{BlockError, TxError},
+    spec::{dev_wallets, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// A dev chain sealed by the first dev wallet
+fn setup() -> (Devnet, Vec<Wallet>) {
+    let wallets = dev_wallets(2);
+    let chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+
+    (Devnet::new(chain, vec![wallets[0].clone()]), wallets)
+}
+
+fn transfer(devnet: &Devnet, from: &Wallet, to: &Wallet, nonce: u128) -> Transaction {
+    let data = TransactionData::Transfer {
+        to: to.address().to_string(),
+        amount: 1,
+    };
+    let mut transaction = Transaction::new(
+        devnet.chain.chain_id,
+        from.address().to_string(),
+        data,
+        nonce,
+    );
+    transaction.sign(from);
+    transaction
+}
+
+#[test]
+fn executed_transactions_bump_the_nonce() {
+    let (mut devnet, wallets) = setup();
+    let address = wallets[1].address().to_string();
+    assert_eq!(devnet.chain.accounts[&address].nonce, 0);
+    assert_eq!(devnet.chain.next_nonce(&address), 1);
+
+    // Consecutive nonces may wait in the same block
+    for nonce in 1..=3 {
+        let transaction = transfer(&devnet, &wallets[1], &wallets[0], nonce);
+        devnet.submit(transaction).unwrap();
+    }
+    assert_eq!(devnet.chain.next_nonce(&address), 4);
+
+    let block = devnet.step().unwrap().unwrap();
+    assert_eq!(block.transactions.len(), 3);
+    assert_eq!(devnet.chain.accounts[&address].nonce, 3);
+    assert_eq!(devnet.chain.next_nonce(&address), 4);
+}
+
+#[test]
+fn gaps_and_used_nonces_are_rejected() {
+    let (mut devnet, wallets) = setup();
+
+    let skipped = transfer(&devnet, &wallets[1], &wallets[0], 2);
+    assert!(matches!(
+        devnet.submit(skipped),
+        Err(TxError::InvalidNonce {
+            expected: 1,
+            actual: 2
+        })
+    ));
+
+    let first = transfer(&devnet, &wallets[1], &wallets[0], 1);
+    devnet.submit(first.clone()).unwrap();
+    devnet.step().unwrap().unwrap();
+
+    // The signed transaction can neither be queued nor sealed again
+    assert!(matches!(
+        devnet.submit(first.clone()),
+        Err(TxError::InvalidNonce {
+            expected: 2,
+            actual: 1
+        })
+    ));
+    let chain = &devnet.chain;
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(first);
+    assert!(matches!(
+        chain.commit_state(&mut block),
+        Err(BlockError::Transaction {
+            source: TxError::InvalidNonce { .. },
+            ..
+        })
+    ));
+}
@@ -0,0 +1,149 @@
+use eternal_core::{
+    account::Wallet,
+    blockchain::{Blockchain, Consensus},
+    devnet::Devnet,
+    error::BlockError,
+    limits::Limits,
+    spec::{Allocation, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+/// A proof of authority chain sealed by the first two wallets, the third one is funded
+/// but no authority
+fn setup() -> (Blockchain, Vec<Wallet>) {
+    let wallets: Vec<Wallet> = (0..3).map(|_| Wallet::generate()).collect();
+    let spec = ChainSpec {
+        name: "poa".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: Consensus::ProofOfAuthority {
+            authorities: wallets[..2]
+                .iter()
+                .map(|wallet| wallet.public_key().to_string())
+                .collect(),
+            period_ms: 0,
+        },
+        limits: Limits::default(),
+        allocations: wallets
+            .iter()
+            .map(|wallet| Allocation {
+                public_key: wallet.public_key().to_string(),
+                tokens: 100,
+                stake: 0,
+            })
+            .collect(),
+        contracts: Vec::new(),
+        genesis_hash: None,
+    };
+
+    (Blockchain::from_spec(&spec).unwrap(), wallets)
+}
+
+/// Will queue the voter's vote to add or remove the candidate
+fn vote(devnet: &mut Devnet, voter: &Wallet, candidate: &Wallet, add: bool) {
+    let data = TransactionData::VoteAuthority {
+        public_key: candidate.public_key().to_string(),
+        add,
+    };
+    devnet.send(voter, data).unwrap();
+}
+
+/// Will return the wallet of the authority whose turn it is to seal the next block
+fn in_turn<'a>(chain: &Blockchain, wallets: &'a [Wallet]) -> &'a Wallet {
+    let public_key = chain.authorities.in_turn(chain.len()).unwrap();
+    wallets
+        .iter()
+        .find(|wallet| &wallet.public_key().to_string() == public_key)
+        .unwrap()
+}
+
+#[test]
+fn votes_add_and_remove_authorities() {
+    let (chain, wallets) = setup();
+    let addresses: Vec<String> = wallets
+        .iter()
+        .map(|wallet| wallet.address().to_string())
+        .collect();
+    let mut devnet = Devnet::new(chain, wallets.clone());
+
+    // Half of the authorities is not enough
+    vote(&mut devnet, &wallets[0], &wallets[2], true);
+    devnet.step().unwrap().unwrap();
+    let authorities = &devnet.chain.authorities;
+    assert!(!authorities.is_authority(&addresses[2]));
+    assert_eq!(authorities.proposals().len(), 1);
+    assert_eq!(authorities.proposals()[0].voters, [addresses[0].clone()]);
+
+    vote(&mut devnet, &wallets[1], &wallets[2], true);
+    devnet.step().unwrap().unwrap();
+    let authorities = &devnet.chain.authorities;
+    assert!(authorities.is_authority(&addresses[2]));
+    assert_eq!(authorities.authorities().len(), 3);
+    assert!(authorities.proposals().is_empty());
+
+    // The new authority votes with the first one to remove the second
+    vote(&mut devnet, &wallets[0], &wallets[1], false);
+    vote(&mut devnet, &wallets[2], &wallets[1], false);
+    devnet.step().unwrap().unwrap();
+    let authorities = &devnet.chain.authorities;
+    assert!(!authorities.is_authority(&addresses[1]));
+    assert_eq!(authorities.authorities().len(), 2);
+
+    // Votes of the removed authority are ignored
+    vote(&mut devnet, &wallets[1], &wallets[0], false);
+    devnet.step().unwrap().unwrap();
+    let authorities = &devnet.chain.authorities;
+    assert!(authorities.is_authority(&addresses[0]));
+    assert!(authorities.proposals().is_empty());
+}
+
+#[test]
+fn out_of_turn_seals_are_rejected() {
+    let (mut chain, wallets) = setup();
+    let sealer = in_turn(&chain, &wallets);
+    let other = wallets[..2]
+        .iter()
+        .find(|wallet| wallet.address() != sealer.address())
+        .unwrap();
+    let from = wallets[0].address().to_string();
+    let data = TransactionData::Transfer {
+        to: from.clone(),
+        amount: 1,
+    };
+    let mut transaction = Transaction::new(chain.chain_id, from, data, 1);
+    transaction.sign(&wallets[0]);
+
+    let engine = chain.engine();
+    let mut block = engine.prepare_block(&chain);
+    block.add_transaction(transaction);
+    chain.commit_state(&mut block).unwrap();
+    assert!(matches!(
+        engine.seal(&chain, &mut block.clone(), other),
+        Err(BlockError::UnexpectedProposer { .. })
+    ));
+
+    // Another authority and an account that is none
+    for wallet in [other, &wallets[2]] {
+        let mut block = block.clone();
+        block.sign(wallet);
+        assert!(matches!(
+            chain.append_block(block),
+            Err(BlockError::UnexpectedProposer { .. })
+        ));
+    }
+
+    // Named as the authority in turn, but signed with another key
+    let mut forged = block.clone();
+    forged.sign(sealer);
+    let hash = forged.hash.clone().unwrap();
+    forged.signature = Some(other.sign(hash.as_bytes()));
+    assert!(matches!(
+        chain.append_block(forged),
+        Err(BlockError::InvalidProposerSignature)
+    ));
+    assert_eq!(chain.len(), 1);
+
+    engine.seal(&chain, &mut block, sealer).unwrap();
+    chain.append_block(block).unwrap();
+    assert_eq!(chain.len(), 2);
+}
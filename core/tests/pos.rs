@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use eternal_core::{
+    account::{Account, AccountType, Unbonding, Wallet},
+    block::Block,
+    blockchain::{Blockchain, Consensus},
+    consensus::ProofOfStake,
+    error::BlockError,
+    limits::Limits,
+    pos::{ValidatorSet, PROPOSER_TIMEOUT_MS, UNBONDING_BLOCKS},
+    replay::replay,
+    spec::{Allocation, ChainSpec},
+    time::now_millis,
+    transaction::{Transaction, TransactionData},
+};
+
+/// Stakes of the validators in the genesis block
+const STAKES: [u128; 3] = [60, 30, 10];
+
+/// A proof of stake chain with the validators staked in the genesis block and a user
+/// holding tokens but no stake
+fn setup() -> (Blockchain, Vec<Wallet>, Wallet) {
+    let validators: Vec<Wallet> = STAKES.iter().map(|_| Wallet::generate()).collect();
+    let user = Wallet::generate();
+    let spec = spec(&validators, &user);
+
+    (Blockchain::from_spec(&spec).unwrap(), validators, user)
+}
+
+/// The spec of the chain built by [`setup`]
+fn spec(validators: &[Wallet], user: &Wallet) -> ChainSpec {
+    let mut allocations: Vec<Allocation> = validators
+        .iter()
+        .zip(STAKES)
+        .map(|(wallet, stake)| Allocation {
+            public_key: wallet.public_key().to_string(),
+            tokens: 0,
+            stake,
+        })
+        .collect();
+    allocations.push(Allocation {
+        public_key: user.public_key().to_string(),
+        tokens: 1_000,
+        stake: 0,
+    });
+
+    ChainSpec {
+        name: "pos".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: Consensus::ProofOfStake { min_stake: 5 },
+        limits: Limits::default(),
+        allocations,
+        contracts: Vec::new(),
+        genesis_hash: None,
+    }
+}
+
+fn validator(stake: u128, jailed: bool) -> Account {
+    Wallet::generate().account(AccountType::Validator {
+        stake,
+        correctly_validated_blocks: 0,
+        incorrectly_validated_blocks: 0,
+        jailed,
+        unbonding: Vec::new(),
+    })
+}
+
+/// Will return the wallet of the validator proposing the next block
+fn proposer<'a>(chain: &Blockchain, validators: &'a [Wallet]) -> &'a Wallet {
+    let proposer = chain.next_proposer().unwrap();
+    validators
+        .iter()
+        .find(|wallet| wallet.address().to_string() == proposer)
+        .unwrap()
+}
+
+/// Will return the next block of the transaction at the given time, signed by the wallet
+fn block_at(
+    chain: &Blockchain,
+    transaction: Transaction,
+    wallet: &Wallet,
+    timestamp: u64,
+) -> Block {
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    block.set_timestamp(timestamp);
+    chain.commit_state(&mut block).unwrap();
+    block.sign(wallet);
+    block
+}
+
+/// Will return the unbonding tokens of a validator
+fn unbonding(chain: &Blockchain, address: &str) -> Vec<Unbonding> {
+    match &chain.accounts[address].acc_type {
+        AccountType::Validator { unbonding, .. } => unbonding.clone(),
+        _ => panic!("not a validator"),
+    }
+}
+
+/// Will return a signed transfer of a token from the user to itself, blocks can not be empty
+fn payment(chain: &Blockchain, user: &Wallet) -> Transaction {
+    let address = user.address().to_string();
+    let nonce = chain.next_nonce(&address);
+    let data = TransactionData::Transfer {
+        to: address.clone(),
+        amount: 1,
+    };
+    let mut transaction = Transaction::new(chain.chain_id, address, data, nonce);
+    transaction.sign(user);
+    transaction
+}
+
+#[test]
+fn proposers_are_picked_by_stake() {
+    let accounts: HashMap<String, Account> = [
+        validator(300, false),
+        validator(100, false),
+        // Below the minimum stake or jailed, never picked
+        validator(4, false),
+        validator(1_000, true),
+        Wallet::generate().account(AccountType::User),
+    ]
+    .into_iter()
+    .map(|account| (account.address().to_string(), account))
+    .collect();
+
+    let set = ValidatorSet::from_accounts(&accounts, 5);
+    assert_eq!(set.validators().len(), 2);
+    assert_eq!(set.total_stake(), 400);
+
+    let mut picks: HashMap<&String, u32> = HashMap::new();
+    for slot in 0..4_000 {
+        let proposer = set.proposer("seed", slot).unwrap();
+        assert_eq!(set.proposer("seed", slot), Some(proposer));
+        *picks.entry(proposer).or_default() += 1;
+    }
+    let heavy = set
+        .validators()
+        .iter()
+        .find(|(_, stake)| *stake == 300)
+        .unwrap();
+    // Three quarters of the stake, three quarters of the slots give or take
+    assert!((2_800..3_200).contains(&picks[&heavy.0]));
+
+    assert!(ValidatorSet::default().proposer("seed", 0).is_none());
+}
+
+#[test]
+fn only_the_selected_proposer_seals() {
+    let (mut chain, validators, user) = setup();
+
+    for _ in 0..5 {
+        let wallet = proposer(&chain, &validators);
+        let transaction = payment(&chain, &user);
+        chain.produce_block(vec![transaction], wallet).unwrap();
+    }
+    assert_eq!(chain.len(), 6);
+
+    let expected = chain.next_proposer().unwrap();
+    let other = validators
+        .iter()
+        .find(|wallet| wallet.address().to_string() != expected)
+        .unwrap();
+    let transaction = payment(&chain, &user);
+    assert!(matches!(
+        chain.produce_block(vec![transaction.clone()], other),
+        Err(BlockError::UnexpectedProposer { .. })
+    ));
+
+    let mut block = Block::new(chain.get_last_block_hash());
+    block.add_transaction(transaction.clone());
+    chain.commit_state(&mut block).unwrap();
+    block.sign(other);
+    assert!(matches!(
+        chain.append_block(block),
+        Err(BlockError::UnexpectedProposer { .. })
+    ));
+
+    // Named as the proposer, but signed with another key
+    let mut block = Block::new(chain.get_last_block_hash());
+    block.add_transaction(transaction);
+    chain.commit_state(&mut block).unwrap();
+    block.sign(proposer(&chain, &validators));
+    let hash = block.hash.clone().unwrap();
+    block.signature = Some(other.sign(hash.as_bytes()));
+    assert!(matches!(
+        chain.append_block(block),
+        Err(BlockError::InvalidProposerSignature)
+    ));
+    assert_eq!(chain.len(), 6);
+}
+
+#[test]
+fn staking_joins_the_validator_set() {
+    let (mut chain, validators, user) = setup();
+    let address = user.address().to_string();
+    assert_eq!(chain.validator_set().stake_of(&address), None);
+
+    let mut stake = Transaction::new(
+        chain.chain_id,
+        address.clone(),
+        TransactionData::Stake { amount: 400 },
+        1,
+    );
+    stake.sign(&user);
+    let wallet = proposer(&chain, &validators);
+    chain.produce_block(vec![stake], wallet).unwrap();
+    assert_eq!(chain.validator_set().stake_of(&address), Some(400));
+    assert_eq!(chain.accounts[&address].tokens, 600);
+
+    let mut unstake = Transaction::new(
+        chain.chain_id,
+        address.clone(),
+        TransactionData::Unstake { amount: 398 },
+        2,
+    );
+    unstake.sign(&user);
+    let mut validators = validators;
+    validators.push(user.clone());
+    let wallet = proposer(&chain, &validators);
+    let height = chain.len() as u64;
+    chain.produce_block(vec![unstake], wallet).unwrap();
+    // Below the minimum stake the account leaves the set
+    assert_eq!(chain.validator_set().stake_of(&address), None);
+    assert_eq!(
+        unbonding(&chain, &address),
+        [Unbonding {
+            amount: 398,
+            release_height: height + UNBONDING_BLOCKS,
+        }]
+    );
+
+    // The tokens stay locked until the unbonding period is over
+    for _ in 0..UNBONDING_BLOCKS {
+        assert_eq!(chain.accounts[&address].tokens, 600);
+        let wallet = proposer(&chain, &validators);
+        let transaction = payment(&chain, &user);
+        chain.produce_block(vec![transaction], wallet).unwrap();
+    }
+    assert_eq!(chain.len() as u64, height + UNBONDING_BLOCKS + 1);
+    assert_eq!(chain.accounts[&address].tokens, 998);
+    assert!(unbonding(&chain, &address).is_empty());
+
+    // Replaying the blocks releases the tokens at the same height
+    let spec = spec(&validators[..STAKES.len()], &user);
+    let replayed = replay(&spec, &chain.blocks).unwrap();
+    assert!(replayed.divergence.is_none());
+    assert_eq!(replayed.chain.accounts[&address].tokens, 998);
+}
+
+#[test]
+fn fallback_proposers_take_over_missed_slots() {
+    let (mut chain, validators, user) = setup();
+    let engine = ProofOfStake::new(5);
+
+    // Far enough in the past that the fallbacks are not ahead of the clock
+    let parent = now_millis() - 60_000;
+    let primary = proposer(&chain, &validators);
+    let block = block_at(&chain, payment(&chain, &user), primary, parent);
+    chain.append_block(block).unwrap();
+
+    let selected = chain.next_proposer().unwrap();
+    assert_eq!(
+        engine.proposers_at(&chain, parent),
+        std::slice::from_ref(&selected)
+    );
+    let allowed = engine.proposers_at(&chain, parent + PROPOSER_TIMEOUT_MS);
+    assert_eq!(allowed.len(), 2);
+    assert_eq!(allowed[0], selected);
+    // Every validator shows up once
+    let all = engine.proposers_at(&chain, parent + 10 * PROPOSER_TIMEOUT_MS);
+    assert_eq!(all.len(), STAKES.len());
+    assert!(validators
+        .iter()
+        .all(|wallet| all.contains(&wallet.address().to_string())));
+
+    let fallback = validators
+        .iter()
+        .find(|wallet| wallet.address().to_string() == allowed[1])
+        .unwrap();
+    let early = block_at(
+        &chain,
+        payment(&chain, &user),
+        fallback,
+        parent + PROPOSER_TIMEOUT_MS - 1,
+    );
+    assert!(matches!(
+        chain.append_block(early),
+        Err(BlockError::UnexpectedProposer { expected, .. }) if expected == selected
+    ));
+
+    let late = block_at(
+        &chain,
+        payment(&chain, &user),
+        fallback,
+        parent + PROPOSER_TIMEOUT_MS,
+    );
+    chain.append_block(late).unwrap();
+    assert_eq!(chain.len(), 3);
+}
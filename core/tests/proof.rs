@@ -0,0 +1,137 @@
+use eternal_core::{
+    account::{StoreKey, Wallet},
+    blockchain::Blockchain,
+    devnet::Devnet,
+    error::ProofError,
+    spec::{dev_wallets, ChainSpec},
+    transaction::TransactionData,
+};
+
+/// A dev chain where bob stores two values, one per block, and then sends alice tokens
+fn setup() -> (Blockchain, String, String) {
+    let spec = ChainSpec::dev();
+    let wallets: Vec<Wallet> = dev_wallets(2);
+    let (bob, alice) = (
+        wallets[0].address().to_string(),
+        wallets[1].address().to_string(),
+    );
+
+    let chain = Blockchain::from_spec(&spec).unwrap();
+    let mut devnet = Devnet::new(chain, vec![wallets[0].clone()]);
+    let transactions = [
+        TransactionData::ChangeStoreValue {
+            key: "color".to_string(),
+            value: "red".to_string(),
+        },
+        TransactionData::ChangeStoreValue {
+            key: "shape".to_string(),
+            value: "round".to_string(),
+        },
+        TransactionData::Transfer {
+            to: alice.clone(),
+            amount: 5,
+        },
+    ];
+    for data in transactions {
+        devnet.send(&wallets[0], data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    (devnet.chain, bob, alice)
+}
+
+#[test]
+fn account_and_storage_proofs() {
+    let (chain, bob, _) = setup();
+    let keys = [
+        StoreKey::new(&bob, b"color"),
+        StoreKey::new(&bob, b"shape"),
+        StoreKey::new(&bob, b"size"),
+    ];
+
+    let proof = chain.prove_account(&bob, &keys, &[], 3).unwrap().unwrap();
+    proof.verify(&chain.headers[3]).unwrap();
+    assert_eq!(proof.account.tokens, chain.accounts[&bob].tokens);
+    assert!(proof.account.store.is_empty());
+    assert_eq!(proof.value(&bob, b"color"), Some(&b"red"[..]));
+    assert_eq!(proof.value(&bob, b"shape"), Some(&b"round"[..]));
+    // Missing keys are left out, which fails when checking the requested ones
+    assert_eq!(proof.storage.len(), 2);
+    assert_eq!(proof.value(&bob, b"size"), None);
+    proof
+        .verify_requested(&chain.headers[3], &keys[..2], &[])
+        .unwrap();
+    assert!(matches!(
+        proof.verify_requested(&chain.headers[3], &keys, &[]),
+        Err(ProofError::MissingEntry(_))
+    ));
+
+    // Older state does not have the later entries
+    let proof = chain.prove_account(&bob, &keys, &[], 1).unwrap().unwrap();
+    proof.verify(&chain.headers[1]).unwrap();
+    assert_eq!(proof.value(&bob, b"color"), Some(&b"red"[..]));
+    assert_eq!(proof.value(&bob, b"shape"), None);
+
+    assert!(chain
+        .prove_account("etnl:00", &keys, &[], 3)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn tampered_proofs_are_rejected() {
+    let (chain, bob, alice) = setup();
+    let keys = [StoreKey::new(&bob, b"color")];
+    let proof = chain.prove_account(&bob, &keys, &[], 3).unwrap().unwrap();
+
+    let mut value = proof.clone();
+    value.storage[0].value = b"blue".to_vec();
+    assert!(matches!(
+        value.verify(&chain.headers[3]),
+        Err(ProofError::InvalidProof)
+    ));
+
+    // An entry of another account's store
+    let mut moved = chain.prove_account(&alice, &[], &[], 3).unwrap().unwrap();
+    moved.storage = proof.storage.clone();
+    assert!(matches!(
+        moved.verify(&chain.headers[3]),
+        Err(ProofError::InvalidProof)
+    ));
+
+    assert!(matches!(
+        proof.verify(&chain.headers[2]),
+        Err(ProofError::WrongBlock { height: 3, .. })
+    ));
+
+    // Withheld entries and balances pass `verify` but not the requested check
+    let mut withheld = proof.clone();
+    withheld.storage.clear();
+    withheld.verify(&chain.headers[3]).unwrap();
+    assert!(matches!(
+        withheld.verify_requested(&chain.headers[3], &keys, &[]),
+        Err(ProofError::MissingEntry(_))
+    ));
+    assert!(matches!(
+        proof.verify_requested(&chain.headers[3], &[], &[alice]),
+        Err(ProofError::MissingEntry(_))
+    ));
+}
+
+#[test]
+fn transaction_proofs() {
+    let (chain, ..) = setup();
+    let hash = chain.blocks[2].transactions[0].hash();
+
+    let proof = chain.prove_transaction(&hash).unwrap().unwrap();
+    assert_eq!(proof.height, 2);
+    proof.verify(&chain.headers[2]).unwrap();
+
+    let mut tampered = proof;
+    tampered.transaction.nonce += 1;
+    assert!(matches!(
+        tampered.verify(&chain.headers[2]),
+        Err(ProofError::InvalidProof)
+    ));
+    assert!(chain.prove_transaction("00").unwrap().is_none());
+}
@@ -0,0 +1,174 @@
+use eternal_core::{
+    account::Wallet,
+    blockchain::Blockchain,
+    devnet::Devnet,
+    error::{BlockError, LookupError},
+    pruning::Pruning,
+    spec::{dev_wallets, ChainSpec},
+    state::state_root,
+    transaction::TransactionData,
+};
+
+/// A dev chain taking a snapshot every 2 blocks, with a transfer to alice in each of
+/// its 8 blocks after genesis
+fn setup(pruning: Pruning) -> Blockchain {
+    let spec = ChainSpec::dev();
+    let wallets: Vec<Wallet> = dev_wallets(2);
+    let alice = wallets[1].address().to_string();
+
+    let mut chain = Blockchain::from_spec(&spec).unwrap();
+    chain.snapshot_interval = 2;
+    chain.pruning = pruning;
+    let mut devnet = Devnet::new(chain, vec![wallets[0].clone()]);
+    for _ in 0..8 {
+        let data = TransactionData::Transfer {
+            to: alice.clone(),
+            amount: 1,
+        };
+        devnet.send(&wallets[0], data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    devnet.chain
+}
+
+/// Will import the blocks of the chain into a new one with the given pruning mode
+fn import(chain: &Blockchain, pruning: Pruning) -> Blockchain {
+    let mut imported = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+    imported.snapshot_interval = 2;
+    imported.pruning = pruning;
+    for block in &chain.blocks[1..] {
+        imported.append_block(block.clone()).unwrap();
+    }
+
+    imported
+}
+
+/// Checks that the derived state of the block is the one it commits to
+fn assert_state_at(chain: &Blockchain, height: usize) {
+    let state = chain.state_at(height).unwrap().unwrap();
+    assert_eq!(
+        Some(state_root(
+            &state.accounts,
+            &state.smart_contracts,
+            &state.ledger,
+            &state.authorities
+        )),
+        chain.headers[height].state_root,
+        "state at {}",
+        height
+    );
+}
+
+#[test]
+fn archive_keeps_everything() {
+    let chain = setup(Pruning::Archive);
+    assert_eq!(chain.len(), 9);
+    assert_eq!(chain.first_block_height(), 0);
+    assert_eq!(chain.first_state_height(), 0);
+
+    for height in 0..chain.len() {
+        assert!(chain.get_block_by_height(height).unwrap().is_some());
+        assert_state_at(&chain, height);
+    }
+    assert!(chain.state_at(chain.len()).unwrap().is_none());
+}
+
+#[test]
+fn full_keeps_the_blocks_but_only_recent_state() {
+    let chain = setup(Pruning::Full { state: 3 });
+
+    // The oldest kept state moved on to the snapshot at height 4
+    assert_eq!(chain.first_block_height(), 0);
+    assert_eq!(chain.first_state_height(), 5);
+    assert!(chain.len() - chain.first_state_height() >= 3);
+
+    for height in 0..chain.len() {
+        assert!(chain.get_block_by_height(height).unwrap().is_some());
+    }
+    for height in 5..chain.len() {
+        assert_state_at(&chain, height);
+    }
+    assert!(matches!(
+        chain.state_at(4),
+        Err(LookupError::StatePruned {
+            height: 4,
+            first: 5
+        })
+    ));
+}
+
+#[test]
+fn pruned_keeps_only_recent_blocks_and_every_header() {
+    let archive = setup(Pruning::Archive);
+    let chain = import(&archive, Pruning::Pruned { blocks: 3 });
+    assert_eq!(chain.state_root(), archive.state_root());
+    assert_eq!(chain.headers, archive.headers);
+
+    assert_eq!(chain.first_block_height(), 4);
+    assert_eq!(chain.blocks.len(), 5);
+    assert!(matches!(
+        chain.get_block_by_height(3),
+        Err(LookupError::BlockPruned {
+            height: 3,
+            first: 4
+        })
+    ));
+    assert_eq!(chain.get_block_by_height(4).unwrap(), archive.blocks.get(4));
+
+    // Pruned blocks are still known by their header
+    let hash = chain.headers[3].hash.clone().unwrap();
+    assert!(chain.contains_block(&hash));
+    assert!(matches!(
+        chain.get_block(&hash),
+        Err(LookupError::BlockPruned { .. })
+    ));
+    assert!(!chain.contains_block("00"));
+
+    // Transactions of pruned blocks are forgotten
+    let pruned = archive.blocks[1].transactions[0].hash();
+    assert!(matches!(
+        chain.get_transaction(&pruned),
+        Err(LookupError::TransactionPruned { first: 4 })
+    ));
+    assert!(matches!(
+        chain.get_receipt(&pruned),
+        Err(LookupError::TransactionPruned { first: 4 })
+    ));
+    let kept = archive.blocks[6].transactions[0].hash();
+    assert_eq!(chain.get_transaction(&kept).unwrap().unwrap().1.height, 6);
+    assert!(archive.get_transaction("00").unwrap().is_none());
+
+    let alice = dev_wallets(2)[1].address().to_string();
+    assert_eq!(archive.get_account_history(&alice).len(), 8);
+    assert_eq!(chain.get_account_history(&alice).len(), 5);
+
+    for height in 5..chain.len() {
+        assert_state_at(&chain, height);
+    }
+    assert!(matches!(
+        chain.state_at(2),
+        Err(LookupError::StatePruned {
+            height: 2,
+            first: 5
+        })
+    ));
+}
+
+#[test]
+fn reorgs_can_not_reach_behind_the_kept_state() {
+    let mut chain = setup(Pruning::Pruned { blocks: 3 });
+    let state_root = chain.state_root();
+
+    let blocks = chain.blocks[1..].to_vec();
+    assert!(matches!(
+        chain.reorg(4, blocks.clone()),
+        Err(BlockError::UnknownForkPoint(4))
+    ));
+
+    // Replacing the blocks after the kept state re-derives it from there
+    chain.reorg(5, blocks).unwrap();
+    assert_eq!(chain.state_root(), state_root);
+    assert_eq!(chain.first_block_height(), 4);
+    assert_state_at(&chain, 6);
+}
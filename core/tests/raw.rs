@@ -0,0 +1,111 @@
+use eternal_core::{
+    error::{RawError, TxError},
+    limits::Limits,
+    raw::{self, RAW_VERSION},
+    spec::dev_wallets,
+    transaction::{Transaction, TransactionData},
+};
+
+/// A transfer of the first dev wallet signed by its key
+fn signed() -> Transaction {
+    let wallets = dev_wallets(2);
+    let data = TransactionData::Transfer {
+        to: wallets[1].address().to_string(),
+        amount: u128::MAX,
+    };
+    let mut transaction =
+        Transaction::new(1, wallets[0].address().to_string(), data, 1).with_valid_until(u64::MAX);
+    transaction.sign(&wallets[0]);
+    transaction
+}
+
+fn deploy() -> Transaction {
+    let data = TransactionData::DeploySmartContract {
+        publisher: "publisher".to_string(),
+        sc: None,
+    };
+    Transaction::new(1, "publisher".to_string(), data, 1)
+}
+
+#[test]
+fn text_forms_round_trip() {
+    let transaction = signed();
+
+    let hex = raw::to_hex(&transaction).unwrap();
+    assert!(hex.starts_with("0x01"));
+    assert_eq!(raw::from_hex(&hex).unwrap(), transaction);
+    assert_eq!(raw::from_hex(&hex[2..]).unwrap(), transaction);
+    assert_eq!(raw::from_text(&format!(" {}\n", hex)).unwrap(), transaction);
+
+    let base64 = raw::to_base64(&transaction).unwrap();
+    assert_eq!(raw::from_base64(&base64).unwrap(), transaction);
+    assert_eq!(
+        raw::from_text(&format!("{}\n", base64)).unwrap(),
+        transaction
+    );
+
+    // The signatures survive, so does the signed hash
+    let decoded = raw::from_text(&base64).unwrap();
+    assert_eq!(decoded.hash(), transaction.hash());
+    raw::check(&decoded, &Limits::default(), 0).unwrap();
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let mut encoded = raw::encode(&signed()).unwrap();
+    assert_eq!(encoded[0], RAW_VERSION);
+
+    encoded[0] = RAW_VERSION + 1;
+    assert!(matches!(
+        raw::decode(&encoded),
+        Err(RawError::UnsupportedVersion(version)) if version == RAW_VERSION + 1
+    ));
+    assert!(matches!(raw::decode(&[]), Err(RawError::Empty)));
+    assert!(matches!(raw::from_hex("0xzz"), Err(RawError::Hex(_))));
+}
+
+#[test]
+fn deployments_have_no_raw_form() {
+    let transaction = deploy();
+    assert!(matches!(raw::encode(&transaction), Err(RawError::Deploy)));
+    assert!(matches!(raw::to_hex(&transaction), Err(RawError::Deploy)));
+
+    // Not even when encoded by hand, the contract code would be missing
+    let mut encoded = vec![RAW_VERSION];
+    serde_json::to_writer(&mut encoded, &transaction).unwrap();
+    assert!(matches!(raw::decode(&encoded), Err(RawError::Deploy)));
+    assert!(matches!(
+        raw::check(&transaction, &Limits::default(), 0),
+        Err(TxError::MissingContract)
+    ));
+}
+
+#[test]
+fn check_fails_on_tampered_signatures() {
+    let transaction = signed();
+    let limits = Limits::default();
+
+    let mut forged = transaction.clone();
+    let signature = &mut forged.signatures[0].signature;
+    let last = if signature.ends_with('0') { "1" } else { "0" };
+    signature.replace_range(signature.len() - 1.., last);
+    assert!(matches!(
+        raw::check(&forged, &limits, 0),
+        Err(TxError::InvalidSignature)
+    ));
+
+    // A signature only covers the transaction it was made for
+    let mut changed = transaction.clone();
+    changed.nonce += 1;
+    assert!(matches!(
+        raw::check(&changed, &limits, 0),
+        Err(TxError::InvalidSignature)
+    ));
+
+    let mut foreign = transaction;
+    foreign.signatures[0].public_key = dev_wallets(2)[1].public_key().to_string();
+    assert!(matches!(
+        raw::check(&foreign, &limits, 0),
+        Err(TxError::InvalidSignature)
+    ));
+}
@@ -0,0 +1,119 @@
+use eternal_core::{
+    account::Wallet,
+    blockchain::Blockchain,
+    devnet::Devnet,
+    replay::{compare, replay, DivergenceKind},
+    spec::{dev_wallets, ChainSpec},
+    transaction::TransactionData,
+};
+
+/// A dev chain with a transfer, an account creation and a store change, one per block
+fn setup() -> (ChainSpec, Blockchain) {
+    let spec = ChainSpec::dev();
+    let wallets: Vec<Wallet> = dev_wallets(3);
+    let alice = wallets[1].address().to_string();
+
+    let mut devnet = Devnet::new(
+        Blockchain::from_spec(&spec).unwrap(),
+        vec![wallets[0].clone()],
+    );
+    let transactions = [
+        (
+            &wallets[0],
+            TransactionData::Transfer {
+                to: alice,
+                amount: 25,
+            },
+        ),
+        (
+            &wallets[1],
+            TransactionData::CreateUserAccount {
+                public_key: wallets[2].public_key().to_string(),
+            },
+        ),
+        (
+            &wallets[0],
+            TransactionData::ChangeStoreValue {
+                key: "color".to_string(),
+                value: "red".to_string(),
+            },
+        ),
+    ];
+    for (wallet, data) in transactions {
+        devnet.send(wallet, data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    (spec, devnet.chain)
+}
+
+#[test]
+fn replay_rebuilds_the_state() {
+    let (spec, chain) = setup();
+
+    let replayed = replay(&spec, &chain.blocks).unwrap();
+    assert_eq!(replayed.divergence, None);
+    assert_eq!(replayed.trace.len(), chain.len());
+    assert_eq!(replayed.chain.state_root(), chain.state_root());
+
+    // The genesis block may be left out
+    let replayed = replay(&spec, &chain.blocks[1..]).unwrap();
+    assert_eq!(replayed.divergence, None);
+    assert_eq!(replayed.chain.state_root(), chain.state_root());
+}
+
+#[test]
+fn replay_reports_the_first_divergent_block() {
+    let (spec, chain) = setup();
+
+    let mut blocks = chain.blocks.clone();
+    blocks[2].set_state_root("00".to_string());
+    blocks[3].set_state_root("00".to_string());
+
+    let replayed = replay(&spec, &blocks).unwrap();
+    let divergence = replayed.divergence.unwrap();
+    assert_eq!(divergence.height, 2);
+    assert!(matches!(divergence.kind, DivergenceKind::StateRoot { .. }));
+    // The state is the one before the divergent block
+    assert_eq!(replayed.chain.len(), 2);
+    assert_eq!(
+        replayed.chain.state_root(),
+        chain.blocks[1].state_root.clone().unwrap()
+    );
+}
+
+#[test]
+fn replay_reports_failing_transactions() {
+    let (spec, chain) = setup();
+
+    // Bob can not transfer more than he owns
+    let mut blocks = chain.blocks.clone();
+    blocks[1].transactions[0].data = TransactionData::Transfer {
+        to: dev_wallets(2)[1].address().to_string(),
+        amount: u128::MAX,
+    };
+
+    let divergence = replay(&spec, &blocks).unwrap().divergence.unwrap();
+    assert_eq!(divergence.height, 1);
+    assert_eq!(divergence.transaction, Some(0));
+    assert!(matches!(divergence.kind, DivergenceKind::Failed { .. }));
+}
+
+#[test]
+fn compare_finds_the_divergent_transaction() {
+    let (spec, chain) = setup();
+    let trace = replay(&spec, &chain.blocks).unwrap().trace;
+    assert_eq!(compare(&trace, &trace), None);
+
+    let mut reference = trace.clone();
+    reference[3].transactions[0].state_root = "00".to_string();
+    reference[3].state_root = "00".to_string();
+
+    let divergence = compare(&trace, &reference).unwrap();
+    assert_eq!(divergence.height, 3);
+    assert_eq!(divergence.transaction, Some(0));
+    assert_eq!(
+        divergence.transaction_hash,
+        Some(chain.blocks[3].transactions[0].hash())
+    );
+}
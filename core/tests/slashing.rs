@@ -0,0 +1,301 @@
+use eternal_core::{
+    account::{AccountType, Unbonding, Wallet},
+    block::Block,
+    blockchain::{Blockchain, Consensus},
+    error::{BlockError, TxError},
+    limits::Limits,
+    slashing::{Evidence, DOUBLE_SIGN_SLASH_PERCENT},
+    spec::{Allocation, ChainSpec},
+    transaction::{Transaction, TransactionData},
+};
+
+const MIN_STAKE: u128 = 1_000;
+
+/// A proof of stake chain where only the reporter has enough stake to propose blocks,
+/// the offender is a validator with less stake
+fn setup() -> (Blockchain, Wallet, Wallet) {
+    let reporter = Wallet::generate();
+    let offender = Wallet::generate();
+
+    let spec = ChainSpec {
+        name: "slashing".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: Consensus::ProofOfStake {
+            min_stake: MIN_STAKE,
+        },
+        limits: Limits::default(),
+        allocations: [(&reporter, MIN_STAKE), (&offender, MIN_STAKE / 2)]
+            .into_iter()
+            .map(|(wallet, stake)| Allocation {
+                public_key: wallet.public_key().to_string(),
+                tokens: 0,
+                stake,
+            })
+            .collect(),
+        contracts: Vec::new(),
+        genesis_hash: None,
+    };
+
+    (Blockchain::from_spec(&spec).unwrap(), reporter, offender)
+}
+
+/// A block on top of the current head, signed by the given wallet
+fn signed_block(bc: &Blockchain, wallet: &Wallet, value: &str) -> Block {
+    let from = wallet.address().to_string();
+    let nonce = bc.next_nonce(&from);
+    let data = TransactionData::ChangeStoreValue {
+        key: "key".to_string(),
+        value: value.to_string(),
+    };
+    let mut transaction = Transaction::new(bc.chain_id, from, data, nonce);
+    transaction.sign(wallet);
+
+    let mut block = Block::new(bc.get_last_block_hash());
+    block.add_transaction(transaction);
+    block.sign(wallet);
+    block
+}
+
+/// Submits the evidence in a block proposed by the reporter
+fn submit(bc: &mut Blockchain, reporter: &Wallet, evidence: Evidence) -> Result<(), TxError> {
+    let from = reporter.address().to_string();
+    let nonce = bc.next_nonce(&from);
+    let data = TransactionData::SubmitEvidence(evidence);
+    let mut transaction = Transaction::new(bc.chain_id, from, data, nonce);
+    transaction.sign(reporter);
+
+    include(bc, reporter, transaction)
+}
+
+/// Appends a block with just the transaction, proposed by the reporter
+fn include(
+    bc: &mut Blockchain,
+    reporter: &Wallet,
+    transaction: Transaction,
+) -> Result<(), TxError> {
+    let mut block = Block::new(bc.get_last_block_hash());
+    block.add_transaction(transaction);
+
+    let result = bc.commit_state(&mut block).and_then(|()| {
+        block.sign(reporter);
+        bc.append_block(block)
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(BlockError::Transaction { source, .. }) => Err(source),
+        Err(err) => panic!("unexpected block error: {}", err),
+    }
+}
+
+/// Will return stake, incorrectly validated blocks and jail state of a validator
+fn validator_state(bc: &Blockchain, wallet: &Wallet) -> (u128, u128, bool) {
+    match bc.accounts[&wallet.address().to_string()].acc_type {
+        AccountType::Validator {
+            stake,
+            incorrectly_validated_blocks,
+            jailed,
+            ..
+        } => (stake, incorrectly_validated_blocks, jailed),
+        _ => panic!("not a validator"),
+    }
+}
+
+#[test]
+fn double_sign_is_slashed_and_jailed() {
+    let (mut bc, reporter, offender) = setup();
+    let (stake_before, _, _) = validator_state(&bc, &offender);
+
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(signed_block(&bc, &offender, "a")),
+        second: Box::new(signed_block(&bc, &offender, "b")),
+    };
+    submit(&mut bc, &reporter, evidence.clone()).unwrap();
+
+    let (stake, incorrect, jailed) = validator_state(&bc, &offender);
+    assert_eq!(
+        stake,
+        stake_before - stake_before * DOUBLE_SIGN_SLASH_PERCENT / 100
+    );
+    assert_eq!(incorrect, 1);
+    assert!(jailed);
+
+    // The same offence can not be punished twice
+    let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+    assert!(matches!(err, TxError::AlreadyJailed(_)));
+}
+
+#[test]
+fn unbonding_stake_is_slashed_and_locked_once_jailed() {
+    let (mut bc, reporter, offender) = setup();
+    let unstake = |bc: &Blockchain| {
+        let from = offender.address().to_string();
+        let nonce = bc.next_nonce(&from);
+        let data = TransactionData::Unstake { amount: 200 };
+        let mut transaction = Transaction::new(bc.chain_id, from, data, nonce);
+        transaction.sign(&offender);
+        transaction
+    };
+
+    let transaction = unstake(&bc);
+    include(&mut bc, &reporter, transaction).unwrap();
+    let (stake, _, _) = validator_state(&bc, &offender);
+    assert_eq!(stake, MIN_STAKE / 2 - 200);
+
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(signed_block(&bc, &offender, "a")),
+        second: Box::new(signed_block(&bc, &offender, "b")),
+    };
+    submit(&mut bc, &reporter, evidence).unwrap();
+
+    let (slashed, _, jailed) = validator_state(&bc, &offender);
+    assert!(jailed);
+    assert_eq!(slashed, stake - stake * DOUBLE_SIGN_SLASH_PERCENT / 100);
+    let AccountType::Validator { unbonding, .. } =
+        &bc.accounts[&offender.address().to_string()].acc_type
+    else {
+        panic!("not a validator");
+    };
+    assert!(matches!(
+        unbonding[..],
+        [Unbonding { amount, .. }] if amount == 200 - 200 * DOUBLE_SIGN_SLASH_PERCENT / 100
+    ));
+
+    // Jailed validators can not withdraw what is left
+    let transaction = unstake(&bc);
+    let err = include(&mut bc, &reporter, transaction).unwrap_err();
+    assert!(matches!(err, TxError::Jailed(address) if address == offender.address().to_string()));
+}
+
+#[test]
+fn jailed_validators_can_not_propose() {
+    let (mut bc, reporter, offender) = setup();
+
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(signed_block(&bc, &reporter, "a")),
+        second: Box::new(signed_block(&bc, &reporter, "b")),
+    };
+    submit(&mut bc, &reporter, evidence).unwrap();
+
+    assert!(bc.validator_set().is_empty());
+    assert!(validator_state(&bc, &reporter).2);
+    assert!(!validator_state(&bc, &offender).2);
+}
+
+#[test]
+fn double_sign_requires_same_height() {
+    let (mut bc, reporter, offender) = setup();
+
+    let first = signed_block(&bc, &offender, "a");
+    let mut second = Block::new(first.hash.clone());
+    second.add_transaction(first.transactions[0].clone());
+    second.sign(&offender);
+
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(first),
+        second: Box::new(second),
+    };
+    let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+    assert!(matches!(err, TxError::InvalidEvidence(_)));
+    assert!(!validator_state(&bc, &offender).2);
+}
+
+#[test]
+fn double_sign_requires_valid_signatures() {
+    let (mut bc, reporter, offender) = setup();
+
+    // Claim that a block signed by the reporter was signed by the offender
+    let first = signed_block(&bc, &offender, "a");
+    let mut second = signed_block(&bc, &reporter, "b");
+    second.proposer = first.proposer.clone();
+    second.update_hash();
+
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(first),
+        second: Box::new(second),
+    };
+    let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+    assert!(matches!(err, TxError::InvalidEvidence(_)));
+    assert!(!validator_state(&bc, &offender).2);
+}
+
+#[test]
+fn identical_blocks_are_no_double_sign() {
+    let (mut bc, reporter, offender) = setup();
+
+    let block = signed_block(&bc, &offender, "a");
+    let evidence = Evidence::DoubleSign {
+        first: Box::new(block.clone()),
+        second: Box::new(block),
+    };
+    let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+    assert!(matches!(err, TxError::InvalidEvidence(_)));
+}
+
+#[test]
+fn invalid_block_is_slashed() {
+    let (mut bc, reporter, offender) = setup();
+
+    // A signed block without any transaction
+    let mut block = Block::new(bc.get_last_block_hash());
+    block.sign(&offender);
+
+    let evidence = Evidence::InvalidBlock {
+        block: Box::new(block),
+    };
+    submit(&mut bc, &reporter, evidence).unwrap();
+
+    let (_, incorrect, jailed) = validator_state(&bc, &offender);
+    assert_eq!(incorrect, 1);
+    assert!(jailed);
+}
+
+#[test]
+fn valid_block_is_no_evidence() {
+    let (mut bc, reporter, offender) = setup();
+
+    let evidence = Evidence::InvalidBlock {
+        block: Box::new(signed_block(&bc, &offender, "a")),
+    };
+    let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+    assert!(matches!(err, TxError::InvalidEvidence(_)));
+    assert!(!validator_state(&bc, &offender).2);
+}
+
+#[test]
+fn tampered_blocks_are_no_evidence() {
+    let (mut bc, reporter, offender) = setup();
+    let block = signed_block(&bc, &offender, "a");
+
+    let mut emptied = block.clone();
+    emptied.transactions.clear();
+    let mut retimed = block.clone();
+    retimed.timestamp += 1;
+    let mut forged = block;
+    forged.transactions[0].signatures[0].signature = reporter.sign(b"forged");
+
+    for block in [emptied, retimed, forged] {
+        let evidence = Evidence::InvalidBlock {
+            block: Box::new(block),
+        };
+        let err = submit(&mut bc, &reporter, evidence).unwrap_err();
+        assert!(matches!(err, TxError::InvalidEvidence(_)));
+    }
+    assert!(!validator_state(&bc, &offender).2);
+}
+
+#[test]
+fn signed_invalid_transactions_are_slashed() {
+    let (mut bc, reporter, offender) = setup();
+
+    // The offender itself includes a transaction with a bad signature
+    let mut block = signed_block(&bc, &offender, "a");
+    block.transactions[0].signatures[0].signature = offender.sign(b"forged");
+    block.sign(&offender);
+
+    let evidence = Evidence::InvalidBlock {
+        block: Box::new(block),
+    };
+    submit(&mut bc, &reporter, evidence).unwrap();
+    assert!(validator_state(&bc, &offender).2);
+}
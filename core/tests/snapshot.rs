@@ -0,0 +1,370 @@
+use eternal_core::{
+    account::Wallet,
+    blockchain::{Blockchain, Consensus},
+    devnet::Devnet,
+    error::{BlockError, LookupError, SnapshotError},
+    snapshot::{chunk_hash, Snapshot, SnapshotManifest, SNAPSHOT_VERSION},
+    spec::{dev_wallets, ChainSpec},
+    transaction::TransactionData,
+};
+
+/// A dev chain taking a snapshot every 2 blocks, with a transfer, an account creation
+/// and a store change, one per block
+fn setup() -> (ChainSpec, Blockchain) {
+    let spec = ChainSpec::dev();
+    let wallets: Vec<Wallet> = dev_wallets(3);
+    let alice = wallets[1].address().to_string();
+
+    let mut chain = Blockchain::from_spec(&spec).unwrap();
+    chain.snapshot_interval = 2;
+    let mut devnet = Devnet::new(chain, vec![wallets[0].clone()]);
+    let transactions = [
+        (
+            &wallets[0],
+            TransactionData::Transfer {
+                to: alice,
+                amount: 25,
+            },
+        ),
+        (
+            &wallets[1],
+            TransactionData::CreateUserAccount {
+                public_key: wallets[2].public_key().to_string(),
+            },
+        ),
+        (
+            &wallets[0],
+            TransactionData::ChangeStoreValue {
+                key: "color".to_string(),
+                value: "red".to_string(),
+            },
+        ),
+    ];
+    for (wallet, data) in transactions {
+        devnet.send(wallet, data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    (spec, devnet.chain)
+}
+
+/// Will send the snapshot through its wire format
+fn transfer(snapshot: &Snapshot) -> Snapshot {
+    let manifest = serde_json::to_string(&snapshot.manifest).unwrap();
+    Snapshot {
+        manifest: serde_json::from_str::<SnapshotManifest>(&manifest).unwrap(),
+        chunks: snapshot.chunks.clone(),
+    }
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let (spec, mut chain) = setup();
+
+    let snapshots = chain.take_snapshots();
+    assert_eq!(snapshots.len(), 1);
+    let snapshot = transfer(&snapshots[0]);
+    assert_eq!(snapshot, snapshots[0]);
+    assert_eq!(snapshot.manifest.version, SNAPSHOT_VERSION);
+    assert_eq!(snapshot.manifest.height, 2);
+    assert_eq!(
+        Some(&snapshot.manifest.state_root),
+        chain.blocks[2].state_root.as_ref()
+    );
+
+    let mut restored = Blockchain::from_snapshot(
+        &spec,
+        &chain.headers[..2],
+        chain.blocks[2].clone(),
+        &snapshot,
+    )
+    .unwrap();
+    assert_eq!(restored.len(), 3);
+    assert_eq!(restored.first_block_height(), 2);
+    assert_eq!(restored.get_last_block_hash(), chain.blocks[2].hash);
+    assert_eq!(restored.get_header(1), chain.get_header(1));
+    assert!(matches!(
+        restored.get_block_by_height(1),
+        Err(LookupError::BlockPruned {
+            height: 1,
+            first: 2
+        })
+    ));
+
+    // The restored node carries on like the original one
+    restored.append_block(chain.blocks[3].clone()).unwrap();
+    assert_eq!(restored.state_root(), chain.state_root());
+    assert_eq!(restored.headers, chain.headers);
+    assert_eq!(
+        restored.get_block_by_height(3).unwrap(),
+        chain.get_block_by_height(3).unwrap()
+    );
+}
+
+#[test]
+fn snapshots_are_chunked_by_account() {
+    let (spec, chain) = setup();
+
+    let snapshot = Snapshot::new(&chain, 3, &chain.headers[3], 1);
+    assert_eq!(snapshot.chunks.len(), chain.accounts.len());
+
+    let restored = Blockchain::from_snapshot(
+        &spec,
+        &chain.headers[..3],
+        chain.blocks[3].clone(),
+        &snapshot,
+    )
+    .unwrap();
+    assert_eq!(restored.state_root(), chain.state_root());
+}
+
+#[test]
+fn tampered_snapshots_are_rejected() {
+    let (spec, mut chain) = setup();
+    let snapshot = chain.take_snapshots().remove(0);
+    let restore = |snapshot: &Snapshot| {
+        Blockchain::from_snapshot(
+            &spec,
+            &chain.headers[..2],
+            chain.blocks[2].clone(),
+            snapshot,
+        )
+        .map(|_| ())
+    };
+
+    let mut newer = snapshot.clone();
+    newer.manifest.version = SNAPSHOT_VERSION + 1;
+    assert!(matches!(
+        restore(&newer),
+        Err(SnapshotError::UnsupportedVersion(_))
+    ));
+
+    let mut corrupt = snapshot.clone();
+    corrupt.chunks[0][10] ^= 1;
+    assert!(matches!(
+        restore(&corrupt),
+        Err(SnapshotError::ChunkHash(0))
+    ));
+
+    // Consistent chunk hashes do not help if the state differs from the block's
+    let mut forged = snapshot.clone();
+    let mut entries: serde_json::Value = serde_json::from_slice(&forged.chunks[0]).unwrap();
+    entries[0]["tokens"] = serde_json::json!(1_000_000);
+    forged.chunks[0] = serde_json::to_vec(&entries).unwrap();
+    forged.manifest.chunks[0] = chunk_hash(&forged.chunks[0]);
+    assert!(matches!(
+        restore(&forged),
+        Err(SnapshotError::StateRootMismatch { .. })
+    ));
+
+    // The snapshot has to be of the block at the end of the headers
+    let result = Blockchain::from_snapshot(
+        &spec,
+        &chain.headers[..3],
+        chain.blocks[3].clone(),
+        &snapshot,
+    );
+    assert!(matches!(result, Err(SnapshotError::WrongBlock { .. })));
+}
+
+#[test]
+fn headers_have_to_lead_to_the_snapshot() {
+    let (spec, mut chain) = setup();
+    let snapshot = chain.take_snapshots().remove(0);
+
+    // A gap in the headers
+    let headers = [chain.headers[0].clone(), chain.headers[0].clone()];
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[2].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Header {
+            height: 1,
+            source: BlockError::InvalidPrev
+        })
+    ));
+
+    // Headers of another chain
+    let mut other = ChainSpec::dev();
+    other.name = "other".to_string();
+    let result = Blockchain::from_snapshot(
+        &other,
+        &chain.headers[..2],
+        chain.blocks[2].clone(),
+        &snapshot,
+    );
+    assert!(matches!(result, Err(SnapshotError::Spec(_))));
+
+    // A header not matching its hash
+    let mut headers = chain.headers[..2].to_vec();
+    headers[1].timestamp += 1;
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[2].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Header {
+            height: 1,
+            source: BlockError::HashMismatch
+        })
+    ));
+}
+
+#[test]
+fn headers_have_to_be_sealed() {
+    let (spec, mut chain) = setup();
+    let snapshot = chain.take_snapshots().remove(0);
+    let wallets = dev_wallets(2);
+
+    // Sealed by alice instead of the dev authority
+    let mut block = chain.blocks[1].clone();
+    block.sign(&wallets[1]);
+    let headers = [chain.headers[0].clone(), block.header()];
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[2].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Header {
+            height: 1,
+            source: BlockError::UnexpectedProposer { .. }
+        })
+    ));
+
+    // The seal of another block
+    let mut headers = chain.headers[..2].to_vec();
+    headers[1].signature = chain.headers[2].signature.clone();
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[2].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Header {
+            height: 1,
+            source: BlockError::InvalidProposerSignature
+        })
+    ));
+}
+
+/// Will produce a block for each transaction with the signers, a snapshot is taken of the
+/// last one
+fn produce(
+    spec: &ChainSpec,
+    signers: &[Wallet],
+    transactions: Vec<(&Wallet, TransactionData)>,
+) -> Blockchain {
+    let mut chain = Blockchain::from_spec(spec).unwrap();
+    chain.snapshot_interval = transactions.len();
+    let mut devnet = Devnet::new(chain, signers.to_vec());
+    for (wallet, data) in transactions {
+        devnet.send(wallet, data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    devnet.chain
+}
+
+#[test]
+fn proof_of_authority_seals_are_checked_against_the_snapshot() {
+    let wallets = dev_wallets(3);
+    let mut spec = ChainSpec::dev();
+    spec.consensus = Consensus::ProofOfAuthority {
+        authorities: vec![
+            wallets[0].public_key().to_string(),
+            wallets[1].public_key().to_string(),
+        ],
+        period_ms: 0,
+    };
+
+    // The third authority is voted in and seals one of the blocks after
+    let vote = || TransactionData::VoteAuthority {
+        public_key: wallets[2].public_key().to_string(),
+        add: true,
+    };
+    let transfer = || TransactionData::Transfer {
+        to: wallets[1].address().to_string(),
+        amount: 1,
+    };
+    let transactions = vec![
+        (&wallets[0], vote()),
+        (&wallets[1], vote()),
+        (&wallets[0], transfer()),
+        (&wallets[0], transfer()),
+        (&wallets[0], transfer()),
+        (&wallets[0], transfer()),
+    ];
+    let mut chain = produce(&spec, &wallets, transactions);
+    let sealer = wallets[2].address().to_string();
+    assert!(chain.headers[3..6]
+        .iter()
+        .any(|header| header.proposer.as_ref() == Some(&sealer)));
+
+    let snapshot = chain.take_snapshots().remove(0);
+    let restored = Blockchain::from_snapshot(
+        &spec,
+        &chain.headers[..6],
+        chain.blocks[6].clone(),
+        &snapshot,
+    )
+    .unwrap();
+    assert_eq!(restored.state_root(), chain.state_root());
+    assert_eq!(restored.authorities, chain.authorities);
+
+    // Sealed by an account which is no authority
+    let mut block = chain.blocks[1].clone();
+    block.sign(&Wallet::generate());
+    let mut headers = chain.headers[..6].to_vec();
+    headers[1] = block.header();
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[6].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::UnknownSealer { height: 1, .. })
+    ));
+
+    // The seal of another block
+    let mut headers = chain.headers[..6].to_vec();
+    headers[1].signature = chain.headers[2].signature.clone();
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[6].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Header {
+            height: 1,
+            source: BlockError::InvalidProposerSignature
+        })
+    ));
+}
+
+#[test]
+fn proof_of_stake_seals_are_checked_against_the_snapshot() {
+    let wallets = dev_wallets(2);
+    let mut spec = ChainSpec::dev();
+    spec.consensus = Consensus::ProofOfStake { min_stake: 5 };
+    spec.allocations[0].stake = 50;
+
+    // The second wallet becomes a validator after genesis
+    let transfer = || TransactionData::Transfer {
+        to: wallets[0].address().to_string(),
+        amount: 1,
+    };
+    let transactions = vec![
+        (&wallets[1], TransactionData::Stake { amount: 1_000 }),
+        (&wallets[1], transfer()),
+        (&wallets[1], transfer()),
+        (&wallets[1], transfer()),
+    ];
+    let mut chain = produce(&spec, &wallets, transactions);
+
+    let snapshot = chain.take_snapshots().remove(0);
+    let restored = Blockchain::from_snapshot(
+        &spec,
+        &chain.headers[..4],
+        chain.blocks[4].clone(),
+        &snapshot,
+    )
+    .unwrap();
+    assert_eq!(restored.state_root(), chain.state_root());
+
+    // Sealed by an account which is no validator
+    let mut block = chain.blocks[2].clone();
+    block.sign(&Wallet::generate());
+    let mut headers = chain.headers[..4].to_vec();
+    headers[2] = block.header();
+    let result = Blockchain::from_snapshot(&spec, &headers, chain.blocks[4].clone(), &snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapshotError::UnknownSealer { height: 2, .. })
+    ));
+}
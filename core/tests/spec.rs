@@ -0,0 +1,122 @@
+use std::{env, fs, process};
+
+use eternal_core::{
+    account::Wallet,
+    blockchain::{Blockchain, Consensus},
+    error::SpecError,
+    spec::{dev_wallets, ChainSpec},
+};
+
+/// A dev chain spec in TOML, its integers are i64 but still read into the u128 balances
+fn toml_spec(authority: &Wallet, funded: &Wallet) -> String {
+    format!(
+        r#"
+name = "spec-test"
+chain_id = 7
+genesis_timestamp = 1700000000000
+
+[consensus.Dev]
+authority = "{}"
+
+[limits]
+max_block_transactions = 10
+
+[[allocations]]
+public_key = "{}"
+tokens = 5000000000000000000
+"#,
+        authority.address(),
+        funded.public_key()
+    )
+}
+
+#[test]
+fn toml_and_json_specs_are_the_same() {
+    let wallets = dev_wallets(2);
+    let spec = ChainSpec::from_toml(&toml_spec(&wallets[0], &wallets[1])).unwrap();
+
+    assert_eq!(spec.chain_id, 7);
+    assert_eq!(
+        spec.consensus,
+        Consensus::Dev {
+            authority: wallets[0].address().to_string()
+        }
+    );
+    // Missing limits keep their defaults
+    assert_eq!(spec.limits.max_block_transactions, 10);
+    assert_eq!(spec.limits.max_store_key_size, 64);
+    assert_eq!(spec.allocations[0].tokens, 5_000_000_000_000_000_000);
+    assert_eq!(spec.allocations[0].stake, 0);
+    assert!(spec.contracts.is_empty());
+
+    assert_eq!(
+        ChainSpec::from_json(&spec.to_json().unwrap()).unwrap(),
+        spec
+    );
+}
+
+#[test]
+fn specs_are_loaded_by_extension() {
+    let wallets = dev_wallets(2);
+    let content = toml_spec(&wallets[0], &wallets[1]);
+    let spec = ChainSpec::from_toml(&content).unwrap();
+
+    let dir = env::temp_dir().join(format!("eternal-spec-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("spec.toml"), &content).unwrap();
+    fs::write(dir.join("spec.json"), spec.to_json().unwrap()).unwrap();
+    fs::write(dir.join("spec.yaml"), &content).unwrap();
+
+    assert_eq!(ChainSpec::load(dir.join("spec.toml")).unwrap(), spec);
+    assert_eq!(ChainSpec::load(dir.join("spec.json")).unwrap(), spec);
+    assert!(matches!(
+        ChainSpec::load(dir.join("spec.yaml")),
+        Err(SpecError::UnsupportedFormat(format)) if format == "yaml"
+    ));
+    assert!(matches!(
+        ChainSpec::load(dir.join("missing.toml")),
+        Err(SpecError::Io(_))
+    ));
+    // The TOML content is no JSON
+    fs::write(dir.join("spec.json"), &content).unwrap();
+    assert!(matches!(
+        ChainSpec::load(dir.join("spec.json")),
+        Err(SpecError::Json(_))
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn the_genesis_hash_is_deterministic() {
+    let wallets = dev_wallets(2);
+    let spec = ChainSpec::from_toml(&toml_spec(&wallets[0], &wallets[1])).unwrap();
+    let hash = spec.genesis_block().unwrap().hash.unwrap();
+
+    let reloaded = ChainSpec::from_json(&spec.to_json().unwrap()).unwrap();
+    assert_eq!(reloaded.genesis_block().unwrap().hash.unwrap(), hash);
+    let chain = Blockchain::from_spec(&spec).unwrap();
+    assert_eq!(chain.headers[0].hash.as_ref(), Some(&hash));
+
+    // Every part of the spec is committed to
+    let mut other = spec.clone();
+    other.chain_id = 8;
+    assert_ne!(other.genesis_block().unwrap().hash.unwrap(), hash);
+    let mut other = spec.clone();
+    other.allocations[0].tokens += 1;
+    assert_ne!(other.genesis_block().unwrap().hash.unwrap(), hash);
+    let mut other = spec.clone();
+    other.limits.max_block_transactions += 1;
+    assert_ne!(other.genesis_block().unwrap().hash.unwrap(), hash);
+
+    // The expected hash is checked, but not part of the spec hash
+    let mut pinned = spec.clone();
+    pinned.genesis_hash = Some(hash.clone());
+    assert_eq!(pinned.hash(), spec.hash());
+    Blockchain::from_spec(&pinned).unwrap();
+    pinned.genesis_hash = Some("00".to_string());
+    assert!(matches!(
+        Blockchain::from_spec(&pinned),
+        Err(SpecError::GenesisHashMismatch { actual, .. }) if actual == hash
+    ));
+}
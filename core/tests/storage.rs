@@ -0,0 +1,66 @@
+use std::{env, fs, io::Write, process};
+
+use eternal_core::{
+    block::Block,
+    blockchain::Blockchain,
+    devnet::Devnet,
+    error::StorageError,
+    spec::{dev_wallets, ChainSpec},
+    storage::BlockLog,
+    transaction::TransactionData,
+};
+
+/// Will return the blocks of a dev chain after its genesis block
+fn blocks(count: usize) -> Vec<Block> {
+    let wallets = dev_wallets(2);
+    let chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+    let mut devnet = Devnet::new(chain, vec![wallets[0].clone()]);
+    for _ in 0..count {
+        let data = TransactionData::Transfer {
+            to: wallets[1].address().to_string(),
+            amount: 1,
+        };
+        devnet.send(&wallets[0], data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+
+    devnet.chain.blocks[1..].to_vec()
+}
+
+#[test]
+fn a_block_cut_off_by_a_crash_is_dropped() {
+    let dir = env::temp_dir().join(format!("eternal-storage-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("blocks.jsonl");
+    let blocks = blocks(3);
+
+    BlockLog::write(&path, &blocks[..2]).unwrap();
+    let line = serde_json::to_string(&blocks[2]).unwrap();
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&line.as_bytes()[..line.len() / 2]).unwrap();
+    assert_eq!(BlockLog::read(&path).unwrap(), blocks[..2]);
+
+    // The next block is appended after the last complete one
+    let mut log = BlockLog::open(&path).unwrap();
+    log.append(&blocks[2]).unwrap();
+    log.flush().unwrap();
+    assert_eq!(BlockLog::read(&path).unwrap(), blocks);
+
+    // A block only missing its line break is kept
+    BlockLog::write(&path, &blocks[..2]).unwrap();
+    file.write_all(line.as_bytes()).unwrap();
+    assert_eq!(BlockLog::read(&path).unwrap(), blocks);
+    let mut log = BlockLog::open(&path).unwrap();
+    log.append(&blocks[0]).unwrap();
+    log.flush().unwrap();
+    assert_eq!(BlockLog::read(&path).unwrap().len(), 4);
+
+    // Damage before the last line is still an error
+    fs::write(&path, format!("{{\n{}\n", line)).unwrap();
+    assert!(matches!(
+        BlockLog::read(&path),
+        Err(StorageError::Corrupt { line: 1, .. })
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
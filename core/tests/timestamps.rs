@@ -0,0 +1,109 @@
+use eternal_core::{
+    account::Wallet,
+    block::Block,
+    blockchain::Blockchain,
+    error::{BlockError, TxError},
+    spec::{dev_wallets, ChainSpec},
+    time::{median_time_past, now_millis, MAX_FUTURE_DRIFT_MS, MEDIAN_TIME_BLOCKS},
+    transaction::{Transaction, TransactionData},
+};
+
+/// Will return the next transaction of the dev authority, signed after `change` was applied
+fn transaction(chain: &Blockchain, change: fn(Transaction) -> Transaction) -> Transaction {
+    let wallet: Wallet = dev_wallets(1).remove(0);
+    let from = wallet.address().to_string();
+    let nonce = chain.next_nonce(&from);
+    let data = TransactionData::ChangeStoreValue {
+        key: "key".to_string(),
+        value: nonce.to_string(),
+    };
+    let mut transaction = change(Transaction::new(chain.chain_id, from, data, nonce));
+    transaction.sign(&wallet);
+    transaction
+}
+
+/// Will build the next block with the transaction at the given time, sealed by the dev authority
+fn block(chain: &Blockchain, transaction: Transaction, timestamp: u64) -> Block {
+    let mut block = chain.engine().prepare_block(chain);
+    block.add_transaction(transaction);
+    block.set_timestamp(timestamp);
+    chain.commit_state(&mut block).unwrap();
+    block.sign(&dev_wallets(1)[0]);
+    block
+}
+
+#[test]
+fn blocks_can_not_go_back_behind_the_median_time() {
+    let mut chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+    let base = now_millis() - 100_000;
+    for i in 0..MEDIAN_TIME_BLOCKS as u64 {
+        let transaction = transaction(&chain, |t| t);
+        let block = block(&chain, transaction, base + i * 1_000);
+        chain.append_block(block).unwrap();
+    }
+
+    // The genesis block dropped out of the window
+    let median = base + 5_000;
+    assert_eq!(median_time_past(&chain.headers), Some(median));
+    assert_eq!(median_time_past(&chain.headers[..1]), Some(0));
+    assert_eq!(median_time_past(&[]), None);
+
+    let early = block(&chain, transaction(&chain, |t| t), median - 1);
+    assert!(matches!(
+        chain.append_block(early),
+        Err(BlockError::TimestampTooOld { median: m, .. }) if m == median
+    ));
+
+    // Before the parent block, but not before the median
+    let late = block(&chain, transaction(&chain, |t| t), median);
+    chain.append_block(late).unwrap();
+}
+
+#[test]
+fn blocks_can_not_run_ahead_of_the_clock() {
+    let mut chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+
+    let ahead = now_millis() + MAX_FUTURE_DRIFT_MS + 60_000;
+    let future = block(&chain, transaction(&chain, |t| t), ahead);
+    assert!(matches!(
+        chain.append_block(future),
+        Err(BlockError::TimestampInFuture { timestamp, max_drift: MAX_FUTURE_DRIFT_MS, .. })
+            if timestamp == ahead
+    ));
+
+    // A drift within the limit is tolerated
+    let ahead = now_millis() + MAX_FUTURE_DRIFT_MS / 2;
+    let close = block(&chain, transaction(&chain, |t| t), ahead);
+    chain.append_block(close).unwrap();
+}
+
+#[test]
+fn expired_transactions_are_rejected() {
+    let mut chain = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
+
+    let expired = transaction(&chain, |t| t.with_valid_until(now_millis() - 1_000));
+    assert!(matches!(
+        chain.submit_transaction(expired),
+        Err(TxError::Expired { .. })
+    ));
+    let valid = transaction(&chain, |t| t.with_valid_until(now_millis() + 60_000));
+    chain.submit_transaction(valid.clone()).unwrap();
+    assert_eq!(chain.take_pending_transactions(), [valid]);
+
+    // Blocks are checked against their own timestamp, the limit is inclusive
+    let transaction = transaction(&chain, |t| t.with_valid_until(now_millis() - 10_000));
+    let valid_until = transaction.valid_until.unwrap();
+    // Producing the block would already fail, so it is moved past the limit once built
+    let mut after = block(&chain, transaction.clone(), valid_until);
+    after.set_timestamp(valid_until + 1);
+    after.sign(&dev_wallets(1)[0]);
+    assert!(matches!(
+        chain.append_block(after),
+        Err(BlockError::Transaction {
+            index: 1,
+            source: TxError::Expired { timestamp, .. },
+        }) if timestamp == valid_until + 1
+    ));
+    let at = block(&chain, transaction, valid_until);
+    chain.append_block(at).unwrap();
+}
@@ -0,0 +1,296 @@
+use eternal_account::{secp256k1::PublicKey, verify_signature, Account, Address, StoreKey};
+use eternal_core::{
+    block::BlockHeader,
+    blockchain::Consensus,
+    consensus::{AuthoritySet, ProofOfWork},
+    error::{BlockError, ProofError, SpecError},
+    proof::{AccountProof, TransactionProof},
+    snapshot::AccountEntry,
+    spec::ChainSpec,
+};
+
+use crate::{error::LightError, provider::Provider};
+
+/// The most headers requested at once
+pub const MAX_HEADERS: usize = 1000;
+
+/// Follows the headers of a chain from its genesis block on
+#[derive(Debug, Clone)]
+pub struct LightClient {
+    consensus: Consensus,
+    /// The checked headers, the genesis block has height 0
+    headers: Vec<BlockHeader>,
+    /// The authorities which sealed the last header (proof of authority only)
+    authorities: AuthoritySet,
+    /// The account sealing every block of a dev chain
+    dev_authority: Option<Account>,
+}
+
+impl LightClient {
+    /// Will create the client with the genesis block of the spec. Fails if the spec
+    /// expects a different genesis hash.
+    pub fn new(spec: &ChainSpec) -> Result<Self, LightError> {
+        let genesis = spec.genesis_block()?;
+        if let Some(expected) = &spec.genesis_hash {
+            let actual = genesis.hash.clone().unwrap_or_default();
+            if &actual != expected {
+                let expected = expected.clone();
+                return Err(SpecError::GenesisHashMismatch { expected, actual }.into());
+            }
+        }
+
+        let dev_authority = match &spec.consensus {
+            Consensus::ProofOfStake { .. } => return Err(LightError::UnsupportedConsensus),
+            Consensus::Dev { authority } => {
+                let (mut accounts, ..) = spec.genesis_state()?;
+                let account = accounts
+                    .remove(authority)
+                    .ok_or_else(|| LightError::UnknownAuthority(authority.clone()))?;
+                Some(account)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            consensus: spec.consensus.clone(),
+            headers: vec![genesis.header()],
+            authorities: spec.consensus.authority_set(),
+            dev_authority,
+        })
+    }
+
+    /// Will return the height of the last checked header
+    pub fn height(&self) -> usize {
+        self.headers.len() - 1
+    }
+
+    /// Will return the checked header at the given height
+    pub fn header(&self, height: usize) -> Option<&BlockHeader> {
+        self.headers.get(height)
+    }
+
+    /// Will return the last checked header
+    pub fn head(&self) -> &BlockHeader {
+        &self.headers[self.headers.len() - 1]
+    }
+
+    /// Will fetch and check the headers up to the full node's head. Returns the new height.
+    pub fn sync<P: Provider>(&mut self, provider: &P) -> Result<usize, LightError> {
+        let head = provider.head()?;
+        while self.height() < head {
+            let from = self.headers.len();
+            let headers = provider.headers(from, (head + 1 - from).min(MAX_HEADERS))?;
+            if headers.is_empty() {
+                return Err(LightError::Unavailable(from));
+            }
+
+            for header in headers {
+                self.import_header(header, provider)?;
+            }
+        }
+
+        Ok(self.height())
+    }
+
+    /// Will check the header on top of the last one and append it. Proof of authority
+    /// chains fetch the authorities committed to by the header from the provider.
+    pub fn import_header<P: Provider>(
+        &mut self,
+        header: BlockHeader,
+        provider: &P,
+    ) -> Result<(), LightError> {
+        let height = self.headers.len();
+        let invalid = |source| LightError::Header { height, source };
+
+        if !header.verify_own_hash() {
+            return Err(invalid(BlockError::HashMismatch));
+        }
+        let parent = self.head();
+        if header.prev != parent.hash {
+            return Err(invalid(BlockError::InvalidPrev));
+        }
+
+        match &self.consensus {
+            Consensus::ProofOfWork { difficulty } => {
+                let work = ProofOfWork::new(*difficulty);
+                if !work.meets_difficulty(header.hash.as_deref().unwrap_or_default()) {
+                    let difficulty = *difficulty;
+                    return Err(invalid(BlockError::InsufficientWork { difficulty }));
+                }
+            }
+            Consensus::Dev { authority } => {
+                let key = self
+                    .dev_authority
+                    .as_ref()
+                    .and_then(|account| PublicKey::from_slice(&account.public_key_bytes).ok())
+                    .ok_or_else(|| LightError::UnknownAuthority(authority.clone()))?;
+                check_seal(&header, authority, &key).map_err(invalid)?;
+            }
+            Consensus::ProofOfAuthority { period_ms, .. } => {
+                let earliest = parent.timestamp.saturating_add(*period_ms);
+                if header.timestamp < earliest {
+                    return Err(invalid(BlockError::PeriodNotElapsed {
+                        timestamp: header.timestamp,
+                        earliest,
+                    }));
+                }
+                self.authorities = self.check_authorities(height, &header, provider)?;
+            }
+            Consensus::ProofOfStake { .. } => return Err(LightError::UnsupportedConsensus),
+        }
+
+        self.headers.push(header);
+        Ok(())
+    }
+
+    /// Will check that the header was sealed by the authority in turn and return the
+    /// authorities it commits to. They may only differ from the ones of the parent by
+    /// the votes inside the parent block, which is fetched and checked then.
+    fn check_authorities<P: Provider>(
+        &self,
+        height: usize,
+        header: &BlockHeader,
+        provider: &P,
+    ) -> Result<AuthoritySet, LightError> {
+        let invalid = |source| LightError::Header { height, source };
+
+        let proof = provider
+            .authority_proof(height)?
+            .ok_or(LightError::Unavailable(height))?;
+        proof.verify(header)?;
+
+        if proof.authorities != self.authorities {
+            let parent = provider
+                .block(height - 1)?
+                .ok_or(LightError::Unavailable(height - 1))?;
+            let parent_header = &self.headers[height - 1];
+            if &parent.header() != parent_header {
+                return Err(ProofError::WrongBlock {
+                    height: height - 1,
+                    expected: parent_header.hash.clone().unwrap_or_default(),
+                    actual: parent.hash.unwrap_or_default(),
+                }
+                .into());
+            }
+
+            let mut expected = self.authorities.clone();
+            expected.apply_votes(&parent);
+            if expected != proof.authorities {
+                return Err(LightError::AuthorityChange(height));
+            }
+        }
+
+        let public_key = proof
+            .authorities
+            .in_turn(height)
+            .ok_or(invalid(BlockError::NoValidators))?;
+        let key = public_key
+            .parse::<PublicKey>()
+            .map_err(|_| invalid(BlockError::InvalidAuthority(public_key.clone())))?;
+        let address = Address::from_public_key_bytes(&key.serialize()).to_string();
+        check_seal(header, &address, &key).map_err(invalid)?;
+
+        Ok(proof.authorities)
+    }
+
+    /// Checks that the transaction is included in a followed block
+    pub fn verify_transaction(&self, proof: &TransactionProof) -> Result<(), LightError> {
+        let header = self
+            .header(proof.height)
+            .ok_or(LightError::UnknownHeight(proof.height))?;
+        Ok(proof.verify(header)?)
+    }
+
+    /// Checks that the account is part of the state of a followed block
+    pub fn verify_account(&self, proof: &AccountProof) -> Result<(), LightError> {
+        let header = self
+            .header(proof.height)
+            .ok_or(LightError::UnknownHeight(proof.height))?;
+        Ok(proof.verify(header)?)
+    }
+
+    /// Will fetch the transaction together with the block it is included in, none if
+    /// the full node does not know it
+    pub fn transaction<P: Provider>(
+        &self,
+        provider: &P,
+        hash: &str,
+    ) -> Result<Option<TransactionProof>, LightError> {
+        let Some(proof) = provider.transaction_proof(hash)? else {
+            return Ok(None);
+        };
+        self.verify_transaction(&proof)?;
+
+        Ok(Some(proof))
+    }
+
+    /// Will fetch the account, the store entries of the given keys and the balances of the
+    /// given token contracts as of the last checked header. None if the full node does not
+    /// know the account, which can not be checked. Fails if a requested store entry or
+    /// balance is missing, as its absence can not be checked either.
+    pub fn proof<P: Provider>(
+        &self,
+        provider: &P,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+    ) -> Result<Option<AccountProof>, LightError> {
+        let Some(proof) = provider.account_proof(address, keys, contracts, self.height())? else {
+            return Ok(None);
+        };
+        let header = self
+            .header(proof.height)
+            .ok_or(LightError::UnknownHeight(proof.height))?;
+        proof.verify_requested(header, keys, contracts)?;
+        if proof.account.address != address {
+            return Err(ProofError::InvalidProof.into());
+        }
+
+        Ok(Some(proof))
+    }
+
+    /// Will fetch the account as of the last checked header, without its store
+    pub fn account<P: Provider>(
+        &self,
+        provider: &P,
+        address: &str,
+    ) -> Result<Option<AccountEntry>, LightError> {
+        let proof = self.proof(provider, address, &[], &[])?;
+        Ok(proof.map(|proof| proof.account))
+    }
+
+    /// Will fetch the balance of the account as of the last checked header
+    pub fn balance<P: Provider>(
+        &self,
+        provider: &P,
+        address: &str,
+    ) -> Result<Option<u128>, LightError> {
+        Ok(self
+            .account(provider, address)?
+            .map(|account| account.tokens))
+    }
+}
+
+/// Checks that the header was signed by the expected proposer
+fn check_seal(header: &BlockHeader, expected: &str, key: &PublicKey) -> Result<(), BlockError> {
+    let proposer = header
+        .proposer
+        .as_ref()
+        .ok_or(BlockError::MissingProposer)?;
+    if proposer != expected {
+        return Err(BlockError::UnexpectedProposer {
+            expected: expected.to_string(),
+            actual: proposer.clone(),
+        });
+    }
+
+    let valid_signature = match (&header.signature, &header.hash) {
+        (Some(signature), Some(hash)) => verify_signature(key, hash.as_bytes(), signature),
+        _ => false,
+    };
+    if !valid_signature {
+        return Err(BlockError::InvalidProposerSignature);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use eternal_core::error::{BlockError, LookupError, ProofError, SpecError};
+#[cfg(feature = "rpc")]
+use eternal_networking::jsonrpc::ClientError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LightError {
+    #[error("Invalid chain spec")]
+    Spec(#[from] SpecError),
+
+    #[error("Headers of proof of stake chains can not be checked without the state")]
+    UnsupportedConsensus,
+
+    #[error("The authority {0} is not part of the genesis state")]
+    UnknownAuthority(String),
+
+    #[error("Invalid header at height {height}")]
+    Header { height: usize, source: BlockError },
+
+    #[error("The authorities of block {0} did not come from the votes of its parent")]
+    AuthorityChange(usize),
+
+    #[error("The header at height {0} was not followed yet")]
+    UnknownHeight(usize),
+
+    #[error("The full node does not serve the data of block {0}")]
+    Unavailable(usize),
+
+    #[error("Invalid proof")]
+    Proof(#[from] ProofError),
+
+    #[error("Lookup failed")]
+    Lookup(#[from] LookupError),
+
+    #[cfg(feature = "rpc")]
+    #[error("Request to the full node failed")]
+    Rpc(#[from] ClientError),
+}
+
+impl LightError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            LightError::UnsupportedConsensus => 6021573941,
+            LightError::UnknownAuthority(_) => 6021573942,
+            LightError::Header { .. } => 6021573943,
+            LightError::AuthorityChange(_) => 6021573944,
+            LightError::UnknownHeight(_) => 6021573945,
+            LightError::Unavailable(_) => 6021573946,
+            LightError::Spec(err) => err.code(),
+            LightError::Proof(err) => err.code(),
+            LightError::Lookup(err) => err.code(),
+            #[cfg(feature = "rpc")]
+            LightError::Rpc(err) => err.code(),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! A light client following only the block headers of a chain.
+//!
+//! Every header is checked to link to its predecessor and to be sealed by the rules of
+//! the chain: proof of work headers have to carry the work, dev and proof of authority
+//! headers the signature of the expected authority. Transactions and accounts are then
+//! fetched from a full node together with Merkle proofs against the checked headers,
+//! see [`eternal_core::proof`], so the full node does not have to be trusted.
+//!
+//! Proof of authority headers are checked against the authorities their state root
+//! commits to, fetched for every header, so the full node has to keep the state of the
+//! followed blocks. Proof of stake chains are not supported, the proposer of a block
+//! depends on the stake of every account.
+//!
+//! The `rpc` feature makes the JSON-RPC client of `eternal-networking` a [`Provider`].
+//! It is off by default, as that crate also brings the peer-to-peer stack.
+
+mod client;
+mod error;
+mod provider;
+
+pub use client::{LightClient, MAX_HEADERS};
+pub use error::LightError;
+pub use provider::Provider;
@@ -0,0 +1,111 @@
+use eternal_account::StoreKey;
+use eternal_core::{
+    block::{Block, BlockHeader},
+    blockchain::Blockchain,
+    proof::{AccountProof, AuthorityProof, TransactionProof},
+};
+#[cfg(feature = "rpc")]
+use eternal_networking::jsonrpc::Client;
+#[cfg(feature = "rpc")]
+use serde_json::json;
+
+use crate::error::LightError;
+
+/// A full node serving the headers and proofs, nothing it returns is trusted
+pub trait Provider {
+    /// Will return the height of the full node's head
+    fn head(&self) -> Result<usize, LightError>;
+
+    /// Will return up to `count` headers starting at height `from`
+    fn headers(&self, from: usize, count: usize) -> Result<Vec<BlockHeader>, LightError>;
+
+    /// Will return the block at the given height
+    fn block(&self, height: usize) -> Result<Option<Block>, LightError>;
+
+    /// Will return the transaction with a proof of its inclusion in a block
+    fn transaction_proof(&self, hash: &str) -> Result<Option<TransactionProof>, LightError>;
+
+    /// Will return the account, the store entries of the given keys and the balances of
+    /// the given token contracts as of the block at the given height with a proof
+    fn account_proof(
+        &self,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+        height: usize,
+    ) -> Result<Option<AccountProof>, LightError>;
+
+    /// Will return the authorities committed to by the block at the given height
+    fn authority_proof(&self, height: usize) -> Result<Option<AuthorityProof>, LightError>;
+}
+
+/// A node reached over JSON-RPC
+#[cfg(feature = "rpc")]
+impl Provider for Client {
+    fn head(&self) -> Result<usize, LightError> {
+        Ok(self.call("eternal_blockNumber", json!([]))?)
+    }
+
+    fn headers(&self, from: usize, count: usize) -> Result<Vec<BlockHeader>, LightError> {
+        Ok(self.call("eternal_getHeaders", json!([from, count]))?)
+    }
+
+    fn block(&self, height: usize) -> Result<Option<Block>, LightError> {
+        Ok(self.call("eternal_getBlockByNumber", json!([height]))?)
+    }
+
+    fn transaction_proof(&self, hash: &str) -> Result<Option<TransactionProof>, LightError> {
+        Ok(self.call("eternal_getTransactionProof", json!([hash]))?)
+    }
+
+    fn account_proof(
+        &self,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+        height: usize,
+    ) -> Result<Option<AccountProof>, LightError> {
+        Ok(self.call(
+            "eternal_getProof",
+            json!([address, keys, contracts, height]),
+        )?)
+    }
+
+    fn authority_proof(&self, height: usize) -> Result<Option<AuthorityProof>, LightError> {
+        Ok(self.call("eternal_getAuthorityProof", json!([height]))?)
+    }
+}
+
+/// A chain held in memory, e.g. of a node running in the same process
+impl Provider for Blockchain {
+    fn head(&self) -> Result<usize, LightError> {
+        Ok(self.len() - 1)
+    }
+
+    fn headers(&self, from: usize, count: usize) -> Result<Vec<BlockHeader>, LightError> {
+        let to = from.saturating_add(count).min(self.len());
+        Ok(self.headers.get(from..to).unwrap_or_default().to_vec())
+    }
+
+    fn block(&self, height: usize) -> Result<Option<Block>, LightError> {
+        Ok(self.get_block_by_height(height)?.cloned())
+    }
+
+    fn transaction_proof(&self, hash: &str) -> Result<Option<TransactionProof>, LightError> {
+        Ok(self.prove_transaction(hash)?)
+    }
+
+    fn account_proof(
+        &self,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+        height: usize,
+    ) -> Result<Option<AccountProof>, LightError> {
+        Ok(self.prove_account(address, keys, contracts, height)?)
+    }
+
+    fn authority_proof(&self, height: usize) -> Result<Option<AuthorityProof>, LightError> {
+        Ok(self.prove_authorities(height)?)
+    }
+}
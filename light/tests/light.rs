@@ -0,0 +1,259 @@
+use eternal_core::{
+    account::{StoreKey, Wallet},
+    block::{Block, BlockHeader},
+    blockchain::{Blockchain, Consensus},
+    consensus::AuthoritySet,
+    devnet::Devnet,
+    error::{BlockError, ProofError},
+    limits::Limits,
+    proof::{AccountProof, AuthorityProof, TransactionProof},
+    spec::{dev_wallets, Allocation, ChainSpec},
+    transaction::TransactionData,
+};
+use eternal_light::{LightClient, LightError, Provider};
+
+/// Will produce a block per transaction, each sent by the first wallet
+fn produce(devnet: &mut Devnet, wallet: &Wallet, data: Vec<TransactionData>) {
+    for data in data {
+        devnet.send(wallet, data).unwrap();
+        devnet.step().unwrap().unwrap();
+    }
+}
+
+/// A dev chain with 3 transfers from bob to alice
+fn dev_chain() -> (ChainSpec, Blockchain, Vec<Wallet>) {
+    let spec = ChainSpec::dev();
+    let wallets = dev_wallets(2);
+    let alice = wallets[1].address().to_string();
+
+    let chain = Blockchain::from_spec(&spec).unwrap();
+    let mut devnet = Devnet::new(chain, vec![wallets[0].clone()]);
+    let transfers = (1..=3)
+        .map(|amount| TransactionData::Transfer {
+            to: alice.clone(),
+            amount,
+        })
+        .collect();
+    produce(&mut devnet, &wallets[0], transfers);
+
+    (spec, devnet.chain, wallets)
+}
+
+/// A proof of authority chain started by bob, who votes alice in with the first block
+fn poa_chain() -> (ChainSpec, Blockchain, Vec<Wallet>) {
+    let wallets = dev_wallets(2);
+    let spec = ChainSpec {
+        name: "light".to_string(),
+        chain_id: 1,
+        genesis_timestamp: 0,
+        consensus: Consensus::ProofOfAuthority {
+            authorities: vec![wallets[0].public_key().to_string()],
+            period_ms: 0,
+        },
+        limits: Limits::default(),
+        allocations: wallets
+            .iter()
+            .map(|wallet| Allocation {
+                public_key: wallet.public_key().to_string(),
+                tokens: 1_000,
+                stake: 0,
+            })
+            .collect(),
+        contracts: Vec::new(),
+        genesis_hash: None,
+    };
+
+    let chain = Blockchain::from_spec(&spec).unwrap();
+    let mut devnet = Devnet::new(chain, wallets.clone());
+    let alice = wallets[1].address().to_string();
+    let mut transactions = vec![TransactionData::VoteAuthority {
+        public_key: wallets[1].public_key().to_string(),
+        add: true,
+    }];
+    transactions.extend((1..=3).map(|amount| TransactionData::Transfer {
+        to: alice.clone(),
+        amount,
+    }));
+    produce(&mut devnet, &wallets[0], transactions);
+
+    (spec, devnet.chain, wallets)
+}
+
+/// Serves the chain, but lies about the authorities or withholds the blocks
+struct Dishonest<'a> {
+    chain: &'a Blockchain,
+    authorities: Option<AuthoritySet>,
+    withhold_blocks: bool,
+}
+
+impl Provider for Dishonest<'_> {
+    fn head(&self) -> Result<usize, LightError> {
+        self.chain.head()
+    }
+
+    fn headers(&self, from: usize, count: usize) -> Result<Vec<BlockHeader>, LightError> {
+        self.chain.headers(from, count)
+    }
+
+    fn block(&self, height: usize) -> Result<Option<Block>, LightError> {
+        if self.withhold_blocks {
+            return Ok(None);
+        }
+        self.chain.block(height)
+    }
+
+    fn transaction_proof(&self, hash: &str) -> Result<Option<TransactionProof>, LightError> {
+        self.chain.transaction_proof(hash)
+    }
+
+    fn account_proof(
+        &self,
+        address: &str,
+        keys: &[StoreKey],
+        contracts: &[String],
+        height: usize,
+    ) -> Result<Option<AccountProof>, LightError> {
+        self.chain.account_proof(address, keys, contracts, height)
+    }
+
+    fn authority_proof(&self, height: usize) -> Result<Option<AuthorityProof>, LightError> {
+        let mut proof = self.chain.authority_proof(height)?;
+        if let (Some(proof), Some(authorities)) = (&mut proof, &self.authorities) {
+            proof.authorities = authorities.clone();
+        }
+        Ok(proof)
+    }
+}
+
+#[test]
+fn follows_a_dev_chain_and_checks_proofs() {
+    let (spec, chain, wallets) = dev_chain();
+    let alice = wallets[1].address().to_string();
+
+    let mut client = LightClient::new(&spec).unwrap();
+    assert_eq!(client.sync(&chain).unwrap(), 3);
+    assert_eq!(client.head(), &chain.headers[3]);
+
+    let hash = chain.blocks[2].transactions[0].hash();
+    let proof = client.transaction(&chain, &hash).unwrap().unwrap();
+    assert_eq!(proof.height, 2);
+    assert_eq!(proof.transaction.hash(), hash);
+    assert!(client.transaction(&chain, "00").unwrap().is_none());
+
+    assert_eq!(
+        client.balance(&chain, &alice).unwrap(),
+        Some(chain.accounts[&alice].tokens)
+    );
+    assert!(client.account(&chain, "etnl:00").unwrap().is_none());
+
+    // Proofs altered by the full node are rejected
+    let mut tampered = proof.clone();
+    if let TransactionData::Transfer { amount, .. } = &mut tampered.transaction.data {
+        *amount += 1;
+    }
+    assert!(matches!(
+        client.verify_transaction(&tampered),
+        Err(LightError::Proof(ProofError::InvalidProof))
+    ));
+
+    let mut account = chain.prove_account(&alice, &[], &[], 3).unwrap().unwrap();
+    client.verify_account(&account).unwrap();
+    account.account.tokens += 1;
+    assert!(matches!(
+        client.verify_account(&account),
+        Err(LightError::Proof(ProofError::InvalidProof))
+    ));
+
+    // Blocks the client does not follow yet can not be checked
+    let mut ahead = proof;
+    ahead.height = 4;
+    assert!(matches!(
+        client.verify_transaction(&ahead),
+        Err(LightError::UnknownHeight(4))
+    ));
+}
+
+#[test]
+fn forged_headers_are_rejected() {
+    let (spec, chain, wallets) = dev_chain();
+    let mut client = LightClient::new(&spec).unwrap();
+
+    let mut header = chain.headers[1].clone();
+    header.timestamp += 1;
+    assert!(matches!(
+        client.import_header(header, &chain),
+        Err(LightError::Header {
+            height: 1,
+            source: BlockError::HashMismatch
+        })
+    ));
+
+    // Sealed by alice instead of the dev authority
+    let mut block = Block::new(chain.headers[0].hash.clone());
+    block.sign(&wallets[1]);
+    assert!(matches!(
+        client.import_header(block.header(), &chain),
+        Err(LightError::Header {
+            height: 1,
+            source: BlockError::UnexpectedProposer { .. }
+        })
+    ));
+
+    assert!(matches!(
+        client.import_header(chain.headers[2].clone(), &chain),
+        Err(LightError::Header {
+            height: 1,
+            source: BlockError::InvalidPrev
+        })
+    ));
+    assert_eq!(client.height(), 0);
+}
+
+#[test]
+fn follows_authority_changes() {
+    let (spec, chain, wallets) = poa_chain();
+    let alice = wallets[1].address().to_string();
+
+    // Alice seals a block once she was voted in
+    let proposers: Vec<_> = chain.headers[2..]
+        .iter()
+        .filter_map(|header| header.proposer.clone())
+        .collect();
+    assert!(proposers.contains(&alice));
+
+    let mut client = LightClient::new(&spec).unwrap();
+    assert_eq!(client.sync(&chain).unwrap(), chain.len() - 1);
+    assert_eq!(client.balance(&chain, &alice).unwrap(), Some(1_006));
+}
+
+#[test]
+fn forged_authorities_are_rejected() {
+    let (spec, chain, wallets) = poa_chain();
+
+    // Alice is claimed to be an authority from the start
+    let forged = AuthoritySet::new(vec![wallets[1].public_key().to_string()]);
+    let provider = Dishonest {
+        chain: &chain,
+        authorities: Some(forged),
+        withhold_blocks: false,
+    };
+    let mut client = LightClient::new(&spec).unwrap();
+    assert!(matches!(
+        client.sync(&provider),
+        Err(LightError::Proof(ProofError::InvalidProof))
+    ));
+    assert_eq!(client.height(), 0);
+
+    // The votes changing the authorities have to be shown
+    let provider = Dishonest {
+        chain: &chain,
+        authorities: None,
+        withhold_blocks: true,
+    };
+    let mut client = LightClient::new(&spec).unwrap();
+    assert!(matches!(
+        client.sync(&provider),
+        Err(LightError::Unavailable(1))
+    ));
+    assert_eq!(client.height(), 1);
+}
@@ -0,0 +1,109 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{Request, Response, RpcError};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Can not reach the node")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid response")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unexpected HTTP response `{0}`")]
+    Http(String),
+
+    #[error("{0}")]
+    Rpc(RpcError),
+}
+
+impl ClientError {
+    /// Will return the stable numeric code of the error, for errors of the node
+    /// the code of the node's error
+    pub fn code(&self) -> u64 {
+        match self {
+            ClientError::Io(_) => 4410928381,
+            ClientError::Json(_) => 4410928382,
+            ClientError::Http(_) => 4410928383,
+            ClientError::Rpc(err) => err
+                .data
+                .as_ref()
+                .and_then(Value::as_u64)
+                .unwrap_or(4410928384),
+        }
+    }
+}
+
+/// A blocking JSON-RPC client sending every request over a new HTTP connection
+#[derive(Debug)]
+pub struct Client {
+    /// `host:port` of the node
+    addr: String,
+    next_id: AtomicU64,
+}
+
+impl Client {
+    /// Will create a client for `host:port`, an `http://` prefix is ignored
+    pub fn new(url: &str) -> Self {
+        let addr = url.strip_prefix("http://").unwrap_or(url);
+
+        Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, ClientError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = serde_json::to_vec(&Request::new(id, method, params))?;
+
+        let mut stream = TcpStream::connect(&self.addr)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.addr,
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(ClientError::Http(status.trim().to_string()));
+        }
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+
+        // The server closes the connection after the response
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+
+        let response: Response = serde_json::from_slice(&body)?;
+        if let Some(error) = response.error {
+            return Err(ClientError::Rpc(error));
+        }
+
+        Ok(serde_json::from_value(
+            response.result.unwrap_or(Value::Null),
+        )?)
+    }
+}
@@ -0,0 +1,98 @@
+use std::{future::Future, io, sync::Arc};
+
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use super::{Request, Response, RpcError, VERSION};
+
+/// Upper bound of a request including its headers
+const MAX_REQUEST_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Will answer JSON-RPC requests sent as HTTP POST on the listener until `shutdown`
+/// completes. Every connection is closed after its response.
+pub async fn serve<H, S>(listener: TcpListener, handler: H, shutdown: S) -> io::Result<()>
+where
+    H: Fn(Request) -> Response + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    let handler = Arc::new(handler);
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    // A broken connection only affects its own client
+                    let _ = handle_connection(stream, handler.as_ref()).await;
+                });
+            }
+        }
+    }
+}
+
+async fn handle_connection<H>(mut stream: TcpStream, handler: &H) -> io::Result<()>
+where
+    H: Fn(Request) -> Response,
+{
+    let (read, mut write) = stream.split();
+    let mut reader = BufReader::new(read.take(MAX_REQUEST_SIZE));
+
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let is_post = line.starts_with("POST ");
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    if !is_post {
+        let response = "HTTP/1.1 405 Method Not Allowed\r\nAllow: POST\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        return write.write_all(response.as_bytes()).await;
+    }
+
+    // The body could never be read in full, refuse before allocating it
+    if content_length as u64 > MAX_REQUEST_SIZE {
+        let response =
+            "HTTP/1.1 413 Payload Too Large\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        return write.write_all(response.as_bytes()).await;
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let response = match serde_json::from_slice::<Request>(&body) {
+        Ok(request) if request.jsonrpc != VERSION => Response::error(
+            request.id,
+            RpcError::new(RpcError::INVALID_REQUEST, "Only JSON-RPC 2.0 is supported"),
+        ),
+        Ok(request) => handler(request),
+        Err(err) => Response::error(
+            Value::Null,
+            RpcError::new(RpcError::PARSE_ERROR, err.to_string()),
+        ),
+    };
+    let body = serde_json::to_vec(&response)?;
+
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    write.write_all(head.as_bytes()).await?;
+    write.write_all(&body).await?;
+    write.flush().await
+}
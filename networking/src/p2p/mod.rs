@@ -1,12 +1,18 @@
-use eternal_account::{Account, AccountType};
+mod network;
+
+use eternal_account::Wallet;
 use libp2p::{floodsub::Topic, PeerId};
 use once_cell::sync::Lazy;
 
-pub static KEYS: Lazy<Account> = Lazy::new(|| Account::new(AccountType::Node));
+pub use libp2p::{multiaddr, Multiaddr};
+pub use network::{Network, NetworkEvent, P2pError};
+
+pub static KEYS: Lazy<Wallet> = Lazy::new(Wallet::generate);
 pub static PEER_ID: Lazy<PeerId> =
-    Lazy::new(|| PeerId::from_bytes(KEYS.public_key_bytes.as_slice()).unwrap());
+    Lazy::new(|| PeerId::from_bytes(&KEYS.public_key().serialize()).unwrap());
 pub static CHAIN_TOPIC: Lazy<Topic> = Lazy::new(|| Topic::new("chains"));
 pub static BLOCK_TOPIC: Lazy<Topic> = Lazy::new(|| Topic::new("blocks"));
+pub static TRANSACTION_TOPIC: Lazy<Topic> = Lazy::new(|| Topic::new("transactions"));
 
 pub fn print_peer_id() {
     println!("{}", PEER_ID.to_base58())
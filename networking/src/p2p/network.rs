@@ -0,0 +1,164 @@
+use std::io;
+
+use libp2p::{
+    core::upgrade,
+    floodsub::{Floodsub, FloodsubEvent, Topic},
+    futures::StreamExt,
+    identity, mdns, noise,
+    swarm::{DialError, NetworkBehaviour, SwarmBuilder, SwarmEvent},
+    tcp, yamux, Multiaddr, PeerId, Swarm, Transport, TransportError,
+};
+use thiserror::Error;
+
+use super::{BLOCK_TOPIC, TRANSACTION_TOPIC};
+
+#[derive(NetworkBehaviour)]
+struct Behaviour {
+    floodsub: Floodsub,
+    mdns: mdns::tokio::Behaviour,
+}
+
+/// What happened on the network, gossiped data is passed on undecoded
+#[derive(Debug)]
+pub enum NetworkEvent {
+    Listening(Multiaddr),
+    Block { source: PeerId, data: Vec<u8> },
+    Transaction { source: PeerId, data: Vec<u8> },
+    PeerConnected(PeerId),
+    PeerDisconnected(PeerId),
+}
+
+#[derive(Debug, Error)]
+pub enum P2pError {
+    #[error("Can not set up the noise handshake")]
+    Noise(#[from] noise::Error),
+
+    #[error("Can not start mDNS discovery")]
+    Mdns(#[source] io::Error),
+
+    #[error("Can not listen on the address")]
+    Listen(#[from] TransportError<io::Error>),
+
+    #[error("Can not dial the peer")]
+    Dial(#[from] DialError),
+}
+
+impl P2pError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            P2pError::Noise(_) => 4410928371,
+            P2pError::Mdns(_) => 4410928372,
+            P2pError::Listen(_) => 4410928373,
+            P2pError::Dial(_) => 4410928374,
+        }
+    }
+}
+
+/// Gossips blocks and transactions over floodsub, peers in the local network are
+/// found through mDNS, others have to be given as bootnodes
+pub struct Network {
+    swarm: Swarm<Behaviour>,
+}
+
+impl Network {
+    /// Will listen on the address and dial the bootnodes, the peer id is new on every start
+    pub fn new(listen: Multiaddr, bootnodes: &[Multiaddr]) -> Result<Self, P2pError> {
+        let keypair = identity::Keypair::generate_ed25519();
+        let local_peer_id = PeerId::from(keypair.public());
+
+        let transport = tcp::tokio::Transport::new(tcp::Config::default().nodelay(true))
+            .upgrade(upgrade::Version::V1)
+            .authenticate(noise::Config::new(&keypair)?)
+            .multiplex(yamux::Config::default())
+            .boxed();
+
+        let mut floodsub = Floodsub::new(local_peer_id);
+        floodsub.subscribe(BLOCK_TOPIC.clone());
+        floodsub.subscribe(TRANSACTION_TOPIC.clone());
+        let behaviour = Behaviour {
+            floodsub,
+            mdns: mdns::tokio::Behaviour::new(mdns::Config::default(), local_peer_id)
+                .map_err(P2pError::Mdns)?,
+        };
+
+        let mut swarm =
+            SwarmBuilder::with_tokio_executor(transport, behaviour, local_peer_id).build();
+        swarm.listen_on(listen)?;
+        for bootnode in bootnodes {
+            swarm.dial(bootnode.clone())?;
+        }
+
+        Ok(Self { swarm })
+    }
+
+    pub fn local_peer_id(&self) -> &PeerId {
+        self.swarm.local_peer_id()
+    }
+
+    pub fn publish_block(&mut self, data: Vec<u8>) {
+        self.publish(BLOCK_TOPIC.clone(), data)
+    }
+
+    pub fn publish_transaction(&mut self, data: Vec<u8>) {
+        self.publish(TRANSACTION_TOPIC.clone(), data)
+    }
+
+    fn publish(&mut self, topic: Topic, data: Vec<u8>) {
+        self.swarm.behaviour_mut().floodsub.publish(topic, data);
+    }
+
+    /// Will drive the network until something of interest happens
+    pub async fn next_event(&mut self) -> NetworkEvent {
+        loop {
+            let event = match self.swarm.select_next_some().await {
+                SwarmEvent::NewListenAddr { address, .. } => NetworkEvent::Listening(address),
+                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    let floodsub = &mut self.swarm.behaviour_mut().floodsub;
+                    floodsub.add_node_to_partial_view(peer_id);
+                    NetworkEvent::PeerConnected(peer_id)
+                }
+                SwarmEvent::ConnectionClosed {
+                    peer_id,
+                    num_established: 0,
+                    ..
+                } => {
+                    let floodsub = &mut self.swarm.behaviour_mut().floodsub;
+                    floodsub.remove_node_from_partial_view(&peer_id);
+                    NetworkEvent::PeerDisconnected(peer_id)
+                }
+                SwarmEvent::Behaviour(BehaviourEvent::Mdns(mdns::Event::Discovered(peers))) => {
+                    for (peer_id, address) in peers {
+                        // A failed dial is retried once the peer is rediscovered
+                        let _ = self.swarm.dial(address);
+                        self.swarm
+                            .behaviour_mut()
+                            .floodsub
+                            .add_node_to_partial_view(peer_id);
+                    }
+                    continue;
+                }
+                SwarmEvent::Behaviour(BehaviourEvent::Floodsub(FloodsubEvent::Message(
+                    message,
+                ))) => {
+                    if message.topics.contains(&BLOCK_TOPIC) {
+                        NetworkEvent::Block {
+                            source: message.source,
+                            data: message.data.to_vec(),
+                        }
+                    } else if message.topics.contains(&TRANSACTION_TOPIC) {
+                        NetworkEvent::Transaction {
+                            source: message.source,
+                            data: message.data.to_vec(),
+                        }
+                    } else {
+                        continue;
+                    }
+                }
+                _ => continue,
+            };
+
+            return event;
+        }
+    }
+}
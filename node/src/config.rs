@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use eternal_core::pruning::{Pruning, DEFAULT_RECENT_BLOCKS};
+use serde::Deserialize;
+
+use crate::error::NodeError;
+
+/// The config file used if none is given
+pub const DEFAULT_CONFIG: &str = "eternal.toml";
+
+/// Prefix of the environment variables overriding the config file
+pub const ENV_PREFIX: &str = "ETERNAL_";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Holds the chain spec and the blocks
+    pub data_dir: PathBuf,
+    pub log: LogConfig,
+    pub rpc: RpcConfig,
+    pub p2p: P2pConfig,
+    pub snapshots: SnapshotConfig,
+    /// What is kept of old blocks and their state, pruned nodes need snapshots
+    pub pruning: Pruning,
+    /// Blocks are only produced if a validator key is configured
+    pub validator: Option<ValidatorConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// A tracing filter, e.g. `info` or `eternal_node=debug`
+    pub level: String,
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    pub enabled: bool,
+    pub addr: SocketAddr,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct P2pConfig {
+    pub enabled: bool,
+    /// Multiaddress to listen on
+    pub listen: String,
+    /// Multiaddresses of the peers dialed on start
+    pub bootnodes: Vec<String>,
+    /// JSON-RPC addresses of the peers to catch up from when the parent of a gossiped
+    /// block is missing, e.g. `127.0.0.1:8545`
+    pub sync_peers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnapshotConfig {
+    /// A snapshot of the state is taken every this many blocks, never if 0
+    pub interval: usize,
+    /// The amount of snapshots kept in the data directory
+    pub keep: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorConfig {
+    /// Encrypted keystore of the sealing key, the password is read from
+    /// `ETERNAL_VALIDATOR_PASSWORD` or asked for on start
+    pub keystore: PathBuf,
+    /// The time between two blocks in milliseconds, proof of authority chains
+    /// use at least their period
+    #[serde(default = "default_block_time_ms")]
+    pub block_time_ms: u64,
+}
+
+fn default_block_time_ms() -> u64 {
+    1000
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("data"),
+            log: LogConfig::default(),
+            rpc: RpcConfig::default(),
+            p2p: P2pConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            pruning: Pruning::Full {
+                state: DEFAULT_RECENT_BLOCKS,
+            },
+            validator: None,
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            addr: SocketAddr::from(([127, 0, 0, 1], 8545)),
+        }
+    }
+}
+
+impl Default for P2pConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen: "/ip4/0.0.0.0/tcp/30333".to_string(),
+            bootnodes: Vec::new(),
+            sync_peers: Vec::new(),
+        }
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1000,
+            keep: 2,
+        }
+    }
+}
+
+impl Config {
+    /// Will read the config file and apply the `ETERNAL_*` environment variables on top.
+    /// A missing file is only an error if it was given explicitly.
+    pub fn load(path: Option<&Path>) -> Result<Self, NodeError> {
+        let mut config = match path {
+            Some(path) => Self::from_toml(&fs::read_to_string(path)?)?,
+            None => match fs::read_to_string(DEFAULT_CONFIG) {
+                Ok(content) => Self::from_toml(&content)?,
+                Err(_) => Self::default(),
+            },
+        };
+
+        config.apply_env(std::env::vars())?;
+        config.check()?;
+        Ok(config)
+    }
+
+    /// Checks that the settings fit together
+    pub fn check(&self) -> Result<(), NodeError> {
+        // Pruned blocks can only be skipped on start from a snapshot
+        if self.pruning.prunes_blocks() && self.snapshots.interval == 0 {
+            return Err(NodeError::PruningWithoutSnapshots);
+        }
+
+        Ok(())
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, NodeError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Will override the config with the given `ETERNAL_*` variables, others are ignored
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<(), NodeError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let vars: HashMap<String, String> = vars
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix(ENV_PREFIX)?.to_string(), value)))
+            .collect();
+        let var = |key: &str| vars.get(key).cloned();
+
+        if let Some(value) = var("DATA_DIR") {
+            self.data_dir = PathBuf::from(value);
+        }
+        if let Some(value) = var("LOG_LEVEL") {
+            self.log.level = value;
+        }
+        if let Some(value) = var("LOG_FORMAT") {
+            self.log.format = parse_env("LOG_FORMAT", value)?;
+        }
+        if let Some(value) = var("RPC_ENABLED") {
+            self.rpc.enabled = parse_env("RPC_ENABLED", value)?;
+        }
+        if let Some(value) = var("RPC_ADDR") {
+            self.rpc.addr = parse_env("RPC_ADDR", value)?;
+        }
+        if let Some(value) = var("P2P_ENABLED") {
+            self.p2p.enabled = parse_env("P2P_ENABLED", value)?;
+        }
+        if let Some(value) = var("P2P_LISTEN") {
+            self.p2p.listen = value;
+        }
+        if let Some(value) = var("P2P_BOOTNODES") {
+            self.p2p.bootnodes = parse_list(&value);
+        }
+        if let Some(value) = var("P2P_SYNC_PEERS") {
+            self.p2p.sync_peers = parse_list(&value);
+        }
+        if let Some(value) = var("SNAPSHOT_INTERVAL") {
+            self.snapshots.interval = parse_env("SNAPSHOT_INTERVAL", value)?;
+        }
+        if let Some(value) = var("PRUNING") {
+            self.pruning = parse_pruning(&value).ok_or_else(|| NodeError::InvalidEnv {
+                name: format!("{}PRUNING", ENV_PREFIX),
+                value,
+            })?;
+        }
+        if let Some(value) = var("VALIDATOR_KEYSTORE") {
+            let keystore = PathBuf::from(value);
+            match &mut self.validator {
+                Some(validator) => validator.keystore = keystore,
+                None => {
+                    self.validator = Some(ValidatorConfig {
+                        keystore,
+                        block_time_ms: default_block_time_ms(),
+                    })
+                }
+            }
+        }
+        if let Some(value) = var("BLOCK_TIME_MS") {
+            let block_time_ms = parse_env("BLOCK_TIME_MS", value.clone())?;
+            match &mut self.validator {
+                Some(validator) => validator.block_time_ms = block_time_ms,
+                // Without a validator no blocks are produced
+                None => {
+                    return Err(NodeError::InvalidEnv {
+                        name: format!("{}BLOCK_TIME_MS", ENV_PREFIX),
+                        value,
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Will parse a comma separated list, empty entries are skipped
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Will parse a pruning mode followed by the amount of recent blocks, e.g. `pruned:64`
+fn parse_pruning(value: &str) -> Option<Pruning> {
+    let (mode, recent) = match value.split_once(':') {
+        Some((mode, recent)) => (mode, Some(recent.parse().ok()?)),
+        None => (value, None),
+    };
+
+    let recent_or_default = recent.unwrap_or(DEFAULT_RECENT_BLOCKS);
+    match (mode, recent) {
+        ("archive", None) => Some(Pruning::Archive),
+        ("full", _) => Some(Pruning::Full {
+            state: recent_or_default,
+        }),
+        ("pruned", _) => Some(Pruning::Pruned {
+            blocks: recent_or_default,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, value: String) -> Result<T, NodeError> {
+    value.parse().map_err(|_| NodeError::InvalidEnv {
+        name: format!("{}{}", ENV_PREFIX, key),
+        value,
+    })
+}
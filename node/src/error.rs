@@ -0,0 +1,132 @@
+use std::{error::Error as _, io, path::PathBuf};
+
+use eternal_account::KeyError;
+use eternal_core::{
+    error::{BlockError, LookupError, SnapshotError, SpecError, StorageError},
+    replay::Divergence,
+};
+use eternal_networking::{jsonrpc::ClientError, p2p::P2pError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NodeError {
+    #[error("IO error")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid config file")]
+    Config(#[from] toml::de::Error),
+
+    #[error("Invalid JSON")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid value `{value}` of the environment variable {name}")]
+    InvalidEnv { name: String, value: String },
+
+    #[error("Invalid multiaddress `{0}`")]
+    InvalidMultiaddr(String),
+
+    #[error("The data directory {0} is already initialized")]
+    AlreadyInitialized(PathBuf),
+
+    #[error("The data directory {0} is not initialized, run `eternal-node init` first")]
+    NotInitialized(PathBuf),
+
+    #[error("Invalid chain spec")]
+    Spec(#[from] SpecError),
+
+    #[error("Block storage error")]
+    Storage(#[from] StorageError),
+
+    #[error("Invalid block")]
+    Block(#[from] BlockError),
+
+    #[error("Can not load the key")]
+    Key(#[from] KeyError),
+
+    #[error("Networking error")]
+    P2p(#[from] P2pError),
+
+    #[error("The state diverges at {0}")]
+    Diverged(Box<Divergence>),
+
+    #[error("The data directory already holds blocks")]
+    NotEmpty,
+
+    #[error("The block of the snapshot the node was started from is missing")]
+    MissingSnapshotBlock,
+
+    #[error("Only the blocks from height {0} on are stored")]
+    BlocksNotStored(usize),
+
+    #[error("The peer does not serve the block at height {0}")]
+    BlockUnavailable(usize),
+
+    #[error("The peer is on chain {actual} instead of {expected}")]
+    WrongChain { expected: u64, actual: u64 },
+
+    #[error("Invalid snapshot")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Request to the peer failed")]
+    Rpc(#[from] ClientError),
+
+    #[error("Pruning blocks needs snapshots, set a snapshot interval")]
+    PruningWithoutSnapshots,
+
+    #[error("Lookup failed")]
+    Lookup(#[from] LookupError),
+
+    #[error("Invalid block range {from}..={to}, the head is at height {height}")]
+    InvalidRange {
+        from: usize,
+        to: usize,
+        height: usize,
+    },
+
+    #[error("The parent `{0}` of the block is not part of the chain")]
+    UnknownParent(String),
+}
+
+impl NodeError {
+    /// Will return the message of the error followed by the messages of its sources
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            report.push_str(": ");
+            report.push_str(&err.to_string());
+            source = err.source();
+        }
+        report
+    }
+
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            NodeError::Io(_) => 3318820401,
+            NodeError::Config(_) => 3318820402,
+            NodeError::InvalidEnv { .. } => 3318820403,
+            NodeError::InvalidMultiaddr(_) => 3318820404,
+            NodeError::AlreadyInitialized(_) => 3318820405,
+            NodeError::NotInitialized(_) => 3318820406,
+            NodeError::Json(_) => 3318820407,
+            NodeError::Diverged(_) => 3318820408,
+            NodeError::NotEmpty => 3318820409,
+            NodeError::MissingSnapshotBlock => 3318820410,
+            NodeError::BlocksNotStored(_) => 3318820411,
+            NodeError::BlockUnavailable(_) => 3318820412,
+            NodeError::WrongChain { .. } => 3318820413,
+            NodeError::PruningWithoutSnapshots => 3318820414,
+            NodeError::InvalidRange { .. } => 3318820415,
+            NodeError::UnknownParent(_) => 3318820416,
+            NodeError::Lookup(err) => err.code(),
+            NodeError::Snapshot(err) => err.code(),
+            NodeError::Rpc(err) => err.code(),
+            NodeError::Spec(err) => err.code(),
+            NodeError::Storage(err) => err.code(),
+            NodeError::Block(err) => err.code(),
+            NodeError::Key(err) => err.code(),
+            NodeError::P2p(err) => err.code(),
+        }
+    }
+}
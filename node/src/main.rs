@@ -0,0 +1,228 @@
+mod config;
+mod error;
+mod node;
+mod rpc;
+mod run;
+mod storage;
+mod sync;
+
+use std::{fs, path::PathBuf, process, sync::Mutex};
+
+use clap::{Parser, Subcommand};
+use eternal_account::{Keystore, Wallet};
+use eternal_core::{
+    replay::{self, BlockTrace},
+    spec::{dev_wallets, ChainSpec},
+    storage::BlockLog,
+};
+use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
+use zeroize::Zeroizing;
+
+use crate::{
+    config::{Config, LogFormat},
+    error::NodeError,
+    node::Node,
+    storage::Storage,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "eternal-node", version, about = "A node of the eternal chain")]
+struct Cli {
+    /// The config file, `eternal.toml` is used if it exists
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Overrides the data directory of the config
+    #[arg(short, long, global = true)]
+    data_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Creates the data directory with the genesis block of a chain spec
+    Init {
+        /// A `.toml` or `.json` chain spec, the development chain if omitted
+        spec: Option<PathBuf>,
+    },
+    /// Runs the node until SIGINT
+    Run,
+    /// Catches up with a peer, an empty node starts from the peer's latest snapshot
+    Sync {
+        /// JSON-RPC address of the peer, e.g. `127.0.0.1:8545`
+        peer: String,
+    },
+    /// Appends the blocks of a block file to the chain, known blocks are skipped
+    Import { file: PathBuf },
+    /// Writes the blocks of the chain to a block file
+    Export {
+        file: PathBuf,
+        /// Height of the first block
+        #[arg(long, default_value_t = 0)]
+        from: usize,
+        /// Height of the last block, the head if omitted
+        #[arg(long)]
+        to: Option<usize>,
+    },
+    /// Re-executes the stored blocks from the genesis state and reports the first block
+    /// whose state root does not match
+    Replay {
+        /// Writes the state root after every transaction to this file
+        #[arg(long)]
+        trace: Option<PathBuf>,
+        /// Compares with the trace of another node to find the diverging transaction
+        #[arg(long)]
+        compare: Option<PathBuf>,
+    },
+    /// Creates a new key and stores it in an encrypted keystore
+    Keygen {
+        #[arg(short, long, default_value = "keystore.json")]
+        output: PathBuf,
+        /// Stores the development account with this index instead of a new key
+        #[arg(long, value_name = "INDEX")]
+        dev: Option<u32>,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let mut config = match Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{} ({})", err.report(), err.code());
+            process::exit(1);
+        }
+    };
+    if let Some(data_dir) = cli.data_dir {
+        config.data_dir = data_dir;
+    }
+    init_logging(&config);
+
+    if let Err(err) = execute(cli.command, config) {
+        error!(code = err.code(), "{}", err.report());
+        process::exit(1);
+    }
+}
+
+fn init_logging(config: &Config) {
+    let filter = EnvFilter::try_new(&config.log.level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match config.log.format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+fn execute(command: Command, config: Config) -> Result<(), NodeError> {
+    match command {
+        Command::Init { spec } => {
+            let spec = match spec {
+                Some(path) => ChainSpec::load(path)?,
+                None => ChainSpec::dev(),
+            };
+            Storage::init(&config.data_dir, &spec)?;
+
+            info!(
+                chain = %spec.name,
+                genesis = spec.genesis_block()?.hash.unwrap_or_default(),
+                data_dir = %config.data_dir.display(),
+                "Initialized"
+            );
+        }
+        Command::Run => {
+            let runtime = tokio::runtime::Runtime::new()?;
+            runtime.block_on(run::run(config))?;
+        }
+        Command::Sync { peer } => {
+            let node = Mutex::new(Node::open(&config)?);
+            sync::sync(&node, &peer)?;
+        }
+        Command::Import { file } => {
+            let mut node = Node::open(&config)?;
+
+            let mut imported = 0;
+            for block in BlockLog::read(&file)? {
+                if !node.contains(&block) {
+                    node.import_block(block)?;
+                    imported += 1;
+                }
+            }
+            node.flush()?;
+
+            info!(imported, height = node.height(), "Imported blocks");
+        }
+        Command::Export { file, from, to } => {
+            let node = Node::open(&config)?;
+
+            let first = node.chain.first_block_height();
+            if from < first {
+                return Err(NodeError::BlocksNotStored(first));
+            }
+            let height = node.height();
+            let to = to.unwrap_or(height);
+            if from > to || to > height {
+                return Err(NodeError::InvalidRange { from, to, height });
+            }
+            let blocks = &node.chain.blocks[from - first..=to - first];
+            BlockLog::write(&file, blocks)?;
+
+            info!(exported = blocks.len(), file = %file.display(), "Exported blocks");
+        }
+        Command::Replay { trace, compare } => {
+            let (storage, spec, blocks) = Storage::open(&config.data_dir)?;
+            if let Some(base) = storage.base() {
+                return Err(NodeError::BlocksNotStored(base));
+            }
+            let replay = replay::replay(&spec, &blocks)?;
+
+            if let Some(path) = &trace {
+                fs::write(path, serde_json::to_string_pretty(&replay.trace)?)?;
+            }
+            let mut divergence = None;
+            if let Some(path) = &compare {
+                let reference: Vec<BlockTrace> = serde_json::from_str(&fs::read_to_string(path)?)?;
+                divergence = replay::compare(&replay.trace, &reference);
+            }
+            if let Some(divergence) = divergence.or(replay.divergence) {
+                return Err(NodeError::Diverged(Box::new(divergence)));
+            }
+
+            info!(
+                height = replay.chain.len() - 1,
+                state_root = replay.chain.state_root(),
+                "Replayed blocks"
+            );
+        }
+        Command::Keygen { output, dev } => {
+            let wallet = match dev {
+                Some(index) => dev_wallets(index + 1).remove(index as usize),
+                None => Wallet::generate(),
+            };
+            let password = read_password("ETERNAL_PASSWORD", "Password: ")?;
+            Keystore::encrypt(&wallet, &password)?.save(&output)?;
+
+            info!(
+                address = %wallet.address(),
+                public_key = %wallet.public_key(),
+                keystore = %output.display(),
+                "Created key"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Will read a password from the environment variable or ask for it on the terminal
+pub(crate) fn read_password(var: &str, prompt: &str) -> Result<Zeroizing<String>, NodeError> {
+    if let Ok(password) = std::env::var(var) {
+        return Ok(Zeroizing::new(password));
+    }
+
+    Ok(Zeroizing::new(rpassword::prompt_password(prompt)?))
+}
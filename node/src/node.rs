@@ -0,0 +1,316 @@
+use std::sync::{Mutex, MutexGuard};
+
+use eternal_account::Wallet;
+use eternal_core::{
+    block::{Block, BlockHeader},
+    blockchain::{Blockchain, Consensus},
+    consensus::ProofOfWork,
+    pruning::Pruning,
+    snapshot::{Snapshot, SnapshotManifest},
+    spec::ChainSpec,
+    transaction::Transaction,
+};
+use tracing::{debug, info};
+
+use crate::{config::Config, error::NodeError, storage::Storage};
+
+/// The chain of a node together with the storage its blocks are written to
+#[derive(Debug)]
+pub struct Node {
+    pub chain: Blockchain,
+    /// The spec the data directory was initialized with
+    spec: ChainSpec,
+    storage: Storage,
+    /// The amount of snapshots kept in the storage
+    keep_snapshots: usize,
+}
+
+impl Node {
+    /// Will rebuild the chain by re-executing the stored blocks on top of the genesis
+    /// state, or of the snapshot the node was started from or pruned to
+    pub fn open(config: &Config) -> Result<Self, NodeError> {
+        let (storage, spec, blocks) = Storage::open(&config.data_dir)?;
+
+        let mut blocks = blocks.into_iter().peekable();
+        let mut chain = match storage.read_base()? {
+            Some((headers, snapshot)) => {
+                // Blocks before the base are left if pruning was interrupted
+                let base_prev = headers.last().and_then(|h| h.hash.clone());
+                while blocks.next_if(|block| block.prev != base_prev).is_some() {}
+
+                let block = blocks.next().ok_or(NodeError::MissingSnapshotBlock)?;
+                Blockchain::from_own_snapshot(&spec, &headers, block, &snapshot)?
+            }
+            None => Blockchain::from_spec(&spec)?,
+        };
+        // Pruning follows the snapshot heights, the snapshots themselves were stored
+        // when the blocks were imported
+        chain.snapshot_interval = config.snapshots.interval;
+        chain.pruning = config.pruning;
+        for block in blocks {
+            chain.append_block(block)?;
+        }
+        let snapshots = chain.take_snapshots();
+
+        let mut node = Self {
+            chain,
+            spec,
+            storage,
+            keep_snapshots: config.snapshots.keep,
+        };
+        // A node switched to pruning may lack the snapshots it is pruned to
+        if node.chain.pruning.prunes_blocks() {
+            node.store_snapshots(&snapshots)?;
+        }
+        node.prune_storage()?;
+        info!(
+            chain = %node.spec.name,
+            chain_id = node.spec.chain_id,
+            height = node.height(),
+            first_block = node.chain.first_block_height(),
+            first_state = node.chain.first_state_height(),
+            "Loaded chain"
+        );
+
+        Ok(node)
+    }
+
+    /// Will replace the chain of an empty data directory by the one starting at the
+    /// snapshot, which has to be of the given block. The headers of the blocks before
+    /// it have to lead from the genesis block to the block, sealed by its consensus.
+    pub fn start_from_snapshot(
+        &mut self,
+        headers: &[BlockHeader],
+        block: Block,
+        snapshot: &Snapshot,
+    ) -> Result<(), NodeError> {
+        if self.height() > 0 || self.storage.base().is_some() {
+            return Err(NodeError::NotEmpty);
+        }
+        let mut chain = Blockchain::from_snapshot(&self.spec, headers, block.clone(), snapshot)?;
+        chain.snapshot_interval = self.chain.snapshot_interval;
+        chain.pruning = self.chain.pruning;
+        self.storage.write_base(headers, snapshot)?;
+        self.storage.append(&block)?;
+        self.chain = chain;
+
+        Ok(())
+    }
+
+    /// Will return the height of the head, the genesis block has height 0
+    pub fn height(&self) -> usize {
+        self.chain.len() - 1
+    }
+
+    /// True if the block is already part of the chain
+    pub fn contains(&self, block: &Block) -> bool {
+        block
+            .hash
+            .as_ref()
+            .is_some_and(|hash| self.chain.contains_block(hash))
+    }
+
+    /// Will append the block to the chain and the storage, together with the snapshot
+    /// taken at its height. The block is on disk once this returns.
+    pub fn import_block(&mut self, block: Block) -> Result<(), NodeError> {
+        self.chain.append_block(block.clone())?;
+        self.storage.append(&block)?;
+        self.storage.flush()?;
+
+        let snapshots = self.chain.take_snapshots();
+        self.store_snapshots(&snapshots)?;
+        for snapshot in &snapshots {
+            info!(
+                height = snapshot.manifest.height,
+                chunks = snapshot.chunks.len(),
+                "Took snapshot"
+            );
+        }
+
+        self.prune_storage()
+    }
+
+    /// Will import a block received from a peer. A block on top of the head is appended,
+    /// one on top of an older block is handed to the fork choice. Returns whether the
+    /// block is part of the chain now.
+    pub fn receive_block(&mut self, block: Block) -> Result<bool, NodeError> {
+        if block.prev == self.chain.get_last_block_hash() {
+            self.import_block(block)?;
+            return Ok(true);
+        }
+
+        let prev = block.prev.clone().unwrap_or_default();
+        let parent = self
+            .chain
+            .get_block_height(&prev)
+            .ok_or(NodeError::UnknownParent(prev))?;
+        self.import_fork(parent + 1, vec![block])
+    }
+
+    /// Will switch to the fork replacing the blocks from `fork_height` on if the consensus
+    /// prefers it, the stored blocks are rewritten. Returns whether the fork was adopted.
+    pub fn import_fork(
+        &mut self,
+        fork_height: usize,
+        blocks: Vec<Block>,
+    ) -> Result<bool, NodeError> {
+        if !self.chain.import_fork(fork_height, blocks)? {
+            return Ok(false);
+        }
+
+        // The block log holds neither the genesis block nor the blocks before the base
+        let stored = match self.chain.first_block_height() {
+            0 => &self.chain.blocks[1..],
+            _ => &self.chain.blocks[..],
+        };
+        self.storage.rewrite(stored)?;
+        info!(fork_height, height = self.height(), "Switched to a fork");
+
+        let snapshots = self.chain.take_snapshots();
+        self.store_snapshots(&snapshots)?;
+        self.prune_storage()?;
+
+        Ok(true)
+    }
+
+    fn store_snapshots(&mut self, snapshots: &[Snapshot]) -> Result<(), NodeError> {
+        // A pruned node needs every snapshot it may be pruned to later on
+        let keep = match self.chain.pruning {
+            Pruning::Pruned { .. } => usize::MAX,
+            _ => self.keep_snapshots,
+        };
+        for snapshot in snapshots {
+            self.storage.write_snapshot(snapshot, keep)?;
+        }
+
+        Ok(())
+    }
+
+    /// Will drop the blocks the chain pruned from the storage, together with the
+    /// snapshots before them
+    fn prune_storage(&mut self) -> Result<(), NodeError> {
+        let first = self.chain.first_block_height();
+        if first <= self.storage.base().unwrap_or_default() {
+            return Ok(());
+        }
+
+        self.storage
+            .compact(&self.chain.headers[..first], &self.chain.blocks)?;
+        self.storage.remove_snapshots_before(first)?;
+        debug!(first_block = first, "Pruned blocks");
+
+        Ok(())
+    }
+
+    /// Will return the manifest of the snapshot at the given height, the latest if none
+    /// is given
+    pub fn snapshot_manifest(
+        &self,
+        height: Option<usize>,
+    ) -> Result<Option<SnapshotManifest>, NodeError> {
+        let heights = self.storage.snapshot_heights()?;
+        let height = match height {
+            Some(height) if heights.contains(&height) => height,
+            None if !heights.is_empty() => heights[heights.len() - 1],
+            _ => return Ok(None),
+        };
+
+        self.storage.read_manifest(height).map(Some)
+    }
+
+    /// Will return the chunk of the snapshot at the given height
+    pub fn snapshot_chunk(
+        &self,
+        height: usize,
+        index: usize,
+    ) -> Result<Option<Vec<u8>>, NodeError> {
+        match self.snapshot_manifest(Some(height))? {
+            Some(manifest) if index < manifest.chunks.len() => {
+                self.storage.read_chunk(height, index).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Will build a block of the pending transactions on top of the head and sign it
+    /// with the signer's key. Nothing is proposed if there are no pending transactions or
+    /// it is not the signer's turn, the transactions are dropped if the block is invalid.
+    /// The proof of work is left to [`Proposal::seal`], so the node is not held meanwhile.
+    pub fn propose_block(&mut self, signer: &Wallet) -> Result<Option<Proposal>, NodeError> {
+        let transactions = self.chain.take_pending_transactions();
+        if transactions.is_empty() {
+            return Ok(None);
+        }
+
+        let engine = self.chain.engine();
+        let mut block = engine.prepare_block(&self.chain);
+        for transaction in transactions.iter().cloned() {
+            block.add_transaction(transaction);
+        }
+        self.chain.commit_state(&mut block)?;
+
+        let work = match self.chain.consensus {
+            Consensus::ProofOfWork { difficulty } => Some(ProofOfWork::new(difficulty)),
+            _ => None,
+        };
+        if work.is_none() {
+            if let Err(err) = engine.seal(&self.chain, &mut block, signer) {
+                debug!(reason = %err, "Not sealing");
+                self.chain.pending_transactions.splice(0..0, transactions);
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(Proposal {
+            block,
+            transactions,
+            work,
+        }))
+    }
+
+    /// Will import a sealed proposal. If the head moved on in the meantime the block is
+    /// dropped and its transactions are queued again.
+    pub fn import_proposal(&mut self, proposal: Proposal) -> Result<Option<Block>, NodeError> {
+        if proposal.block.prev != self.chain.get_last_block_hash() {
+            debug!("The head moved on while sealing");
+            let pending = std::mem::take(&mut self.chain.pending_transactions);
+            for transaction in proposal.transactions.into_iter().chain(pending) {
+                // The ones the new head included carry a used nonce now
+                let _ = self.chain.submit_transaction(transaction);
+            }
+            return Ok(None);
+        }
+
+        self.import_block(proposal.block.clone())?;
+        Ok(Some(proposal.block))
+    }
+
+    pub fn flush(&mut self) -> Result<(), NodeError> {
+        self.storage.flush()
+    }
+}
+
+/// A block built by [`Node::propose_block`], imported with [`Node::import_proposal`]
+#[derive(Debug)]
+pub struct Proposal {
+    pub block: Block,
+    transactions: Vec<Transaction>,
+    /// Set if the block still needs its proof of work
+    work: Option<ProofOfWork>,
+}
+
+impl Proposal {
+    /// Will do the proof of work if the chain needs one, the block is signed already
+    /// otherwise
+    pub fn seal(&mut self) {
+        if let Some(work) = self.work.take() {
+            work.mine(&mut self.block);
+        }
+    }
+}
+
+/// Will lock the shared node. A panic while holding the lock leaves the chain
+/// consistent, blocks are only appended once fully checked.
+pub fn lock(node: &Mutex<Node>) -> MutexGuard<'_, Node> {
+    node.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,237 @@
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use eternal_account::{Account, StoreKey};
+use eternal_core::{blockchain::Blockchain, error::LookupError, raw, transaction::Transaction};
+use eternal_networking::jsonrpc::{Request, Response, RpcError};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+
+use crate::{
+    error::NodeError,
+    node::{lock, Node},
+};
+
+/// The most headers returned by `eternal_getHeaders`
+pub const MAX_HEADERS: usize = 1000;
+
+/// Will create the JSON-RPC handler of the node. Submitted transactions are passed
+/// to `gossip` to be sent to the peers.
+pub fn handler(
+    node: Arc<Mutex<Node>>,
+    gossip: UnboundedSender<Transaction>,
+) -> impl Fn(Request) -> Response + Send + Sync + 'static {
+    move |request| {
+        debug!(method = %request.method, "RPC request");
+
+        match call(&mut lock(&node), &gossip, &request.method, &request.params) {
+            Ok(result) => Response::result(request.id, result),
+            Err(err) => Response::error(request.id, err),
+        }
+    }
+}
+
+fn call(
+    node: &mut Node,
+    gossip: &UnboundedSender<Transaction>,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    let chain = &node.chain;
+
+    let result = match method {
+        "eternal_chainId" => json!(chain.chain_id),
+        "eternal_blockNumber" => json!(node.height()),
+        "eternal_getBlockByNumber" => {
+            let height: usize = param(params, 0)?;
+            to_value(chain.get_block_by_height(height).map_err(lookup_error)?)?
+        }
+        "eternal_getBlockByHash" => {
+            let hash: String = param(params, 0)?;
+            to_value(chain.get_block(&hash).map_err(lookup_error)?)?
+        }
+        "eternal_getHeaders" => {
+            let from: usize = param(params, 0)?;
+            let count: usize = param(params, 1)?;
+            let to = from.saturating_add(count.min(MAX_HEADERS)).min(chain.len());
+            to_value(chain.headers.get(from..to).unwrap_or_default())?
+        }
+        "eternal_getSnapshotManifest" => {
+            // The latest snapshot if no height is given
+            let height: Option<usize> = optional_param(params, 0)?;
+            let manifest = node.snapshot_manifest(height).map_err(server_error)?;
+            to_value(manifest)?
+        }
+        "eternal_getSnapshotChunk" => {
+            // Base64, the chunk hash is taken over the decoded bytes
+            let height: usize = param(params, 0)?;
+            let index: usize = param(params, 1)?;
+            match node.snapshot_chunk(height, index).map_err(server_error)? {
+                Some(chunk) => json!(STANDARD.encode(chunk)),
+                None => Value::Null,
+            }
+        }
+        "eternal_getTransaction" => {
+            let hash: String = param(params, 0)?;
+            match chain.get_transaction(&hash).map_err(lookup_error)? {
+                Some((transaction, location)) => json!({
+                    "transaction": to_value(transaction)?,
+                    "height": location.height,
+                    "index": location.index,
+                }),
+                None => Value::Null,
+            }
+        }
+        "eternal_getTransactionReceipt" => {
+            let hash: String = param(params, 0)?;
+            to_value(chain.get_receipt(&hash).map_err(lookup_error)?)?
+        }
+        "eternal_getTransactionProof" => {
+            let hash: String = param(params, 0)?;
+            to_value(chain.prove_transaction(&hash).map_err(lookup_error)?)?
+        }
+        "eternal_getTransactionCount" => {
+            // Including the pending ones, the next transaction carries the count plus one
+            let address: String = param(params, 0)?;
+            let count = chain.next_nonce(&address) - 1;
+            json!(count as u64)
+        }
+        "eternal_getContracts" => {
+            let mut contracts: Vec<&String> = chain.smart_contracts.keys().collect();
+            contracts.sort();
+            json!(contracts)
+        }
+        "eternal_getAccount" => {
+            // The state of the head if no height is given
+            let address: String = param(params, 0)?;
+            let height: Option<usize> = optional_param(params, 1)?;
+            to_value(account_at(chain, &address, height)?)?
+        }
+        "eternal_getBalance" => {
+            let address: String = param(params, 0)?;
+            let height: Option<usize> = optional_param(params, 1)?;
+            // Balances may exceed the range of JSON numbers
+            match account_at(chain, &address, height)? {
+                Some(account) => json!(account.tokens.to_string()),
+                None => Value::Null,
+            }
+        }
+        "eternal_getTokenBalances" => {
+            // By contract, as strings like `eternal_getBalance`
+            let address: String = param(params, 0)?;
+            let balances: Map<String, Value> = chain
+                .ledger
+                .tokens(&address)
+                .map(|(contract, balance)| (contract.to_string(), json!(balance.to_string())))
+                .collect();
+            Value::Object(balances)
+        }
+        "eternal_getTokenHolders" => {
+            let contract: String = param(params, 0)?;
+            let holders: Map<String, Value> = chain
+                .ledger
+                .holders(&contract)
+                .map(|(holder, balance)| (holder.to_string(), json!(balance.to_string())))
+                .collect();
+            Value::Object(holders)
+        }
+        "eternal_getProof" => {
+            // The account with the store entries of the given keys and the balances of the
+            // given token contracts, against the state root of the head if no height is given
+            let address: String = param(params, 0)?;
+            let keys: Vec<StoreKey> = param(params, 1)?;
+            let contracts: Vec<String> = param(params, 2)?;
+            let height: Option<usize> = optional_param(params, 3)?;
+            let height = height.unwrap_or(chain.len() - 1);
+            let proof = chain.prove_account(&address, &keys, &contracts, height);
+            to_value(proof.map_err(lookup_error)?)?
+        }
+        "eternal_getAuthorityProof" => {
+            let height: usize = param(params, 0)?;
+            to_value(chain.prove_authorities(height).map_err(lookup_error)?)?
+        }
+        "eternal_sendTransaction" => {
+            let transaction: Transaction = param(params, 0)?;
+            submit(node, gossip, transaction)?
+        }
+        "eternal_sendRawTransaction" => {
+            // Hex or base64, see `eternal_core::raw`
+            let raw: String = param(params, 0)?;
+            let transaction =
+                raw::from_text(&raw).map_err(|err| RpcError::invalid_params(err.to_string()))?;
+            submit(node, gossip, transaction)?
+        }
+        _ => return Err(RpcError::method_not_found(method)),
+    };
+
+    Ok(result)
+}
+
+/// Will add the transaction to the pending ones and gossip it, returning its hash
+fn submit(
+    node: &mut Node,
+    gossip: &UnboundedSender<Transaction>,
+    transaction: Transaction,
+) -> Result<Value, RpcError> {
+    let hash = transaction.hash();
+
+    node.chain
+        .submit_transaction(transaction.clone())
+        .map_err(|err| RpcError::server(err.to_string(), err.code()))?;
+    // The network task is only gone while shutting down
+    let _ = gossip.send(transaction);
+
+    Ok(json!(hash))
+}
+
+/// Will return the account as of the block at the given height, which fails once the
+/// state of the block was pruned
+fn account_at(
+    chain: &Blockchain,
+    address: &str,
+    height: Option<usize>,
+) -> Result<Option<Account>, RpcError> {
+    let Some(height) = height else {
+        return Ok(chain.accounts.get(address).cloned());
+    };
+
+    let state = chain.state_at(height).map_err(lookup_error)?;
+    Ok(state.and_then(|mut state| state.accounts.remove(address)))
+}
+
+/// Will deserialize the positional parameter, which may be left out
+fn optional_param<T: DeserializeOwned>(
+    params: &Value,
+    index: usize,
+) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => param(params, index).map(Some),
+    }
+}
+
+/// Will deserialize the positional parameter
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, RpcError> {
+    let value = params
+        .get(index)
+        .cloned()
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing parameter {}", index)))?;
+
+    serde_json::from_value(value)
+        .map_err(|err| RpcError::invalid_params(format!("Parameter {}: {}", index, err)))
+}
+
+fn server_error(err: NodeError) -> RpcError {
+    RpcError::server(err.report(), err.code())
+}
+
+fn lookup_error(err: LookupError) -> RpcError {
+    RpcError::server(err.to_string(), err.code())
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value)
+        .map_err(|err| RpcError::new(RpcError::INTERNAL_ERROR, err.to_string()))
+}
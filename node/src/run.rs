@@ -0,0 +1,258 @@
+use std::{
+    future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use eternal_account::{Keystore, Wallet};
+use eternal_core::{block::Block, blockchain::Consensus, error::TxError, transaction::Transaction};
+use eternal_networking::{
+    jsonrpc,
+    p2p::{Multiaddr, Network, NetworkEvent},
+};
+use tokio::{
+    net::TcpListener,
+    signal,
+    sync::{mpsc, watch},
+    task::{self, JoinHandle},
+    time,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::{Config, ValidatorConfig},
+    error::NodeError,
+    node::{lock, Node},
+    read_password, rpc, sync,
+};
+
+/// Will run the node until SIGINT: serve RPC, gossip with the peers and produce blocks
+/// if a validator key is configured. The storage is flushed before returning.
+pub async fn run(config: Config) -> Result<(), NodeError> {
+    let node = Node::open(&config)?;
+    let producer = match &config.validator {
+        Some(validator) => Some(load_validator(validator, &node)?),
+        None => None,
+    };
+    let node = Arc::new(Mutex::new(node));
+
+    let (gossip_tx, mut gossip_rx) = mpsc::unbounded_channel::<Transaction>();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    let rpc_server = if config.rpc.enabled {
+        let listener = TcpListener::bind(config.rpc.addr).await?;
+        info!(addr = %listener.local_addr()?, "RPC listening");
+
+        let handler = rpc::handler(node.clone(), gossip_tx.clone());
+        let mut shutdown = shutdown_rx.clone();
+        let stopped = async move {
+            let _ = shutdown.changed().await;
+        };
+        Some(tokio::spawn(jsonrpc::serve(listener, handler, stopped)))
+    } else {
+        None
+    };
+
+    let mut network = if config.p2p.enabled {
+        let listen = parse_multiaddr(&config.p2p.listen)?;
+        let bootnodes = config
+            .p2p
+            .bootnodes
+            .iter()
+            .map(|b| parse_multiaddr(b))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let network = Network::new(listen, &bootnodes)?;
+        info!(peer_id = %network.local_peer_id(), "P2P started");
+        Some(network)
+    } else {
+        None
+    };
+
+    let period = producer
+        .as_ref()
+        .map(|(_, period)| *period)
+        .unwrap_or(Duration::from_secs(1));
+    let mut ticker = time::interval(period);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+    let shutdown = signal::ctrl_c();
+    tokio::pin!(shutdown);
+    let mut catching_up: Option<JoinHandle<()>> = None;
+    let mut producing: Option<JoinHandle<Result<Option<Block>, NodeError>>> = None;
+
+    loop {
+        tokio::select! {
+            result = &mut shutdown => {
+                if let Err(err) = result {
+                    error!(error = %err, "Can not listen for SIGINT");
+                }
+                info!("Shutting down");
+                break;
+            }
+            // A block is only started once the previous one is done
+            _ = ticker.tick(), if producer.is_some() && producing.is_none() => {
+                let (signer, _) = producer.as_ref().expect("checked by the guard");
+                let (node, signer) = (node.clone(), signer.clone());
+                producing = Some(task::spawn_blocking(move || produce(&node, &signer)));
+            }
+            produced = async { producing.as_mut().expect("checked by the guard").await }, if producing.is_some() => {
+                producing = None;
+                match produced {
+                    Ok(Ok(Some(block))) => {
+                        info!(
+                            hash = block.hash.as_deref().unwrap_or_default(),
+                            transactions = block.get_transaction_count(),
+                            "Produced block"
+                        );
+                        if let Some(network) = network.as_mut() {
+                            network.publish_block(encode(&block));
+                        }
+                    }
+                    Ok(Ok(None)) => {}
+                    Ok(Err(err)) => warn!(error = %err.report(), code = err.code(), "Block production failed"),
+                    Err(err) => error!(error = %err, "Block production panicked"),
+                }
+            }
+            Some(transaction) = gossip_rx.recv() => {
+                if let Some(network) = network.as_mut() {
+                    network.publish_transaction(encode(&transaction));
+                }
+            }
+            event = next_event(&mut network) => {
+                let missing_parent = handle_event(&node, event);
+                let syncing = catching_up.as_ref().is_some_and(|task| !task.is_finished());
+                if missing_parent && !syncing {
+                    catching_up = catch_up(node.clone(), &config.p2p.sync_peers);
+                }
+            }
+        }
+    }
+
+    let _ = shutdown_tx.send(true);
+    if let Some(rpc_server) = rpc_server {
+        match rpc_server.await {
+            Ok(Err(err)) => error!(error = %err, "RPC server failed"),
+            Err(err) => error!(error = %err, "RPC server panicked"),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    let mut node = lock(&node);
+    node.flush()?;
+    info!(height = node.height(), "Storage flushed");
+
+    Ok(())
+}
+
+/// Will decrypt the validator key and derive the time between two blocks
+fn load_validator(
+    validator: &ValidatorConfig,
+    node: &Node,
+) -> Result<(Arc<Wallet>, Duration), NodeError> {
+    let keystore = Keystore::load(&validator.keystore)?;
+    let password = read_password("ETERNAL_VALIDATOR_PASSWORD", "Validator key password: ")?;
+    let signer = keystore.decrypt(&password)?;
+
+    let mut block_time_ms = validator.block_time_ms;
+    if let Consensus::ProofOfAuthority { period_ms, .. } = &node.chain.consensus {
+        block_time_ms = block_time_ms.max(*period_ms);
+    }
+    info!(address = %signer.address(), block_time_ms, "Producing blocks");
+
+    Ok((Arc::new(signer), Duration::from_millis(block_time_ms)))
+}
+
+/// Will produce a block of the pending transactions. The node is held to build and to
+/// import the block, not while it is sealed.
+fn produce(node: &Mutex<Node>, signer: &Wallet) -> Result<Option<Block>, NodeError> {
+    let Some(mut proposal) = lock(node).propose_block(signer)? else {
+        return Ok(None);
+    };
+    proposal.seal();
+
+    lock(node).import_proposal(proposal)
+}
+
+async fn next_event(network: &mut Option<Network>) -> NetworkEvent {
+    match network {
+        Some(network) => network.next_event().await,
+        None => future::pending().await,
+    }
+}
+
+/// Will sync with the first of the peers that answers, in the background. Nothing is
+/// started without peers.
+fn catch_up(node: Arc<Mutex<Node>>, peers: &[String]) -> Option<JoinHandle<()>> {
+    if peers.is_empty() {
+        warn!("Missing the parent of a block, configure sync peers to catch up");
+        return None;
+    }
+
+    let peers = peers.to_vec();
+    Some(task::spawn_blocking(move || {
+        for peer in &peers {
+            match sync::sync(&node, peer) {
+                Ok(()) => return,
+                Err(err) => warn!(%peer, error = %err.report(), code = err.code(), "Sync failed"),
+            }
+        }
+    }))
+}
+
+/// Will handle an event of the network. Returns true if the parent of a block is
+/// missing, so the node has to catch up.
+fn handle_event(node: &Mutex<Node>, event: NetworkEvent) -> bool {
+    match event {
+        NetworkEvent::Listening(address) => info!(%address, "P2P listening"),
+        NetworkEvent::PeerConnected(peer) => info!(%peer, "Peer connected"),
+        NetworkEvent::PeerDisconnected(peer) => info!(%peer, "Peer disconnected"),
+        NetworkEvent::Block { source, data } => {
+            let Ok(block) = serde_json::from_slice::<Block>(&data) else {
+                warn!(%source, "Undecodable block");
+                return false;
+            };
+
+            let mut node = lock(node);
+            if node.contains(&block) {
+                return false;
+            }
+            let hash = block.hash.clone().unwrap_or_default();
+            match node.receive_block(block) {
+                Ok(true) => info!(%source, %hash, height = node.height(), "Imported block"),
+                Ok(false) => debug!(%source, %hash, "Kept the current chain over the fork"),
+                Err(NodeError::UnknownParent(parent)) => {
+                    info!(%source, %hash, %parent, "Missing the parent of the block");
+                    return true;
+                }
+                Err(err) => {
+                    warn!(%source, %hash, error = %err.report(), code = err.code(), "Rejected block")
+                }
+            }
+        }
+        NetworkEvent::Transaction { source, data } => {
+            let Ok(transaction) = serde_json::from_slice::<Transaction>(&data) else {
+                warn!(%source, "Undecodable transaction");
+                return false;
+            };
+
+            match lock(node).chain.submit_transaction(transaction) {
+                Ok(()) | Err(TxError::AlreadyPending) => {}
+                Err(err) => debug!(%source, error = %err, "Rejected transaction"),
+            }
+        }
+    }
+
+    false
+}
+
+fn parse_multiaddr(address: &str) -> Result<Multiaddr, NodeError> {
+    address
+        .parse()
+        .map_err(|_| NodeError::InvalidMultiaddr(address.to_string()))
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    // Blocks and transactions only hold plain data, encoding them can not fail
+    serde_json::to_vec(value).unwrap_or_default()
+}
@@ -0,0 +1,223 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eternal_core::{
+    block::{Block, BlockHeader},
+    snapshot::{Snapshot, SnapshotManifest},
+    spec::ChainSpec,
+    storage::BlockLog,
+};
+
+use crate::error::NodeError;
+
+const SPEC_FILE: &str = "spec.json";
+const BLOCKS_FILE: &str = "blocks.jsonl";
+const HEADERS_FILE: &str = "headers.json";
+const SNAPSHOTS_DIR: &str = "snapshots";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The data directory of a node: the chain spec it was initialized with and the
+/// blocks after genesis, the state is re-derived from them on start.
+///
+/// A node started from a snapshot, or pruning its blocks, keeps the headers of the blocks
+/// before it instead, its block log starts with the block of the snapshot. Snapshots are
+/// stored as `snapshots/<height>/manifest.json` and one `<index>.chunk` file per chunk.
+#[derive(Debug)]
+pub struct Storage {
+    root: PathBuf,
+    log: BlockLog,
+    /// Height of the snapshot the node was started from or pruned to
+    base: Option<usize>,
+}
+
+impl Storage {
+    /// Will create the data directory for the chain spec. The genesis hash is pinned,
+    /// so a changed spec file is noticed on the next start.
+    pub fn init(root: &Path, spec: &ChainSpec) -> Result<(), NodeError> {
+        if root.join(SPEC_FILE).exists() {
+            return Err(NodeError::AlreadyInitialized(root.to_path_buf()));
+        }
+
+        let mut spec = spec.clone();
+        spec.genesis_hash = spec.genesis_block()?.hash;
+
+        fs::create_dir_all(root)?;
+        fs::write(root.join(SPEC_FILE), spec.to_json()?)?;
+        BlockLog::write(root.join(BLOCKS_FILE), &[])?;
+
+        Ok(())
+    }
+
+    /// Will open the data directory and read the chain spec and the stored blocks
+    pub fn open(root: &Path) -> Result<(Self, ChainSpec, Vec<Block>), NodeError> {
+        let spec_path = root.join(SPEC_FILE);
+        if !spec_path.exists() {
+            return Err(NodeError::NotInitialized(root.to_path_buf()));
+        }
+
+        let spec = ChainSpec::load(spec_path)?;
+        let blocks = BlockLog::read(root.join(BLOCKS_FILE))?;
+        let base = match fs::read(root.join(HEADERS_FILE)) {
+            Ok(headers) => Some(serde_json::from_slice::<Vec<BlockHeader>>(&headers)?.len()),
+            Err(_) => None,
+        };
+        let storage = Self {
+            root: root.to_path_buf(),
+            log: BlockLog::open(root.join(BLOCKS_FILE))?,
+            base,
+        };
+
+        Ok((storage, spec, blocks))
+    }
+
+    pub fn append(&mut self, block: &Block) -> Result<(), NodeError> {
+        Ok(self.log.append(block)?)
+    }
+
+    /// Will write everything buffered to disk
+    pub fn flush(&mut self) -> Result<(), NodeError> {
+        Ok(self.log.flush()?)
+    }
+
+    /// Will return the height of the snapshot the node was started from or pruned to
+    pub fn base(&self) -> Option<usize> {
+        self.base
+    }
+
+    /// Will read the headers of the blocks before the snapshot the node was started
+    /// from, together with the snapshot
+    pub fn read_base(&self) -> Result<Option<(Vec<BlockHeader>, Snapshot)>, NodeError> {
+        let Some(height) = self.base else {
+            return Ok(None);
+        };
+
+        let headers = serde_json::from_slice(&fs::read(self.root.join(HEADERS_FILE))?)?;
+        Ok(Some((headers, self.read_snapshot(height)?)))
+    }
+
+    /// Will store the headers and the snapshot of a node started from it
+    pub fn write_base(
+        &mut self,
+        headers: &[BlockHeader],
+        snapshot: &Snapshot,
+    ) -> Result<(), NodeError> {
+        self.write_snapshot(snapshot, usize::MAX)?;
+        fs::write(self.root.join(HEADERS_FILE), serde_json::to_vec(headers)?)?;
+        self.base = Some(headers.len());
+
+        Ok(())
+    }
+
+    /// Will drop the blocks before the last of the headers and keep only the headers of
+    /// them. The snapshot at that height has to be stored already.
+    pub fn compact(&mut self, headers: &[BlockHeader], blocks: &[Block]) -> Result<(), NodeError> {
+        self.log.flush()?;
+
+        // The headers go first: blocks left before them are skipped on start
+        let path = self.root.join(HEADERS_FILE);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_vec(headers)?)?;
+        fs::rename(partial, path)?;
+        self.base = Some(headers.len());
+
+        self.rewrite(blocks)
+    }
+
+    /// Will replace the stored blocks, e.g. after switching to a fork
+    pub fn rewrite(&mut self, blocks: &[Block]) -> Result<(), NodeError> {
+        self.log.flush()?;
+
+        let path = self.root.join(BLOCKS_FILE);
+        let partial = path.with_extension("partial");
+        BlockLog::write(&partial, blocks)?;
+        fs::rename(partial, &path)?;
+        self.log = BlockLog::open(path)?;
+
+        Ok(())
+    }
+
+    /// Will store the snapshot and remove the oldest ones, so at most `keep` are left.
+    /// The snapshot the node was started from is always kept. A snapshot of another
+    /// block at the same height, left by a reorg, is replaced.
+    pub fn write_snapshot(&mut self, snapshot: &Snapshot, keep: usize) -> Result<(), NodeError> {
+        let dir = self.snapshot_dir(snapshot.manifest.height);
+        if dir.exists() {
+            let stored = self.read_manifest(snapshot.manifest.height)?;
+            if stored.block_hash != snapshot.manifest.block_hash {
+                fs::remove_dir_all(&dir)?;
+            }
+        }
+        if !dir.exists() {
+            // Written to a temporary directory first, so a snapshot is either complete or missing
+            let partial = dir.with_extension("partial");
+            fs::create_dir_all(&partial)?;
+            for (index, chunk) in snapshot.chunks.iter().enumerate() {
+                fs::write(partial.join(format!("{}.chunk", index)), chunk)?;
+            }
+            fs::write(
+                partial.join(MANIFEST_FILE),
+                serde_json::to_vec_pretty(&snapshot.manifest)?,
+            )?;
+            fs::rename(partial, dir)?;
+        }
+
+        let heights = self.snapshot_heights()?;
+        let removable = heights.iter().filter(|h| Some(**h) != self.base);
+        let excess = heights.len().saturating_sub(keep);
+        for height in removable.take(excess) {
+            fs::remove_dir_all(self.snapshot_dir(*height))?;
+        }
+
+        Ok(())
+    }
+
+    /// Will remove the snapshots before the given height
+    pub fn remove_snapshots_before(&mut self, height: usize) -> Result<(), NodeError> {
+        for stored in self.snapshot_heights()? {
+            if stored < height && Some(stored) != self.base {
+                fs::remove_dir_all(self.snapshot_dir(stored))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Will return the heights of the stored snapshots, oldest first
+    pub fn snapshot_heights(&self) -> Result<Vec<usize>, NodeError> {
+        let dir = self.root.join(SNAPSHOTS_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut heights: Vec<usize> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    pub fn read_manifest(&self, height: usize) -> Result<SnapshotManifest, NodeError> {
+        let path = self.snapshot_dir(height).join(MANIFEST_FILE);
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn read_chunk(&self, height: usize, index: usize) -> Result<Vec<u8>, NodeError> {
+        let path = self.snapshot_dir(height).join(format!("{}.chunk", index));
+        Ok(fs::read(path)?)
+    }
+
+    pub fn read_snapshot(&self, height: usize) -> Result<Snapshot, NodeError> {
+        let manifest = self.read_manifest(height)?;
+        let chunks = (0..manifest.chunks.len())
+            .map(|index| self.read_chunk(height, index))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Snapshot { manifest, chunks })
+    }
+
+    fn snapshot_dir(&self, height: usize) -> PathBuf {
+        self.root.join(SNAPSHOTS_DIR).join(height.to_string())
+    }
+}
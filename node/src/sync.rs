@@ -0,0 +1,152 @@
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use eternal_core::{
+    block::{Block, BlockHeader},
+    snapshot::{Snapshot, SnapshotManifest},
+};
+use eternal_networking::jsonrpc::Client;
+use serde_json::json;
+use tracing::info;
+
+use crate::{
+    error::NodeError,
+    node::{lock, Node},
+    rpc::MAX_HEADERS,
+};
+
+/// Will catch up with the peer behind the JSON-RPC address. An empty node starts from
+/// the peer's latest snapshot instead of executing every block since genesis: the
+/// headers up to the snapshot are checked to lead from the node's genesis block to the
+/// block the snapshot commits to, sealed by its consensus, see
+/// [`eternal_core::snapshot::verify_headers`]. The blocks after it are fully checked on
+/// import. If the peer is on another fork, its blocks from the first one the chains
+/// differ in are handed to the fork choice.
+///
+/// The node is only locked while blocks are imported, so a running node keeps serving.
+pub fn sync(node: &Mutex<Node>, url: &str) -> Result<(), NodeError> {
+    let client = Client::new(url);
+    let chain_id: u64 = client.call("eternal_chainId", json!([]))?;
+    let expected = lock(node).chain.chain_id;
+    if chain_id != expected {
+        return Err(NodeError::WrongChain {
+            expected,
+            actual: chain_id,
+        });
+    }
+
+    let empty = lock(node).height() == 0;
+    if empty {
+        let manifest: Option<SnapshotManifest> =
+            client.call("eternal_getSnapshotManifest", json!([]))?;
+        match manifest {
+            Some(manifest) => download_snapshot(node, &client, manifest)?,
+            None => info!("The peer has no snapshot, executing every block"),
+        }
+    }
+
+    let head: usize = client.call("eternal_blockNumber", json!([]))?;
+    let fork_height = fork_height(node, &client, head)?;
+    let height = lock(node).height();
+    if fork_height <= height.min(head) {
+        let blocks = (fork_height..=head)
+            .map(|height| fetch_block(&client, height))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !lock(node).import_fork(fork_height, blocks)? {
+            info!(fork_height, "Kept the current chain over the fork");
+        }
+    } else {
+        for height in fork_height..=head {
+            let block = fetch_block(&client, height)?;
+            let mut node = lock(node);
+            // Gossiped blocks may have been imported in the meantime
+            if !node.contains(&block) {
+                node.receive_block(block)?;
+            }
+        }
+    }
+
+    let mut node = lock(node);
+    node.flush()?;
+    info!(height = node.height(), "Synced");
+    Ok(())
+}
+
+/// Will return the height of the first block the chains of the node and the peer
+/// differ in, the blocks before it are shared
+fn fork_height(node: &Mutex<Node>, client: &Client, head: usize) -> Result<usize, NodeError> {
+    let mut end = lock(node).height().min(head) + 1;
+    while end > 1 {
+        let start = end.saturating_sub(MAX_HEADERS).max(1);
+        let theirs: Vec<BlockHeader> =
+            client.call("eternal_getHeaders", json!([start, end - start]))?;
+        if theirs.len() < end - start {
+            return Err(NodeError::BlockUnavailable(start + theirs.len()));
+        }
+
+        let node = lock(node);
+        let shared = (start..end).zip(&theirs).rev().find(|(height, header)| {
+            node.chain.get_header(*height).map(|ours| &ours.hash) == Some(&header.hash)
+        });
+        if let Some((height, _)) = shared {
+            return Ok(height + 1);
+        }
+        end = start;
+    }
+
+    Ok(1)
+}
+
+fn download_snapshot(
+    node: &Mutex<Node>,
+    client: &Client,
+    manifest: SnapshotManifest,
+) -> Result<(), NodeError> {
+    let height = manifest.height;
+    info!(
+        height,
+        chunks = manifest.chunks.len(),
+        "Downloading snapshot"
+    );
+
+    let mut headers: Vec<BlockHeader> = Vec::with_capacity(height);
+    while headers.len() < height {
+        let count = (height - headers.len()).min(MAX_HEADERS);
+        let batch: Vec<BlockHeader> =
+            client.call("eternal_getHeaders", json!([headers.len(), count]))?;
+        if batch.is_empty() {
+            return Err(NodeError::BlockUnavailable(headers.len()));
+        }
+        headers.extend(batch);
+    }
+    headers.truncate(height);
+
+    let mut chunks = Vec::with_capacity(manifest.chunks.len());
+    for index in 0..manifest.chunks.len() {
+        let chunk: Option<String> =
+            client.call("eternal_getSnapshotChunk", json!([height, index]))?;
+        // A missing or undecodable chunk fails the hash check
+        chunks.push(
+            STANDARD
+                .decode(chunk.unwrap_or_default())
+                .unwrap_or_default(),
+        );
+    }
+
+    let block = fetch_block(client, height)?;
+    let mut node = lock(node);
+    node.start_from_snapshot(&headers, block, &Snapshot { manifest, chunks })?;
+    node.flush()?;
+
+    info!(
+        height,
+        hash = node.chain.get_last_block_hash().unwrap_or_default(),
+        "Started from snapshot"
+    );
+    Ok(())
+}
+
+fn fetch_block(client: &Client, height: usize) -> Result<Block, NodeError> {
+    let block: Option<Block> = client.call("eternal_getBlockByNumber", json!([height]))?;
+    block.ok_or(NodeError::BlockUnavailable(height))
+}
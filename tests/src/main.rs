@@ -1,20 +1,17 @@
-use std::process;
-
 use eternal_core::{
     block::Block,
     blockchain::Blockchain,
+    spec::{dev_wallets, ChainSpec},
     transaction::{Transaction, TransactionData},
 };
 
 use eternal_macro::SmartContract;
 use eternal_vm::smart_contract::SmartContract as SC;
 use eternal_vm::smart_contract::{SmartContractApi, SmartContractStanderd};
-use rustyline::{error::ReadlineError, DefaultEditor};
 
 #[derive(SmartContract)]
 #[standerd(name = "ESC20")]
 struct PepeToken {
-
     #[property(name = "total_supply")]
     total_supply: u128,
 
@@ -23,11 +20,9 @@ struct PepeToken {
 }
 
 fn main() {
-    let mut bc = Blockchain::new();
+    let mut bc = Blockchain::from_spec(&ChainSpec::dev()).unwrap();
     let sc = PepeToken {
-        transfer: |_, _, _| {
-            Ok(())
-        },
+        transfer: |_, _, _| Ok(()),
         total_supply: 100,
     };
 
@@ -39,100 +34,94 @@ fn main() {
 }
 
 fn run_blockchain_actions(bc: &mut Blockchain) {
-    let (bob, alice) = bc.get_bob_alice();
+    // Bob is the authority of the dev chain and seals every block
+    let wallets = dev_wallets(3);
+    let (bob, alice) = (
+        wallets[0].address().to_string(),
+        wallets[1].address().to_string(),
+    );
 
     // Block 1
-    let mut block = Block::new(None);
+    let mut block = Block::new(bc.get_last_block_hash());
     {
         block.add_transaction(Transaction::new(
-            bob.clone(),
-            TransactionData::MintTokens {
-                receiver: bob.clone(),
-                amount: 100,
-            },
-            10,
-        ));
-        block.add_transaction(Transaction::new(
+            bc.chain_id,
             bob.clone(),
             TransactionData::Transfer {
                 to: alice.clone(),
                 amount: 1000,
             },
-            10,
+            1,
         ));
         block.add_transaction(Transaction::new(
+            bc.chain_id,
             alice.clone(),
             TransactionData::Transfer {
                 to: bob.clone(),
                 amount: 999,
             },
-            10,
+            1,
         ));
     }
+    bc.commit_state(&mut block).unwrap();
+    block.sign(&wallets[0]);
     bc.append_block(block.clone()).unwrap();
 
     // Block 2
     let mut block = Block::new(block.hash.clone());
     {
         block.add_transaction(Transaction::new(
+            bc.chain_id,
             alice.clone(),
-            TransactionData::CreateUserAccount,
-            10,
+            TransactionData::CreateUserAccount {
+                public_key: wallets[2].public_key().to_string(),
+            },
+            2,
         ));
 
         let sc: SC = smart_contract();
 
         block.add_transaction(Transaction::new(
+            bc.chain_id,
             alice.clone(),
             TransactionData::DeploySmartContract {
                 publisher: alice.clone(),
                 sc: Some(sc),
             },
-            10,
+            3,
         ));
     }
+    bc.commit_state(&mut block).unwrap();
+    block.sign(&wallets[0]);
     bc.append_block(block.clone()).unwrap();
 
-    println!("{}", bc.temp[4]);
+    println!("{}", bc.temp[3]);
 
     let mut block = Block::new(block.hash.clone());
     {
-        let (token, amount, to) = get_input();
+        // The deployed contract, interactive transfers are done with `eternal-wallet repl`
+        let token = bc.temp[3].clone();
         block.add_transaction(Transaction::new(
+            bc.chain_id,
             alice,
             TransactionData::TransferToken {
                 token,
-                to,
-                amount: amount.parse().unwrap(),
+                to: bob,
+                amount: 10,
             },
-            10,
+            4,
         ));
     }
+    bc.commit_state(&mut block).unwrap();
+    block.sign(&wallets[0]);
     bc.append_block(block.clone()).unwrap();
 }
 
-fn get_input() -> (String, String, String) {
-    let mut rl = DefaultEditor::new().unwrap();
-    let token = match rl.readline("Token: ") {
-        Ok(line) => line,
-        Err(ReadlineError::Interrupted) => process::exit(1),
-        Err(ReadlineError::Eof) => process::exit(1),
-        Err(err) => {
-            eprintln!("{:#?}", err);
-            process::exit(1)
-        }
-    };
-    let amount = rl.readline("Amount: ").unwrap();
-    let to = rl.readline("To: ").unwrap();
-
-    (token, amount, to)
-}
-
 fn smart_contract() -> SC {
     SC::new(
         SmartContractStanderd::from("ESC20"),
         SmartContractApi::ESC20 {
-            transfer: |from, to, amount| Ok(println!("{} =Transfered {}> {}", from, amount, to)),
+            transfer: |_, from, to, amount| Ok(println!("{} =Transfered {}> {}", from, amount, to)),
             publisher: String::new(),
             total_suply: 100_000,
         },
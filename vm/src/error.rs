@@ -0,0 +1,51 @@
+use eternal_account::StoreError;
+use thiserror::Error;
+
+/// Errors raised by the world state or while executing a smart contract
+#[derive(Debug, Error)]
+pub enum VmError {
+    #[error("User already exists!")]
+    AccountExists(String),
+
+    #[error("Smart contract already exists!")]
+    ContractExists(String),
+
+    #[error("Unknown smart contract function `{0}`")]
+    UnknownFunction(String),
+
+    #[error("Invalid arguments for smart contract function `{0}`")]
+    InvalidArguments(String),
+
+    #[error("Smart contract execution failed: {0}")]
+    Execution(String),
+
+    #[error("{holder} holds {balance} tokens of {contract}, {amount} are needed")]
+    InsufficientTokens {
+        contract: String,
+        holder: String,
+        balance: u128,
+        amount: u128,
+    },
+
+    #[error("A token balance of {0} would overflow")]
+    TokenOverflow(String),
+
+    #[error("Smart contract storage error")]
+    Store(#[from] StoreError),
+}
+
+impl VmError {
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            VmError::AccountExists(_) => 934823094,
+            VmError::ContractExists(_) => 934823095,
+            VmError::UnknownFunction(_) => 8402938471,
+            VmError::InvalidArguments(_) => 8402938472,
+            VmError::Execution(_) => 8402938473,
+            VmError::InsufficientTokens { .. } => 8402938474,
+            VmError::TokenOverflow(_) => 8402938475,
+            VmError::Store(err) => err.code(),
+        }
+    }
+}
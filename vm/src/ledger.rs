@@ -0,0 +1,122 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::VmError;
+
+/// The token balances of every contract, keyed by contract and holder. Holders are
+/// indexed per contract and contracts per holder, so neither has to scan the balances.
+/// Zero balances are not kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenLedger {
+    balances: HashMap<(String, String), u128>,
+    /// The holders of every contract
+    holders: HashMap<String, BTreeSet<String>>,
+    /// The contracts every holder has a balance of
+    holdings: HashMap<String, BTreeSet<String>>,
+}
+
+impl TokenLedger {
+    /// Will return the balance of the holder, 0 if it holds none of the tokens
+    pub fn balance(&self, contract: &str, holder: &str) -> u128 {
+        let key = (contract.to_string(), holder.to_string());
+        self.balances.get(&key).copied().unwrap_or_default()
+    }
+
+    /// Will return the holders of the contract's tokens with their balances, sorted by holder
+    pub fn holders<'a>(&'a self, contract: &'a str) -> impl Iterator<Item = (&'a str, u128)> {
+        self.holders
+            .get(contract)
+            .into_iter()
+            .flatten()
+            .map(move |holder| (holder.as_str(), self.balance(contract, holder)))
+    }
+
+    /// Will return the contracts the holder has tokens of with its balances, sorted by contract
+    pub fn tokens<'a>(&'a self, holder: &'a str) -> impl Iterator<Item = (&'a str, u128)> {
+        self.holdings
+            .get(holder)
+            .into_iter()
+            .flatten()
+            .map(move |contract| (contract.as_str(), self.balance(contract, holder)))
+    }
+
+    /// True if nobody holds any tokens
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+    }
+
+    /// Will add the amount to the holder's balance
+    pub fn credit(&mut self, contract: &str, holder: &str, amount: u128) -> Result<(), VmError> {
+        let balance = self
+            .balance(contract, holder)
+            .checked_add(amount)
+            .ok_or_else(|| VmError::TokenOverflow(contract.to_string()))?;
+        self.set(contract, holder, balance);
+
+        Ok(())
+    }
+
+    /// Will take the amount from the holder's balance
+    pub fn debit(&mut self, contract: &str, holder: &str, amount: u128) -> Result<(), VmError> {
+        let balance = self.balance(contract, holder);
+        let rest = balance
+            .checked_sub(amount)
+            .ok_or_else(|| VmError::InsufficientTokens {
+                contract: contract.to_string(),
+                holder: holder.to_string(),
+                balance,
+                amount,
+            })?;
+        self.set(contract, holder, rest);
+
+        Ok(())
+    }
+
+    /// Will move the amount between the holders, nothing changes if it fails
+    pub fn transfer(
+        &mut self,
+        contract: &str,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<(), VmError> {
+        if self.balance(contract, to).checked_add(amount).is_none() && from != to {
+            return Err(VmError::TokenOverflow(contract.to_string()));
+        }
+
+        self.debit(contract, from, amount)?;
+        self.credit(contract, to, amount)
+    }
+
+    /// Will set the balance and keep the indexes in line
+    fn set(&mut self, contract: &str, holder: &str, balance: u128) {
+        let key = (contract.to_string(), holder.to_string());
+        if balance > 0 {
+            self.balances.insert(key, balance);
+            self.holders
+                .entry(contract.to_string())
+                .or_default()
+                .insert(holder.to_string());
+            self.holdings
+                .entry(holder.to_string())
+                .or_default()
+                .insert(contract.to_string());
+            return;
+        }
+
+        if self.balances.remove(&key).is_none() {
+            return;
+        }
+        remove_from(&mut self.holders, contract, holder);
+        remove_from(&mut self.holdings, holder, contract);
+    }
+}
+
+/// Will remove the value from the index, dropping the set once it is empty
+fn remove_from(index: &mut HashMap<String, BTreeSet<String>>, key: &str, value: &str) {
+    if let Some(values) = index.get_mut(key) {
+        values.remove(value);
+        if values.is_empty() {
+            index.remove(key);
+        }
+    }
+}
@@ -1,9 +1,14 @@
+mod error;
+pub mod ledger;
 pub mod smart_contract;
 use std::collections::HashMap;
 
-use eternal_account::{Account, AccountType};
+use eternal_account::Account;
+use ledger::TokenLedger;
 use smart_contract::SmartContract as SC;
 
+pub use error::VmError;
+
 pub trait WorldState {
     /// Will bring us all registered user ids
     fn get_user_ids(&self) -> Vec<String>;
@@ -14,8 +19,8 @@ pub trait WorldState {
     /// Will return an account given it id if is available
     fn get_account_by_id(&self, id: &String) -> Option<&Account>;
 
-    /// Will add a new account
-    fn create_account(&mut self, account_type: AccountType) -> Result<String, &'static str>;
+    /// Will add the account under its address, failing if the address is taken
+    fn create_account(&mut self, account: Account) -> Result<String, VmError>;
 
     fn get_smart_contact_ids(&self) -> Vec<String>;
 
@@ -26,14 +31,17 @@ pub trait WorldState {
     fn get_smart_contact_by_id(&self, id: &String) -> Option<&SC>;
 
     /// Will add a new account
-    fn create_smart_contact(
-        &mut self,
-        account_type: SC,
-    ) -> Result<String, &'static str>;
+    fn create_smart_contact(&mut self, account_type: SC) -> Result<String, VmError>;
 
     fn get_accounts(&mut self) -> &mut HashMap<String, Account>;
 
     fn get_smart_contacts(&mut self) -> &mut HashMap<String, SC>;
+
+    /// Will return the token balances of every contract
+    fn get_token_ledger(&mut self) -> &mut TokenLedger;
+
+    /// Will return the height of the block being executed
+    fn block_height(&self) -> u64;
 }
 
 pub trait SmartContract {
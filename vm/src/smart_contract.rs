@@ -1,5 +1,8 @@
+use eternal_account::{Store, StoreError};
 use serde::{Deserialize, Serialize};
 
+use crate::VmError;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SmartContractStanderd {
     ESC20,
@@ -11,7 +14,12 @@ pub enum SmartContractApi {
     ESC20 {
         publisher: String,
         total_suply: u128,
-        transfer: fn(from: String, to: String, amount: u128) -> Result<(), String>,
+        transfer: fn(
+            storage: &mut ContractStorage,
+            from: String,
+            to: String,
+            amount: u128,
+        ) -> Result<(), VmError>,
     },
 }
 
@@ -43,19 +51,59 @@ impl SmartContract {
         SmartContract { r#type, api }
     }
 
-    pub fn execute_fn(&mut self, fun: &str, params: Vec<&str>) {
+    pub fn execute_fn(
+        &mut self,
+        storage: &mut ContractStorage,
+        fun: &str,
+        params: Vec<&str>,
+    ) -> Result<(), VmError> {
         match fun {
             "transfer" => match self.api {
                 SmartContractApi::ESC20 { transfer, .. } => {
-                    transfer(
-                        params[0].to_string(),
-                        params[1].to_string(),
-                        params[2].parse().unwrap(),
-                    )
-                    .unwrap();
+                    let [from, to, amount] = params[..] else {
+                        return Err(VmError::InvalidArguments(fun.to_string()));
+                    };
+                    let amount = amount
+                        .parse()
+                        .map_err(|_| VmError::InvalidArguments(fun.to_string()))?;
+
+                    transfer(storage, from.to_string(), to.to_string(), amount)
                 }
             },
-            _ => unreachable!(),
+            _ => Err(VmError::UnknownFunction(fun.to_string())),
         }
     }
 }
+
+/// The store of the calling account as seen by a contract. Writes go through [`Store`]
+/// with the contract's address as caller, so a contract can only change its own namespace.
+pub struct ContractStorage<'a> {
+    contract: &'a str,
+    store: &'a mut Store,
+}
+
+impl<'a> ContractStorage<'a> {
+    pub fn new(contract: &'a str, store: &'a mut Store) -> Self {
+        Self { contract, store }
+    }
+
+    /// Will return the address of the called contract
+    pub fn contract(&self) -> &str {
+        self.contract
+    }
+
+    /// Will return the value stored under the key of any namespace
+    pub fn get_bytes(&self, namespace: &str, key: &[u8]) -> Option<&[u8]> {
+        self.store.get_bytes(namespace, key)
+    }
+
+    /// Will store the value under the key, fails unless the contract owns the namespace
+    pub fn set_bytes(
+        &mut self,
+        namespace: &str,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<(), StoreError> {
+        self.store.set_bytes(self.contract, namespace, key, value)
+    }
+}
@@ -0,0 +1,74 @@
+use std::io;
+
+use eternal_account::KeyError;
+use eternal_core::error::{RawError, TxError};
+use eternal_networking::jsonrpc::ClientError;
+use rustyline::error::ReadlineError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WalletError {
+    #[error("IO error")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid JSON")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid raw transaction")]
+    Raw(#[from] RawError),
+
+    #[error("Invalid transaction")]
+    Invalid(#[from] TxError),
+
+    #[error("Key error")]
+    Key(#[from] KeyError),
+
+    #[error("RPC request failed")]
+    Rpc(#[from] ClientError),
+
+    #[error("`{0}` is neither a keystore nor an address")]
+    UnknownKey(String),
+
+    #[error("A keystore named `{0}` already exists")]
+    KeyExists(String),
+
+    #[error("`--{0}` is required")]
+    MissingArgument(&'static str),
+
+    #[error("The transaction `{0}` was not included in time")]
+    NotIncluded(String),
+
+    #[error("Can not read the input")]
+    Readline(#[from] ReadlineError),
+}
+
+impl WalletError {
+    /// Will return the message of the error followed by the messages of its sources
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            report.push_str(": ");
+            report.push_str(&err.to_string());
+            source = err.source();
+        }
+        report
+    }
+
+    /// Will return the stable numeric code of the error
+    pub fn code(&self) -> u64 {
+        match self {
+            WalletError::Io(_) => 2290384101,
+            WalletError::Json(_) => 2290384102,
+            WalletError::Raw(err) => err.code(),
+            WalletError::Invalid(err) => err.code(),
+            WalletError::Key(err) => err.code(),
+            WalletError::Rpc(err) => err.code(),
+            WalletError::UnknownKey(_) => 2290384103,
+            WalletError::KeyExists(_) => 2290384104,
+            WalletError::MissingArgument(_) => 2290384105,
+            WalletError::NotIncluded(_) => 2290384106,
+            WalletError::Readline(_) => 2290384107,
+        }
+    }
+}
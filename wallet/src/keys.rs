@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eternal_account::{Address, Keystore, Wallet};
+use zeroize::Zeroizing;
+
+use crate::error::WalletError;
+
+/// A directory of keystores, each stored as `<name>.json`
+#[derive(Debug, Clone)]
+pub struct KeyDir {
+    dir: PathBuf,
+}
+
+impl KeyDir {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Will return the names and keystores, sorted by name
+    pub fn list(&self) -> Result<Vec<(String, Keystore)>, WalletError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut keystores = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            // Other JSON files in the directory are skipped
+            let (Some(name), Ok(keystore)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                Keystore::load(&path),
+            ) else {
+                continue;
+            };
+            keystores.push((name.to_string(), keystore));
+        }
+        keystores.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(keystores)
+    }
+
+    pub fn load(&self, name: &str) -> Result<Keystore, WalletError> {
+        let path = self.path(name);
+        if !path.exists() {
+            return Err(WalletError::UnknownKey(name.to_string()));
+        }
+
+        Ok(Keystore::load(path)?)
+    }
+
+    /// Will encrypt the wallet into a new keystore, existing keystores are never overwritten
+    pub fn save(&self, name: &str, wallet: &Wallet) -> Result<Keystore, WalletError> {
+        let path = self.path(name);
+        if path.exists() {
+            return Err(WalletError::KeyExists(name.to_string()));
+        }
+
+        let password = read_secret("ETERNAL_PASSWORD", &format!("New password for {}: ", name))?;
+        let keystore = Keystore::encrypt(wallet, &password)?;
+        fs::create_dir_all(&self.dir)?;
+        keystore.save(path)?;
+
+        Ok(keystore)
+    }
+
+    /// Will decrypt the keystore, asking for its password
+    pub fn unlock(&self, name: &str) -> Result<Wallet, WalletError> {
+        let keystore = self.load(name)?;
+        let password = read_secret("ETERNAL_PASSWORD", &format!("Password for {}: ", name))?;
+
+        Ok(keystore.decrypt(&password)?)
+    }
+
+    /// Will return the address of a keystore name or the address itself
+    pub fn resolve(&self, name_or_address: &str) -> Result<String, WalletError> {
+        if let Ok(address) = name_or_address.parse::<Address>() {
+            return Ok(address.to_string());
+        }
+
+        Ok(self.load(name_or_address)?.address)
+    }
+}
+
+/// Will read a secret from the environment variable or ask for it on the terminal
+pub fn read_secret(var: &str, prompt: &str) -> Result<Zeroizing<String>, WalletError> {
+    if let Ok(secret) = std::env::var(var) {
+        return Ok(Zeroizing::new(secret));
+    }
+
+    Ok(Zeroizing::new(rpassword::prompt_password(prompt)?))
+}
@@ -0,0 +1,379 @@
+mod error;
+mod keys;
+mod repl;
+mod tx;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    process, thread,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use clap::{Parser, Subcommand};
+use eternal_account::{
+    hd::parse_mnemonic, secp256k1::PublicKey, verify_signature, Account, Address, HdWallet, Wallet,
+};
+use eternal_core::{
+    error::TxError, index::Receipt, limits::Limits, raw, time::now_millis, transaction::Transaction,
+};
+use eternal_networking::jsonrpc::Client;
+use serde_json::json;
+
+use crate::{
+    error::WalletError,
+    keys::{read_secret, KeyDir},
+    tx::{Format, TxKind, TxOptions},
+};
+
+/// How long to wait for a transaction to be included
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(60);
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "eternal-wallet",
+    version,
+    about = "Manages keys and builds, signs and sends transactions"
+)]
+struct Cli {
+    /// Directory of the keystores
+    #[arg(long, env = "ETERNAL_KEYSTORE_DIR", default_value = "keystores")]
+    keystore_dir: PathBuf,
+
+    /// JSON-RPC address of the node
+    #[arg(long, env = "ETERNAL_RPC", default_value = "127.0.0.1:8545")]
+    rpc: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Creates a new key
+    New { name: String },
+    /// Imports a private key, or a mnemonic with `--mnemonic`
+    Import {
+        name: String,
+        #[arg(long)]
+        mnemonic: bool,
+        /// Account index derived from the mnemonic
+        #[arg(long, default_value_t = 0)]
+        index: u32,
+    },
+    /// Lists the keystores
+    List,
+    /// Shows the balance and token balances of a keystore or address
+    Balance { account: String },
+    /// Builds a transaction, signs it with the sender's key and sends it
+    ///
+    /// Tokens are only minted by the allocations of the chain spec. Smart contracts hold
+    /// native code, so they are deployed by the chain spec as well and can not be sent.
+    Tx {
+        #[command(subcommand)]
+        kind: TxKind,
+        #[command(flatten)]
+        options: TxOptions,
+    },
+    /// Adds a signature to a transaction file, e.g. of a multisig owner
+    Sign {
+        file: PathBuf,
+        /// Keystore name of the signer
+        #[arg(long)]
+        key: String,
+        /// File to write the signed transaction to, the input file if omitted
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Encoding of the signed transaction, the one of the input if omitted
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Prints and checks a transaction without a node
+    Decode {
+        /// Hex or base64 raw transaction, or a file containing one or JSON
+        input: String,
+        /// Also checks that the transaction is meant for this chain
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+    /// Sends a signed transaction file, JSON or raw
+    Send {
+        file: PathBuf,
+        /// Returns right after submitting instead of waiting for the receipt
+        #[arg(long)]
+        no_wait: bool,
+    },
+    /// Shows the receipt of a transaction
+    Receipt { hash: String },
+    /// Starts an interactive shell accepting the same commands
+    Repl,
+}
+
+/// What every command works with
+pub struct Context {
+    keys: KeyDir,
+    client: Client,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let context = Context {
+        keys: KeyDir::new(&cli.keystore_dir),
+        client: Client::new(&cli.rpc),
+    };
+
+    let result = match cli.command {
+        Command::Repl => repl::run(&context),
+        command => execute(&context, command),
+    };
+    if let Err(err) = result {
+        eprintln!("{} ({})", err.report(), err.code());
+        process::exit(1);
+    }
+}
+
+fn execute(context: &Context, command: Command) -> Result<(), WalletError> {
+    let Context { keys, client } = context;
+
+    match command {
+        Command::New { name } => {
+            let keystore = keys.save(&name, &Wallet::generate())?;
+            println!("{}  {}", name, keystore.address);
+        }
+        Command::Import {
+            name,
+            mnemonic,
+            index,
+        } => {
+            let wallet = if mnemonic {
+                let phrase = read_secret("ETERNAL_MNEMONIC", "Mnemonic: ")?;
+                HdWallet::from_mnemonic(&parse_mnemonic(&phrase)?, "")?.account(index)?
+            } else {
+                let private_key = read_secret("ETERNAL_PRIVATE_KEY", "Private key: ")?;
+                Wallet::from_private_key(private_key.trim())?
+            };
+
+            let keystore = keys.save(&name, &wallet)?;
+            println!("{}  {}", name, keystore.address);
+        }
+        Command::List => {
+            for (name, keystore) in keys.list()? {
+                println!("{}  {}", name, keystore.address);
+            }
+        }
+        Command::Balance { account } => {
+            let address = keys.resolve(&account)?;
+            let Some(account) =
+                client.call::<Option<Account>>("eternal_getAccount", json!([address]))?
+            else {
+                println!("{}  unknown account", address);
+                return Ok(());
+            };
+
+            println!("{}", address);
+            println!("  tokens  {}", account.tokens);
+            let balances: BTreeMap<String, String> =
+                client.call("eternal_getTokenBalances", json!([address]))?;
+            for (contract, balance) in balances {
+                println!("  {}  {}", contract, balance);
+            }
+        }
+        Command::Tx { kind, options } => {
+            let data = kind.data(keys)?;
+            let sender = options
+                .from
+                .clone()
+                .ok_or(WalletError::MissingArgument("from"))?;
+            let from = keys.resolve(&sender)?;
+
+            let offline = options.offline || options.unsigned;
+            let chain_id = match options.chain_id {
+                Some(chain_id) => chain_id,
+                None if offline => return Err(WalletError::MissingArgument("chain-id")),
+                None => client.call("eternal_chainId", json!([]))?,
+            };
+            let nonce = match options.nonce {
+                Some(nonce) => nonce,
+                None if offline => return Err(WalletError::MissingArgument("nonce")),
+                None => {
+                    client.call::<u64>("eternal_getTransactionCount", json!([from]))? as u128 + 1
+                }
+            };
+
+            let mut transaction = Transaction::new(chain_id, from, data, nonce);
+            if let Some(valid_until) = options.valid_until {
+                transaction = transaction.with_valid_until(valid_until);
+            }
+            if !options.unsigned {
+                transaction.sign(&keys.unlock(&sender)?);
+            }
+
+            if offline {
+                write_transaction(&transaction, options.out.as_ref(), options.format)?;
+            } else {
+                send(client, &transaction, !options.no_wait)?;
+            }
+        }
+        Command::Sign {
+            file,
+            key,
+            out,
+            format,
+        } => {
+            let (mut transaction, input_format) = read_transaction(&file)?;
+            transaction.sign(&keys.unlock(&key)?);
+            write_transaction(
+                &transaction,
+                Some(out.as_ref().unwrap_or(&file)),
+                format.unwrap_or(input_format),
+            )?;
+        }
+        Command::Decode { input, chain_id } => {
+            let path = PathBuf::from(&input);
+            let transaction = if path.is_file() {
+                read_transaction(&path)?.0
+            } else {
+                raw::from_text(&input)?
+            };
+            decode(&transaction, chain_id)?;
+        }
+        Command::Send { file, no_wait } => {
+            send(client, &read_transaction(&file)?.0, !no_wait)?;
+        }
+        Command::Receipt { hash } => {
+            match client.call::<Option<Receipt>>("eternal_getTransactionReceipt", json!([hash]))? {
+                Some(receipt) => print_receipt(&receipt),
+                None => println!("{}  not included", hash),
+            }
+        }
+        Command::Repl => {}
+    }
+
+    Ok(())
+}
+
+/// Will submit the transaction in its raw form and print its receipt once it is included
+fn send(client: &Client, transaction: &Transaction, wait: bool) -> Result<(), WalletError> {
+    let raw = raw::to_hex(transaction)?;
+    let hash: String = client.call("eternal_sendRawTransaction", json!([raw]))?;
+    println!("{}  submitted", hash);
+    if !wait {
+        return Ok(());
+    }
+
+    let mut waited = Duration::ZERO;
+    while waited < RECEIPT_TIMEOUT {
+        if let Some(receipt) =
+            client.call::<Option<Receipt>>("eternal_getTransactionReceipt", json!([hash]))?
+        {
+            print_receipt(&receipt);
+            return Ok(());
+        }
+
+        thread::sleep(RECEIPT_POLL_INTERVAL);
+        waited += RECEIPT_POLL_INTERVAL;
+    }
+
+    Err(WalletError::NotIncluded(hash))
+}
+
+fn print_receipt(receipt: &Receipt) {
+    println!("{}  included", receipt.transaction_hash);
+    println!("  block   {}", receipt.block_hash);
+    println!("  height  {}", receipt.location.height);
+    println!("  index   {}", receipt.location.index);
+    println!("  time    {}", receipt.timestamp);
+}
+
+/// Will print the fields of the transaction and check everything that can be checked
+/// without the chain state, against the default limits
+fn decode(transaction: &Transaction, chain_id: Option<u64>) -> Result<(), WalletError> {
+    let hash = transaction.calculate_hash();
+    let created_at = transaction
+        .created_at
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+
+    println!("{}", transaction.hash());
+    println!("  version      {}", raw::RAW_VERSION);
+    println!("  size         {} bytes", raw::encode(transaction)?.len());
+    println!("  chain id     {}", transaction.chain_id);
+    println!("  from         {}", transaction.from);
+    println!("  nonce        {}", transaction.nonce);
+    println!("  created at   {}", created_at);
+    match transaction.valid_until {
+        Some(valid_until) => println!("  valid until  {}", valid_until),
+        None => println!("  valid until  -"),
+    }
+    let data = serde_json::to_string_pretty(&transaction.data)?;
+    println!("  data         {}", data.replace('\n', "\n               "));
+    if !transaction.is_signed() {
+        println!("  signatures   none");
+    }
+    for signature in &transaction.signatures {
+        let Ok(public_key) = signature.public_key.parse::<PublicKey>() else {
+            println!("  signed by    {}  invalid key", signature.public_key);
+            continue;
+        };
+        let signer = Address::from_public_key_bytes(&public_key.serialize());
+        let valid = verify_signature(&public_key, &hash, &signature.signature);
+        println!(
+            "  signed by    {}  {}",
+            signer,
+            if valid { "valid" } else { "invalid" }
+        );
+    }
+
+    let result = match chain_id {
+        Some(expected) if expected != transaction.chain_id => Err(TxError::WrongChain {
+            expected,
+            actual: transaction.chain_id,
+        }),
+        _ => raw::check(transaction, &Limits::default(), now_millis()),
+    };
+    match result {
+        Ok(()) => {
+            println!("  status       ok");
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Will read a transaction file, JSON or a raw transaction as hex or base64
+fn read_transaction(file: &PathBuf) -> Result<(Transaction, Format), WalletError> {
+    let text = fs::read_to_string(file)?;
+    let text = text.trim();
+
+    if text.starts_with('{') {
+        Ok((serde_json::from_str(text)?, Format::Json))
+    } else if text.starts_with("0x") {
+        Ok((raw::from_hex(text)?, Format::Hex))
+    } else {
+        Ok((raw::from_base64(text)?, Format::Base64))
+    }
+}
+
+fn write_transaction(
+    transaction: &Transaction,
+    out: Option<&PathBuf>,
+    format: Format,
+) -> Result<(), WalletError> {
+    let text = match format {
+        Format::Json => serde_json::to_string_pretty(transaction)?,
+        Format::Hex => raw::to_hex(transaction)?,
+        Format::Base64 => raw::to_base64(transaction)?,
+    };
+    match out {
+        Some(path) => {
+            fs::write(path, text)?;
+            println!("{}  written to {}", transaction.hash(), path.display());
+        }
+        None => println!("{}", text),
+    }
+
+    Ok(())
+}
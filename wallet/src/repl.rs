@@ -0,0 +1,142 @@
+use clap::{CommandFactory, Parser};
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context as LineContext, Editor, Helper,
+};
+use serde_json::json;
+
+use crate::{error::WalletError, execute, Command, Context};
+
+/// A line of the REPL, the commands of the CLI without the binary name
+#[derive(Debug, Parser)]
+#[command(no_binary_name = true, name = "", disable_version_flag = true)]
+struct Line {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Completes commands, keystore names, their addresses and contract addresses
+#[derive(Default)]
+struct WalletHelper {
+    words: Vec<String>,
+}
+
+impl Helper for WalletHelper {}
+
+impl Hinter for WalletHelper {
+    type Hint = String;
+}
+
+impl Highlighter for WalletHelper {}
+
+impl Validator for WalletHelper {}
+
+impl Completer for WalletHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &LineContext<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(' ').map_or(0, |i| i + 1);
+        let prefix = &line[start..pos];
+
+        let candidates = self
+            .words
+            .iter()
+            .filter(|word| word.starts_with(prefix))
+            .map(|word| Pair {
+                display: word.clone(),
+                replacement: word.clone(),
+            })
+            .collect();
+
+        Ok((start, candidates))
+    }
+}
+
+impl WalletHelper {
+    /// Will collect the words to complete, contracts are left out if the node is unreachable
+    fn refresh(&mut self, context: &Context) {
+        let mut words = vec!["exit".to_string()];
+        let cli = Line::command();
+        for command in cli.get_subcommands() {
+            words.push(command.get_name().to_string());
+            words.extend(command.get_subcommands().map(|c| c.get_name().to_string()));
+        }
+
+        if let Ok(keystores) = context.keys.list() {
+            for (name, keystore) in keystores {
+                words.push(name);
+                words.push(keystore.address);
+            }
+        }
+        if let Ok(contracts) = context
+            .client
+            .call::<Vec<String>>("eternal_getContracts", json!([]))
+        {
+            words.extend(contracts);
+        }
+
+        words.sort();
+        words.dedup();
+        self.words = words;
+    }
+}
+
+/// Will read and execute commands until `exit` or EOF, errors are printed and do not end the REPL
+pub fn run(context: &Context) -> Result<(), WalletError> {
+    let mut editor = Editor::new()?;
+    let mut helper = WalletHelper::default();
+    helper.refresh(context);
+    editor.set_helper(Some(helper));
+
+    loop {
+        let line = match editor.readline("eternal> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        if line == "exit" || line == "quit" {
+            return Ok(());
+        }
+
+        let words = match shell_words::split(line) {
+            Ok(words) => words,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+        match Line::try_parse_from(words) {
+            Ok(Line {
+                command: Command::Repl,
+            }) => {}
+            Ok(Line { command }) => {
+                if let Err(err) = execute(context, command) {
+                    eprintln!("{} ({})", err.report(), err.code());
+                }
+            }
+            // Also covers `help`
+            Err(err) => {
+                let _ = err.print();
+            }
+        }
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh(context);
+        }
+    }
+}
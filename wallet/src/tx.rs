@@ -0,0 +1,139 @@
+use std::{fs, path::PathBuf};
+
+use clap::{Args, Subcommand, ValueEnum};
+use eternal_core::{slashing::Evidence, transaction::TransactionData};
+
+use crate::{error::WalletError, keys::KeyDir};
+
+/// The transactions the wallet can build. Genesis transactions and token mints only come
+/// from the chain spec and contract deployments can not be sent to a node, so they are
+/// missing.
+#[derive(Debug, Clone, Subcommand)]
+pub enum TxKind {
+    /// Sends tokens
+    Transfer { to: String, amount: u128 },
+    /// Sends tokens of a contract
+    TransferToken {
+        token: String,
+        to: String,
+        amount: u128,
+    },
+    /// Registers the account of a public key
+    CreateAccount { public_key: String },
+    /// Creates an account controlled by several keys
+    CreateMultisig {
+        /// Public key of an owner, repeat for every owner
+        #[arg(long = "owner", required = true)]
+        public_keys: Vec<String>,
+        /// Signatures needed to authorize a transaction
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Sets a value of the sender's store
+    SetStore { key: String, value: String },
+    /// Moves tokens into stake
+    Stake { amount: u128 },
+    /// Moves staked tokens back into the balance
+    Unstake { amount: u128 },
+    /// Reports a misbehaving validator
+    Evidence {
+        /// JSON file of the evidence
+        file: PathBuf,
+    },
+    /// Votes to add an authority, or to remove it with `--remove`
+    VoteAuthority {
+        public_key: String,
+        #[arg(long)]
+        remove: bool,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct TxOptions {
+    /// Keystore name or address of the sender
+    #[arg(long, global = true)]
+    pub from: Option<String>,
+
+    /// Defaults to one more than the number of transactions sent so far
+    #[arg(long, global = true)]
+    pub nonce: Option<u128>,
+
+    /// Defaults to the chain id of the node
+    #[arg(long, global = true)]
+    pub chain_id: Option<u64>,
+
+    /// Unix timestamp in milliseconds after which the transaction is dropped
+    #[arg(long, global = true)]
+    pub valid_until: Option<u64>,
+
+    /// Writes the transaction instead of sending it, needs `--chain-id` and `--nonce`
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Leaves the transaction unsigned, e.g. to collect multisig signatures. Implies `--offline`
+    #[arg(long, global = true)]
+    pub unsigned: bool,
+
+    /// File to write an offline transaction to, stdout if omitted
+    #[arg(short, long, global = true)]
+    pub out: Option<PathBuf>,
+
+    /// Encoding of an offline transaction
+    #[arg(long, global = true, value_enum, default_value_t = Format::Json)]
+    pub format: Format,
+
+    /// Returns right after submitting instead of waiting for the receipt
+    #[arg(long, global = true)]
+    pub no_wait: bool,
+}
+
+/// How a transaction is written to a file, see `eternal_core::raw` for the raw forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Pretty printed JSON, readable before signing
+    Json,
+    /// Raw transaction as `0x` prefixed hex
+    Hex,
+    /// Raw transaction as base64
+    Base64,
+}
+
+impl TxKind {
+    /// Will build the transaction data, keystore names are resolved to their addresses
+    pub fn data(&self, keys: &KeyDir) -> Result<TransactionData, WalletError> {
+        let data = match self.clone() {
+            TxKind::Transfer { to, amount } => TransactionData::Transfer {
+                to: keys.resolve(&to)?,
+                amount,
+            },
+            TxKind::TransferToken { token, to, amount } => TransactionData::TransferToken {
+                token: keys.resolve(&token)?,
+                to: keys.resolve(&to)?,
+                amount,
+            },
+            TxKind::CreateAccount { public_key } => {
+                TransactionData::CreateUserAccount { public_key }
+            }
+            TxKind::CreateMultisig {
+                public_keys,
+                threshold,
+            } => TransactionData::CreateMultisigAccount {
+                public_keys,
+                threshold,
+            },
+            TxKind::SetStore { key, value } => TransactionData::ChangeStoreValue { key, value },
+            TxKind::Stake { amount } => TransactionData::Stake { amount },
+            TxKind::Unstake { amount } => TransactionData::Unstake { amount },
+            TxKind::Evidence { file } => {
+                let evidence: Evidence = serde_json::from_str(&fs::read_to_string(file)?)?;
+                TransactionData::SubmitEvidence(evidence)
+            }
+            TxKind::VoteAuthority { public_key, remove } => TransactionData::VoteAuthority {
+                public_key,
+                add: !remove,
+            },
+        };
+
+        Ok(data)
+    }
+}